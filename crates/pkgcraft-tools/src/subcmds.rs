@@ -5,6 +5,7 @@ mod cpv;
 mod dep;
 mod pkg;
 mod repo;
+mod search;
 mod version;
 
 #[derive(clap::Subcommand)]
@@ -20,6 +21,8 @@ pub(crate) enum Subcommand {
     Pkg(pkg::Command),
     /// Repository commands
     Repo(repo::Command),
+    /// Search packages
+    Search(search::Command),
     /// Version commands
     Version(version::Command),
 }
@@ -31,6 +34,7 @@ impl Subcommand {
             Self::Dep(cmd) => cmd.run(),
             Self::Pkg(cmd) => cmd.run(args.load_config()?),
             Self::Repo(cmd) => cmd.run(args.load_config()?),
+            Self::Search(cmd) => cmd.run(args.load_config()?),
             Self::Completion(cmd) => cmd.run(),
             Self::Version(cmd) => cmd.run(),
         }
@@ -3,10 +3,13 @@ use std::sync::OnceLock;
 
 use pkgcraft::config::Config;
 
+mod depgraph;
+mod distclean;
 mod env;
 mod fetch;
 mod manifest;
 mod metadata;
+mod mirror;
 mod pretend;
 mod revdeps;
 mod showkw;
@@ -27,6 +30,10 @@ impl Command {
 #[derive(clap::Subcommand)]
 #[allow(clippy::large_enum_variant)]
 enum Subcommand {
+    /// Output dependency graph
+    Depgraph(depgraph::Command),
+    /// Clean distfile directory
+    Distclean(distclean::Command),
     /// Output ebuild environment
     Env(env::Command),
     /// Fetch distfiles
@@ -35,6 +42,8 @@ enum Subcommand {
     Manifest(manifest::Command),
     /// Manipulate package metadata
     Metadata(metadata::Command),
+    /// Populate a local distfile mirror
+    Mirror(mirror::Command),
     /// Run the pkg_pretend phase
     Pretend(pretend::Command),
     /// Output reverse dependencies
@@ -48,10 +57,13 @@ enum Subcommand {
 impl Subcommand {
     fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
         match self {
+            Self::Depgraph(cmd) => cmd.run(config),
+            Self::Distclean(cmd) => cmd.run(config),
             Self::Env(cmd) => cmd.run(config),
             Self::Fetch(cmd) => cmd.run(config),
             Self::Manifest(cmd) => cmd.run(config),
             Self::Metadata(cmd) => cmd.run(config),
+            Self::Mirror(cmd) => cmd.run(config),
             Self::Pretend(cmd) => cmd.run(config),
             Self::Revdeps(cmd) => cmd.run(config),
             Self::Showkw(cmd) => cmd.run(config),
@@ -0,0 +1,344 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use anyhow::anyhow;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, ValueEnum};
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use pkgcraft::cli::{MaybeStdinVec, Targets};
+use pkgcraft::config::Config;
+use pkgcraft::dep::{Dep, DependencySet, Evaluate};
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::metadata::Key;
+use pkgcraft::repo::ebuild::EbuildRepo;
+use pkgcraft::repo::{PkgRepository, RepoFormat, Repository};
+use serde::Serialize;
+
+/// Dependency keys that can be followed.
+static DEP_KEYS: [Key; 5] =
+    [Key::BDEPEND, Key::DEPEND, Key::IDEPEND, Key::PDEPEND, Key::RDEPEND];
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Format {
+    /// Indented tree output
+    Tree,
+    /// DOT graph output
+    Dot,
+    /// JSON output
+    Json,
+}
+
+#[derive(Args)]
+#[clap(next_help_heading = "Depgraph options")]
+pub(crate) struct Command {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Tree)]
+    format: Format,
+
+    /// Dependency keys to follow
+    #[arg(
+        short,
+        long,
+        value_name = "KEY",
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(DEP_KEYS.iter().map(|k| k.as_ref()))
+            .map(|s| s.parse::<Key>().unwrap()),
+    )]
+    keys: Vec<Key>,
+
+    /// Evaluate USE conditionals using a USE configuration
+    ///
+    /// Flags are enabled on top of IUSE defaults with "-" prefixed flags
+    /// disabled. Without this option, all conditional dependencies are
+    /// followed.
+    #[arg(
+        short,
+        long,
+        value_name = "FLAGS",
+        value_delimiter = ',',
+        allow_hyphen_values = true
+    )]
+    r#use: Option<Vec<String>>,
+
+    /// Maximum depth to recurse
+    #[arg(short, long)]
+    depth: Option<usize>,
+
+    /// Target repo
+    #[arg(short, long)]
+    repo: Option<String>,
+
+    // positionals
+    /// Target packages or paths
+    #[arg(value_name = "TARGET", default_value = ".", help_heading = "Arguments")]
+    targets: Vec<MaybeStdinVec<String>>,
+}
+
+/// Dependency edge between packages.
+#[derive(Debug, Serialize)]
+struct Edge {
+    key: String,
+    dep: String,
+    target: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cycle: bool,
+}
+
+/// Forward dependency graph.
+#[derive(Debug, Default, Serialize)]
+struct Graph {
+    targets: Vec<String>,
+    pkgs: IndexMap<String, Vec<Edge>>,
+    cycles: Vec<Vec<String>>,
+}
+
+/// Dependency graph builder.
+struct Builder<'a> {
+    repos: &'a [EbuildRepo],
+    keys: IndexSet<Key>,
+    enabled: Option<IndexSet<String>>,
+    disabled: IndexSet<String>,
+    depth: usize,
+    depths: IndexMap<String, usize>,
+    graph: Graph,
+    stack: IndexSet<String>,
+}
+
+impl Builder<'_> {
+    /// Return the best matching package for a dependency across all repos.
+    ///
+    /// Higher versions take precedence and repo order breaks ties.
+    fn resolve(&self, dep: &Dep) -> pkgcraft::Result<Option<EbuildPkg>> {
+        let mut best: Option<EbuildPkg> = None;
+        for repo in self.repos {
+            for pkg in repo.iter_restrict(dep) {
+                let pkg = pkg?;
+                if best.as_ref().is_none_or(|x| pkg.cpv() > x.cpv()) {
+                    best = Some(pkg);
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    /// Return the dependencies of a package, optionally evaluating USE conditionals.
+    fn deps(&self, pkg: &EbuildPkg) -> Vec<(Key, Dep)> {
+        let mut deps = vec![];
+        for key in &self.keys {
+            let set: &DependencySet<Dep> = match key {
+                Key::BDEPEND => pkg.bdepend(),
+                Key::DEPEND => pkg.depend(),
+                Key::IDEPEND => pkg.idepend(),
+                Key::PDEPEND => pkg.pdepend(),
+                Key::RDEPEND => pkg.rdepend(),
+                _ => continue,
+            };
+
+            if let Some(enabled) = &self.enabled {
+                let options: IndexSet<_> = pkg
+                    .iuse()
+                    .iter()
+                    .filter(|x| x.is_enabled())
+                    .map(|x| x.flag().to_string())
+                    .chain(enabled.iter().cloned())
+                    .filter(|x| !self.disabled.contains(x))
+                    .collect();
+                deps.extend(
+                    set.evaluate(&options)
+                        .iter_flatten()
+                        .map(|x| (*key, (*x).clone())),
+                );
+            } else {
+                deps.extend(set.iter_flatten().map(|x| (*key, x.clone())));
+            }
+        }
+
+        // blockers don't pull in packages
+        deps.into_iter()
+            .filter(|(_, dep)| dep.blocker().is_none())
+            .unique()
+            .collect()
+    }
+
+    /// Recursively add a package and its dependencies to the graph.
+    ///
+    /// Packages are re-expanded when reached at a shallower depth than previously
+    /// seen so depth limits don't depend on traversal order.
+    fn add(&mut self, pkg: &EbuildPkg, depth: usize) -> pkgcraft::Result<()> {
+        let id = pkg.to_string();
+        if self.depths.get(&id).is_some_and(|x| *x <= depth) {
+            return Ok(());
+        }
+        self.depths.insert(id.clone(), depth);
+        self.graph.pkgs.insert(id.clone(), vec![]);
+        if depth >= self.depth {
+            return Ok(());
+        }
+
+        self.stack.insert(id.clone());
+        for (key, dep) in self.deps(pkg) {
+            let target = self.resolve(&dep)?;
+            let target_id = target.as_ref().map(|x| x.to_string());
+
+            // flag dependencies back to a package in the current walk
+            let mut cycle = false;
+            if let Some(x) = target_id.as_ref()
+                && let Some(idx) = self.stack.get_index_of(x)
+            {
+                let path: Vec<_> = self.stack.iter().skip(idx).cloned().collect();
+                if !self.graph.cycles.contains(&path) {
+                    self.graph.cycles.push(path);
+                }
+                cycle = true;
+            }
+
+            let edge = Edge {
+                key: key.to_string(),
+                dep: dep.to_string(),
+                target: target_id,
+                cycle,
+            };
+            self.graph.pkgs[&id].push(edge);
+
+            if let Some(pkg) = target
+                && !cycle
+            {
+                self.add(&pkg, depth + 1)?;
+            }
+        }
+        self.stack.pop();
+
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Render a package subtree in indented text form.
+    fn tree<W: Write>(
+        &self,
+        f: &mut W,
+        id: &str,
+        level: usize,
+        seen: &mut IndexSet<String>,
+    ) -> io::Result<()> {
+        let Some(edges) = self.pkgs.get(id) else {
+            return Ok(());
+        };
+        if !seen.insert(id.to_string()) {
+            return Ok(());
+        }
+
+        let indent = "  ".repeat(level + 1);
+        for edge in edges {
+            let Edge { key, dep, target, cycle } = edge;
+            match target {
+                Some(x) if *cycle => writeln!(f, "{indent}{key}: {dep} -> {x} (cycle)")?,
+                Some(x) if seen.contains(x) => {
+                    writeln!(f, "{indent}{key}: {dep} -> {x} (seen)")?
+                }
+                Some(x) => {
+                    writeln!(f, "{indent}{key}: {dep} -> {x}")?;
+                    self.tree(f, x, level + 1, seen)?;
+                }
+                None => writeln!(f, "{indent}{key}: {dep} (unresolved)")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the graph in DOT format.
+    fn dot<W: Write>(&self, f: &mut W) -> io::Result<()> {
+        writeln!(f, "digraph deps {{")?;
+        for (id, edges) in &self.pkgs {
+            writeln!(f, "  \"{id}\";")?;
+            for edge in edges {
+                let Edge { key, dep, target, cycle } = edge;
+                match target {
+                    Some(x) if *cycle => {
+                        writeln!(f, "  \"{id}\" -> \"{x}\" [label=\"{key}\", color=red];")?
+                    }
+                    Some(x) => writeln!(f, "  \"{id}\" -> \"{x}\" [label=\"{key}\"];")?,
+                    None => writeln!(
+                        f,
+                        "  \"{id}\" -> \"{dep}\" [label=\"{key}\", style=dashed];"
+                    )?,
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        let targets = Targets::new(config)
+            .repo_format(RepoFormat::Ebuild)
+            .repo(self.repo.as_deref())?
+            .pkg_targets(self.targets.iter().flatten())?;
+
+        // resolve dependencies across target and configured repos
+        let repos: Vec<_> = targets
+            .ebuild_repos()
+            .chain(config.repos().set(Some(RepoFormat::Ebuild)).iter_ebuild())
+            .cloned()
+            .unique_by(|r| r.id().to_string())
+            .collect();
+
+        let mut keys: IndexSet<_> = self.keys.iter().copied().collect();
+        if keys.is_empty() {
+            keys = DEP_KEYS.iter().copied().collect();
+        }
+
+        let (enabled, disabled) = match &self.r#use {
+            Some(flags) => {
+                let (disabled, enabled): (IndexSet<_>, IndexSet<_>) =
+                    flags.iter().partition_map(|x| match x.strip_prefix('-') {
+                        Some(flag) => itertools::Either::Left(flag.to_string()),
+                        None => itertools::Either::Right(x.to_string()),
+                    });
+                (Some(enabled), disabled)
+            }
+            None => (None, Default::default()),
+        };
+
+        let mut builder = Builder {
+            repos: &repos,
+            keys,
+            enabled,
+            disabled,
+            depth: self.depth.unwrap_or(usize::MAX),
+            depths: Default::default(),
+            graph: Default::default(),
+            stack: Default::default(),
+        };
+
+        for pkg in targets.collapse().ebuild_pkgs() {
+            let pkg = pkg?;
+            builder.graph.targets.push(pkg.to_string());
+            builder.add(&pkg, 0)?;
+        }
+
+        let graph = builder.graph;
+        let mut stdout = io::stdout().lock();
+        match self.format {
+            Format::Tree => {
+                let mut seen = IndexSet::new();
+                for id in &graph.targets {
+                    writeln!(stdout, "{id}")?;
+                    graph.tree(&mut stdout, id, 0, &mut seen)?;
+                }
+            }
+            Format::Dot => graph.dot(&mut stdout)?,
+            Format::Json => {
+                let data = serde_json::to_string(&graph)
+                    .map_err(|e| anyhow!("failed serializing graph: {e}"))?;
+                writeln!(stdout, "{data}")?;
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
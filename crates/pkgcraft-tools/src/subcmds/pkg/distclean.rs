@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use camino::Utf8PathBuf;
+use clap::Args;
+use pkgcraft::cli::Targets;
+use pkgcraft::config::Config;
+use pkgcraft::fetch::{DistdirCleaner, installed_pkgs};
+use pkgcraft::pkg::Package;
+use pkgcraft::repo::ebuild::{EbuildRepo, Profile};
+use pkgcraft::repo::{PkgRepository, RepoFormat, Repository};
+use pkgcraft::traits::LogErrors;
+
+#[derive(Args)]
+#[clap(next_help_heading = "Distclean options")]
+pub(crate) struct Command {
+    /// Verify file checksums
+    #[arg(short, long)]
+    checksums: bool,
+
+    /// Distfile directory
+    #[arg(short, long)]
+    dir: Utf8PathBuf,
+
+    /// Ignore invalid packages
+    #[arg(short, long)]
+    ignore: bool,
+
+    /// Output files instead of removing them
+    #[arg(short, long)]
+    pretend: bool,
+
+    /// Target repositories
+    ///
+    /// By default, all configured ebuild repos are targeted. Only entire repos can be
+    /// targeted so files used by untargeted packages aren't removed.
+    #[arg(short, long)]
+    repo: Vec<String>,
+
+    /// Only reference files for packages visible to a profile
+    ///
+    /// Packages must be unmasked and keyworded for the profile's ACCEPT_KEYWORDS.
+    /// Profiles are loaded from each package's repo, falling back to its masters.
+    #[arg(long, value_name = "PROFILE")]
+    visible: Option<String>,
+
+    /// Only reference files for installed packages
+    ///
+    /// Installed packages are pulled from a package database, defaulting to
+    /// /var/db/pkg. Packages no longer available in the target repos aren't
+    /// referenced.
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        default_missing_value = "/var/db/pkg"
+    )]
+    installed: Option<Utf8PathBuf>,
+}
+
+/// Load a profile from the first repo in an inheritance tree that contains it.
+fn load_profile(repo: &EbuildRepo, path: &str) -> pkgcraft::Result<Profile> {
+    let repo = repo
+        .trees()
+        .find(|r| r.path().join("profiles").join(path).is_dir())
+        .unwrap_or(repo);
+    Profile::load(repo, path)
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        // default to targeting all configured repos
+        let mut repos = self.repo.clone();
+        if repos.is_empty() {
+            config.load()?;
+            repos = config
+                .repos()
+                .set(Some(RepoFormat::Ebuild))
+                .iter_ebuild()
+                .map(|r| r.id().to_string())
+                .collect();
+        }
+
+        let repos = Targets::new(config).repo_targets(repos)?.ebuild_repos()?;
+        let mut iter = repos
+            .iter()
+            .flat_map(|repo| repo.iter())
+            .log_errors(self.ignore);
+
+        let installed = self.installed.as_ref().map(installed_pkgs).transpose()?;
+        let mut profiles = HashMap::new();
+        let mut cleaner = DistdirCleaner::new(&self.dir).checksums(self.checksums);
+        for pkg in &mut iter {
+            if let Some(path) = self.visible.as_deref() {
+                let repo = pkg.repo();
+                let profile = match profiles.entry(repo.id().to_string()) {
+                    Entry::Occupied(e) => e.into_mut(),
+                    Entry::Vacant(e) => e.insert(load_profile(&repo, path)?),
+                };
+                if !profile.visible(&pkg) {
+                    continue;
+                }
+            }
+
+            if let Some(cpvs) = &installed
+                && !cpvs.contains(pkg.cpv())
+            {
+                continue;
+            }
+
+            cleaner.add_pkg(&pkg);
+        }
+
+        // avoid removing files when package loading fails
+        if iter.failed() {
+            anyhow::bail!("failed loading packages");
+        }
+
+        let mut stdout = io::stdout().lock();
+        for file in cleaner.scan()? {
+            if self.pretend {
+                writeln!(stdout, "{file}")?;
+            } else {
+                file.remove()?;
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
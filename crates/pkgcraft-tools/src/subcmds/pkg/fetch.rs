@@ -11,8 +11,7 @@ use indexmap::IndexSet;
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
 use pkgcraft::cli::{MaybeStdinVec, Targets};
 use pkgcraft::config::Config;
-use pkgcraft::error::Error;
-use pkgcraft::fetch::Fetcher;
+use pkgcraft::fetch::{CommandBackend, Fetcher};
 use pkgcraft::repo::RepoFormat;
 use pkgcraft::restrict::{Restrict, Restriction, str::Restrict as StrRestrict};
 use pkgcraft::traits::LogErrors;
@@ -28,10 +27,23 @@ pub(crate) struct Command {
     #[arg(short, long, default_value = "3")]
     concurrent: usize,
 
+    /// URL schemes using custom commands
+    #[arg(
+        long,
+        value_name = "SCHEME",
+        value_delimiter = ',',
+        default_value = "file,ftp,http,https"
+    )]
+    command_schemes: Vec<String>,
+
     /// Download directory
     #[arg(short, long, default_value = ".")]
     dir: Utf8PathBuf,
 
+    /// Custom fetch command
+    #[arg(long, value_name = "COMMAND")]
+    fetch_command: Option<String>,
+
     /// Filter URLs via regex
     #[arg(short = 'F', long, value_name = "REGEX")]
     filter: Option<String>,
@@ -56,6 +68,10 @@ pub(crate) struct Command {
     #[arg(short, long)]
     no_progress: bool,
 
+    /// Run pkg_nofetch for missing fetch-restricted files
+    #[arg(long, conflicts_with = "restrict")]
+    nofetch: bool,
+
     /// Output targets instead of fetching them
     #[arg(short, long)]
     pretend: bool,
@@ -72,6 +88,10 @@ pub(crate) struct Command {
     #[arg(long)]
     restrict: bool,
 
+    /// Custom resume command
+    #[arg(long, value_name = "COMMAND", requires = "fetch_command")]
+    resume_command: Option<String>,
+
     // positionals
     /// Target packages or paths
     #[arg(
@@ -107,6 +127,7 @@ impl Command {
 
         let failed = &AtomicBool::new(false);
         let mut fetchables = IndexSet::new();
+        let mut nofetch_pkgs = vec![];
         for pkg in &mut iter {
             fetchables.extend(
                 pkg.fetchables(self.restrict, self.mirrors)
@@ -129,6 +150,11 @@ impl Command {
                         }
                     }),
             );
+
+            // pkg_nofetch is skipped when pretending
+            if self.nofetch && !self.pretend {
+                nofetch_pkgs.push(pkg);
+            }
         }
 
         // output targets if pretending to fetch
@@ -139,13 +165,38 @@ impl Command {
             }
         }
 
+        // determine fetch-restricted files requiring manual download
+        let mut nofetch = vec![];
+        for pkg in nofetch_pkgs {
+            match pkg.nofetch(&self.dir) {
+                Ok(value) if !value.is_empty() => nofetch.push((pkg.to_string(), value)),
+                Ok(_) => (),
+                Err(e) => {
+                    error!("{e}");
+                    failed.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+
         let builder = reqwest::Client::builder()
             .danger_accept_invalid_certs(self.insecure)
             .hickory_dns(true)
             .read_timeout(Duration::from_secs_f64(self.timeout))
             .connect_timeout(Duration::from_secs_f64(self.timeout))
             .referer(false);
-        let fetcher = &Fetcher::new(builder)?;
+        let mut fetcher = Fetcher::new(builder)?;
+
+        // use external commands for the targeted URL schemes
+        if let Some(cmd) = self.fetch_command.as_deref() {
+            let mut backend = CommandBackend::new(cmd);
+            if let Some(value) = self.resume_command.as_deref() {
+                backend = backend.resume(value);
+            }
+            for scheme in &self.command_schemes {
+                fetcher = fetcher.backend(scheme, backend.clone());
+            }
+        }
+        let fetcher = &fetcher;
 
         // TODO: track overall download size if all targets have manifest data
         // show a global progress bar when downloading more files than concurrency limit
@@ -170,28 +221,15 @@ impl Command {
             // convert fetchables into download results stream
             let results = stream::iter(fetchables)
                 .map(|(f, path, manifest)| async move {
-                    let size = manifest.as_ref().map(|m| m.size());
                     let part_path = Utf8PathBuf::from(format!("{path}.part"));
-                    let result = fetcher.fetch(&f, &part_path, mb, size).await;
-                    (result, manifest, part_path, path)
+                    let result = fetcher.fetch(&f, &part_path, mb, manifest.as_ref()).await;
+                    (result, part_path, path)
                 })
                 .buffer_unordered(concurrent);
 
             // process results stream while logging errors
             results
-                .for_each(|(mut result, manifest, src, dest)| async move {
-                    // verify file hashes if manifest entry exists
-                    if let Some(manifest) = manifest.as_ref()
-                        && result.is_ok()
-                    {
-                        result = match tokio::fs::read(&src).await {
-                            Ok(data) => manifest.verify(&data),
-                            Err(e) => {
-                                Err(Error::InvalidValue(format!("failed reading: {src}: {e}")))
-                            }
-                        }
-                    }
-
+                .for_each(|(result, src, dest)| async move {
                     if let Err(e) = result {
                         mb.suspend(|| error!("{e}"));
                         failed.store(true, Ordering::Relaxed);
@@ -212,6 +250,19 @@ impl Command {
             pb.finish_and_clear();
         }
 
+        // output manual download instructions, falling back to listing the files
+        let mut stdout = stdout().lock();
+        for (pkg, value) in &nofetch {
+            if let Some(output) = value.output() {
+                writeln!(stdout, "{output}")?;
+            } else {
+                for download in value.downloads() {
+                    writeln!(stdout, "{pkg}: {download}")?;
+                }
+            }
+            failed.store(true, Ordering::Relaxed);
+        }
+
         let status = iter.failed() | failed.load(Ordering::Relaxed);
         Ok(ExitCode::from(status as u8))
     }
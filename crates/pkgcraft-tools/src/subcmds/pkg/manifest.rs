@@ -245,29 +245,17 @@ impl Command {
             // convert targets into download results stream
             let results = stream::iter(fetchables)
                 .map(|(f, path, manifest)| async move {
-                    let size = manifest.as_ref().map(|m| m.size());
+                    // skip verification when forcing manifest regeneration
+                    let entry = manifest.as_ref().filter(|_| !self.force);
                     let part_path = Utf8PathBuf::from(format!("{path}.part"));
-                    let result = fetcher.fetch(&f, &part_path, mb, size).await;
-                    (result, manifest, part_path, path)
+                    let result = fetcher.fetch(&f, &part_path, mb, entry).await;
+                    (result, part_path, path)
                 })
                 .buffer_unordered(concurrent);
 
             // process results stream while logging errors
             results
-                .for_each(|(mut result, manifest, src, dest)| async move {
-                    // verify file hashes if manifest entry exists
-                    if !self.force
-                        && let Some(manifest) = manifest.as_ref()
-                        && result.is_ok()
-                    {
-                        result = match tokio::fs::read(&src).await {
-                            Ok(data) => manifest.verify(&data),
-                            Err(e) => {
-                                Err(Error::InvalidValue(format!("failed reading: {src}: {e}")))
-                            }
-                        }
-                    }
-
+                .for_each(|(result, src, dest)| async move {
                     if let Err(e) = result {
                         mb.suspend(|| error!("{e}"));
                         failed.store(true, Ordering::Relaxed);
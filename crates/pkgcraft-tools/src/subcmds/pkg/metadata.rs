@@ -86,6 +86,7 @@ impl Command {
                     for cpv in repo.iter_cpv_restrict(&restrict) {
                         cache.remove_entry(&cpv)?;
                     }
+                    cache.sync()?;
                 } else {
                     cache
                         .regen(repo)
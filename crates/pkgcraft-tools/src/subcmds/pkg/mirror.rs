@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{IsTerminal, Write, stdout};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, builder::ArgPredicate};
+use futures::{StreamExt, stream};
+use indexmap::IndexMap;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+use pkgcraft::cli::{MaybeStdinVec, Targets};
+use pkgcraft::config::Config;
+use pkgcraft::error::Error;
+use pkgcraft::fetch::{Fetcher, MirrorLayout, Structure};
+use pkgcraft::repo::RepoFormat;
+use pkgcraft::traits::LogErrors;
+use pkgcraft::utils::bounded_jobs;
+use rayon::prelude::*;
+use tracing::error;
+use walkdir::WalkDir;
+
+use super::tokio;
+
+#[derive(Args)]
+#[clap(next_help_heading = "Mirror options")]
+pub(crate) struct Command {
+    /// Concurrent downloads
+    #[arg(short, long, default_value = "3")]
+    concurrent: usize,
+
+    /// Mirror directory
+    #[arg(short, long)]
+    dir: Utf8PathBuf,
+
+    /// Remove unreferenced files
+    ///
+    /// This requires targeting an entire repo so files used by untargeted packages
+    /// aren't removed. Cleaning is skipped if any failures occur.
+    #[arg(long, requires = "repo", conflicts_with_all = ["targets", "ignore"])]
+    clean: bool,
+
+    /// Ignore invalid packages
+    #[arg(short, long)]
+    ignore: bool,
+
+    /// Ignore invalid service certificates
+    #[arg(short = 'I', long)]
+    insecure: bool,
+
+    /// Layout structure for new mirrors
+    #[arg(short, long, default_value = "filename-hash BLAKE2B 8")]
+    layout: Structure,
+
+    /// Try fetching from default mirrors
+    #[arg(short, long)]
+    mirrors: bool,
+
+    /// Disable progress output
+    #[arg(short, long)]
+    no_progress: bool,
+
+    /// Output targets instead of fetching them
+    #[arg(short, long)]
+    pretend: bool,
+
+    /// Connection timeout in seconds
+    #[arg(short, long, default_value = "15")]
+    timeout: f64,
+
+    /// Target repo
+    #[arg(short, long)]
+    repo: Option<String>,
+
+    // positionals
+    /// Target packages or paths
+    #[arg(
+        value_name = "TARGET",
+        // default to the current working directory
+        default_value = ".",
+        // default to all packages when targeting a repo
+        default_value_if("repo", ArgPredicate::IsPresent, Some("*")),
+        help_heading = "Arguments",
+    )]
+    targets: Vec<MaybeStdinVec<String>>,
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        let concurrent = bounded_jobs(self.concurrent);
+        fs::create_dir_all(&self.dir)?;
+
+        // use the existing mirror layout, otherwise create a new one
+        let layout = if self.dir.join("layout.conf").exists() {
+            MirrorLayout::from_path(&self.dir)?
+        } else {
+            let layout = MirrorLayout::new([self.layout.clone(), Structure::Flat]);
+            if !self.pretend {
+                layout.to_path(&self.dir)?;
+            }
+            layout
+        };
+
+        // convert targets to pkgs
+        let mut iter = Targets::new(config)
+            .repo_format(RepoFormat::Ebuild)
+            .repo(self.repo.as_deref())?
+            .pkg_targets(self.targets.iter().flatten())?
+            .collapse()
+            .ebuild_pkgs()
+            .log_errors(self.ignore);
+
+        let failed = &AtomicBool::new(false);
+        let mut referenced = HashSet::new();
+        let mut fetchables = IndexMap::new();
+        for pkg in &mut iter {
+            for result in pkg.fetchables(false, self.mirrors) {
+                let f = match result {
+                    Ok(f) if f.mirror_restricted() => continue,
+                    Ok(f) => f,
+                    Err(e) => {
+                        error!("{e}");
+                        failed.store(true, Ordering::Relaxed);
+                        continue;
+                    }
+                };
+
+                let Some(manifest) = pkg.manifest().get(f.filename()).cloned() else {
+                    error!("{pkg}: missing Manifest entry: {}", f.filename());
+                    failed.store(true, Ordering::Relaxed);
+                    continue;
+                };
+
+                let path = layout.distfile_path(&self.dir, f.filename());
+                referenced.insert(path.clone());
+                fetchables.entry(path).or_insert((f, manifest));
+            }
+        }
+
+        // skip files matching their Manifest entries
+        let current: HashSet<_> = fetchables
+            .par_iter()
+            .filter(|(path, (_, manifest))| manifest.verify_path(path).is_ok())
+            .map(|(path, _)| path.clone())
+            .collect();
+        fetchables.retain(|path, _| !current.contains(path));
+
+        // output targets if pretending to fetch
+        if self.pretend {
+            let mut stdout = stdout().lock();
+            for (_, (f, _)) in fetchables.drain(..) {
+                writeln!(stdout, "{f}")?;
+            }
+        }
+
+        let builder = reqwest::Client::builder()
+            .danger_accept_invalid_certs(self.insecure)
+            .hickory_dns(true)
+            .read_timeout(Duration::from_secs_f64(self.timeout))
+            .connect_timeout(Duration::from_secs_f64(self.timeout))
+            .referer(false);
+        let fetcher = &Fetcher::new(builder)?;
+
+        // show a global progress bar when downloading more files than concurrency limit
+        let global_pb = if fetchables.len() > concurrent {
+            Some(ProgressBar::new(fetchables.len() as u64))
+        } else {
+            None
+        };
+
+        // initialize progress handling
+        let mb = &MultiProgress::new();
+        let hidden = !stdout().is_terminal() || self.no_progress;
+        if hidden {
+            mb.set_draw_target(ProgressDrawTarget::hidden());
+        } else if let Some(pb) = global_pb.as_ref() {
+            mb.add(pb.clone());
+        }
+
+        // download files asynchronously tracking failure status
+        let global_pb = &global_pb;
+        tokio().block_on(async {
+            let results = stream::iter(fetchables)
+                .map(|(path, (f, manifest))| async move {
+                    let part_path = Utf8PathBuf::from(format!("{path}.part"));
+                    let mut result = match part_path.parent() {
+                        Some(dir) => tokio::fs::create_dir_all(dir).await.map_err(Error::from),
+                        None => Ok(()),
+                    };
+                    if result.is_ok() {
+                        result = fetcher.fetch(&f, &part_path, mb, Some(&manifest)).await;
+                    }
+                    (result, part_path, path)
+                })
+                .buffer_unordered(concurrent);
+
+            // process results stream while logging errors
+            results
+                .for_each(|(result, src, dest)| async move {
+                    if let Err(e) = result {
+                        mb.suspend(|| error!("{e}"));
+                        failed.store(true, Ordering::Relaxed);
+                    } else if let Err(e) = fs::rename(&src, &dest) {
+                        mb.suspend(|| error!("failed renaming: {src} -> {dest}: {e}"));
+                        failed.store(true, Ordering::Relaxed);
+                    }
+
+                    if let Some(pb) = global_pb.as_ref() {
+                        pb.inc(1);
+                    }
+                })
+                .await;
+        });
+
+        // clear global progress bar
+        if let Some(pb) = global_pb.as_ref() {
+            pb.finish_and_clear();
+        }
+
+        let status = iter.failed() | failed.load(Ordering::Relaxed);
+
+        // remove files no longer referenced by any package
+        if self.clean {
+            // avoid removing files for packages that failed processing
+            if status {
+                anyhow::bail!("skipping cleaning due to failures");
+            }
+
+            let mut stdout = stdout().lock();
+            for entry in WalkDir::new(&self.dir)
+                .min_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+            {
+                let Some(path) = Utf8Path::from_path(entry.path()) else {
+                    continue;
+                };
+
+                if path != self.dir.join("layout.conf") && !referenced.contains(path) {
+                    if self.pretend {
+                        writeln!(stdout, "removing: {path}")?;
+                    } else {
+                        fs::remove_file(path)?;
+                    }
+                }
+            }
+        }
+
+        Ok(ExitCode::from(status as u8))
+    }
+}
@@ -5,8 +5,9 @@ use clap::Args;
 use itertools::Itertools;
 use pkgcraft::cli::{MaybeStdinVec, Targets};
 use pkgcraft::config::Config;
-use pkgcraft::dep::{CpvOrDep, Flatten};
-use pkgcraft::traits::{Intersects, LogErrors};
+use pkgcraft::dep::CpvOrDep;
+use pkgcraft::repo::ebuild::RevDepIndex;
+use tracing::warn;
 
 #[derive(Args)]
 #[clap(next_help_heading = "Revdeps options")]
@@ -20,7 +21,10 @@ pub(crate) struct Command {
     repo: String,
 
     // positionals
-    /// Target dependencies
+    /// Target packages or dependencies
+    ///
+    /// Dependency targets match by package name, version, and slot with any USE
+    /// dependencies required to exist in matching reverse dependencies.
     #[arg(value_name = "TARGET", help_heading = "Arguments")]
     targets: Vec<MaybeStdinVec<String>>,
 }
@@ -39,18 +43,28 @@ impl Command {
             .map(CpvOrDep::try_new)
             .try_collect()?;
 
-        // TODO: use a revdeps cache for queries (#120)
-        // TODO: use parallel iterators (#121)
+        // load the revdeps index, only updating it in memory if outdated
+        let path = RevDepIndex::default_path(&config.path().cache, &repo);
+        let mut index = RevDepIndex::load(path)?;
+        let mut failed = false;
+        if index.is_stale(&repo) {
+            warn!("{repo}: outdated revdeps index, run `pk repo revdeps` to update it");
+            // invalid packages are logged and dropped from the index
+            failed = index.update(&repo, self.ignore).is_err();
+        }
+
         let mut stdout = io::stdout().lock();
-        let mut iter = repo.iter_unordered().log_errors(self.ignore);
-        for pkg in &mut iter {
-            for dep in pkg.dependencies([]).into_iter_flatten() {
-                if targets.iter().any(|t| t.intersects(dep)) && dep.blocker().is_none() {
-                    writeln!(stdout, "{pkg}: {dep}")?;
-                }
-            }
+        for line in targets
+            .iter()
+            .flat_map(|target| index.cache().query(target))
+            .filter(|(revdep, _)| revdep.dep().blocker().is_none())
+            .map(|(revdep, _)| format!("{}::{repo}: {}", revdep.cpv(), revdep.dep()))
+            .sorted()
+            .dedup()
+        {
+            writeln!(stdout, "{line}")?;
         }
 
-        Ok(ExitCode::from(iter))
+        Ok(ExitCode::from(failed as u8))
     }
 }
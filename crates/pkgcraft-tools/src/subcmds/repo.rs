@@ -8,6 +8,8 @@ mod eclass;
 mod leaf;
 mod license;
 mod list;
+mod lock;
+mod manifest;
 mod metadata;
 mod mirror;
 mod remove;
@@ -41,6 +43,10 @@ enum Subcommand {
     License(license::Command),
     /// List repositories
     List(list::Command),
+    /// Record synced repository revisions
+    Lock(lock::Command),
+    /// Verify or regenerate repository Manifests
+    Manifest(manifest::Command),
     /// Manipulate repo metadata
     Metadata(metadata::Command),
     /// Output mirror statistics
@@ -62,6 +68,8 @@ impl Subcommand {
             Self::Leaf(cmd) => cmd.run(config),
             Self::License(cmd) => cmd.run(config),
             Self::List(cmd) => cmd.run(config),
+            Self::Lock(cmd) => cmd.run(config),
+            Self::Manifest(cmd) => cmd.run(config),
             Self::Metadata(cmd) => cmd.run(config),
             Self::Mirror(cmd) => cmd.run(config),
             Self::Remove(cmd) => cmd.run(config),
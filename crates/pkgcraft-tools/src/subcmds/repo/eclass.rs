@@ -8,9 +8,16 @@ use pkgcraft::config::Config;
 use pkgcraft::pkg::Package;
 use pkgcraft::traits::LogErrors;
 
+mod doc;
+mod graph;
+
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
 #[clap(next_help_heading = "Eclass options")]
 pub(crate) struct Command {
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+
     /// Output packages for a target eclass
     #[arg(long)]
     eclass: Option<String>,
@@ -25,8 +32,29 @@ pub(crate) struct Command {
     repos: Vec<String>,
 }
 
+#[derive(clap::Subcommand)]
+enum Subcommand {
+    /// Output eclass documentation
+    Doc(doc::Command),
+    /// Output eclass inheritance graph
+    Graph(graph::Command),
+}
+
+impl Subcommand {
+    fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::Doc(cmd) => cmd.run(config),
+            Self::Graph(cmd) => cmd.run(config),
+        }
+    }
+}
+
 impl Command {
     pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        if let Some(cmd) = &self.command {
+            return cmd.run(config);
+        }
+
         let repos = Targets::new(config)
             .repo_targets(&self.repos)?
             .ebuild_repos()?;
@@ -0,0 +1,401 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use anyhow::anyhow;
+use camino::Utf8PathBuf;
+use clap::{Args, ValueEnum};
+use itertools::Itertools;
+use pkgcraft::cli::Targets;
+use pkgcraft::config::Config;
+use pkgcraft::repo::Repository;
+use pkgcraft::repo::ebuild::eclass::doc::{EclassDoc, FunctionDoc, VariableDoc};
+use tracing::error;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Format {
+    /// Man page output
+    Man,
+    /// Markdown output
+    Markdown,
+}
+
+impl Format {
+    /// Return the file extension for the output format.
+    fn extension(&self) -> &str {
+        match self {
+            Self::Man => "5",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Args)]
+#[clap(next_help_heading = "Doc options")]
+pub(crate) struct Command {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Man)]
+    format: Format,
+
+    /// Include internal functions and variables
+    #[arg(long)]
+    internal: bool,
+
+    /// Write files to a directory
+    #[arg(short, long, value_name = "DIR")]
+    output: Option<Utf8PathBuf>,
+
+    /// Target repository
+    #[arg(short, long, default_value = ".")]
+    repo: String,
+
+    // positionals
+    /// Target eclasses
+    ///
+    /// By default, all eclasses in the repo are targeted.
+    #[arg(value_name = "ECLASS", help_heading = "Arguments")]
+    eclasses: Vec<String>,
+}
+
+/// Return the deprecation notice for a deprecated item.
+fn deprecation(replacement: &str) -> String {
+    if replacement == "none" {
+        "Deprecated with no replacement.".to_string()
+    } else {
+        format!("Deprecated, use {replacement} instead.")
+    }
+}
+
+/// Return the attribute descriptions for a variable.
+fn attributes(var: &VariableDoc) -> Vec<&'static str> {
+    [
+        (var.required(), "required"),
+        (var.pre_inherit(), "set before inherit"),
+        (var.user(), "user variable"),
+        (var.output(), "generated by eclass"),
+        (var.default_unset(), "unset by default"),
+        (var.internal(), "internal"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, s)| enabled.then_some(s))
+    .collect()
+}
+
+/// Escape text for use in a man page.
+fn man_escape(line: &str) -> String {
+    let line = line.replace('\\', "\\e");
+    if line.starts_with(['.', '\'']) {
+        format!("\\&{line}")
+    } else {
+        line
+    }
+}
+
+/// Render preformatted lines for a man page.
+fn man_lines<I>(lines: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    lines.into_iter().map(|s| man_escape(s.as_ref())).join("\n")
+}
+
+/// Render documentation text for a man page using a given paragraph macro.
+fn man_text(text: &str, paragraph: &str) -> String {
+    let mut code = false;
+    let mut lines = vec![];
+    for line in text.lines() {
+        if line.trim() == "@CODE" {
+            lines.push(if code { ".fi" } else { ".nf" }.to_string());
+            code = !code;
+        } else if let Some(title) = line.strip_prefix("@SUBSECTION ") {
+            lines.push(format!(".SS {}", man_escape(title)));
+        } else if line.is_empty() && !code {
+            lines.push(paragraph.to_string());
+        } else {
+            lines.push(man_escape(line));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Render documentation text for markdown.
+fn markdown_text(text: &str) -> String {
+    let mut lines = vec![];
+    for line in text.lines() {
+        if line.trim() == "@CODE" {
+            lines.push("```".to_string());
+        } else if let Some(title) = line.strip_prefix("@SUBSECTION ") {
+            lines.push(format!("#### {title}"));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+struct Renderer<'a> {
+    doc: &'a EclassDoc,
+    repo: &'a str,
+    internal: bool,
+}
+
+impl Renderer<'_> {
+    /// Return the functions to render.
+    fn functions(&self) -> impl Iterator<Item = &FunctionDoc> {
+        self.doc
+            .functions()
+            .values()
+            .filter(move |x| self.internal || !x.internal())
+    }
+
+    /// Return the variables to render for a variable type.
+    fn variables(&self, eclass: bool) -> impl Iterator<Item = &VariableDoc> {
+        self.doc
+            .variables()
+            .values()
+            .filter(move |x| x.eclass() == eclass)
+            .filter(move |x| self.internal || !x.internal())
+    }
+
+    /// Render the documentation as a man page.
+    fn man(&self) -> anyhow::Result<String> {
+        let doc = self.doc;
+        let name = format!("{}.eclass", doc.name());
+        let mut s = String::new();
+        writeln!(s, ".TH \"{}\" 5 \"\" \"{}\"", name.to_uppercase(), self.repo)?;
+        writeln!(s, ".SH \"NAME\"")?;
+        writeln!(s, "{name} \\- {}", man_escape(doc.blurb()))?;
+
+        if doc.dead() || doc.deprecated().is_some() || doc.description().is_some() {
+            writeln!(s, ".SH \"DESCRIPTION\"")?;
+            if doc.dead() {
+                writeln!(s, "This eclass is dead and pending removal.")?;
+                writeln!(s, ".PP")?;
+            }
+            if let Some(value) = doc.deprecated() {
+                writeln!(s, "{}", deprecation(value))?;
+                writeln!(s, ".PP")?;
+            }
+            if let Some(text) = doc.description() {
+                writeln!(s, "{}", man_text(text, ".PP"))?;
+            }
+        }
+
+        if !doc.supported_eapis().is_empty() {
+            writeln!(s, ".SH \"SUPPORTED EAPIS\"")?;
+            writeln!(s, "{}", doc.supported_eapis().join(" "))?;
+        }
+
+        if !doc.provides().is_empty() {
+            writeln!(s, ".SH \"TRANSITIVELY PROVIDED ECLASSES\"")?;
+            writeln!(s, "{}", doc.provides().join(" "))?;
+        }
+
+        if let Some(text) = doc.example() {
+            writeln!(s, ".SH \"EXAMPLE\"")?;
+            writeln!(s, ".nf\n{}\n.fi", man_lines(text.lines()))?;
+        }
+
+        let mut functions = self.functions().peekable();
+        if functions.peek().is_some() {
+            writeln!(s, ".SH \"FUNCTIONS\"")?;
+        }
+        for func in functions {
+            writeln!(s, ".TP")?;
+            match func.usage() {
+                Some(usage) => writeln!(s, "\\fB{}\\fR {}", func.name(), man_escape(usage))?,
+                None => writeln!(s, "\\fB{}\\fR", func.name())?,
+            }
+            if let Some(value) = func.deprecated() {
+                writeln!(s, "{}", deprecation(value))?;
+                writeln!(s, ".IP")?;
+            }
+            if let Some(text) = func.description() {
+                writeln!(s, "{}", man_text(text, ".IP"))?;
+            }
+            if let Some(value) = func.returns() {
+                writeln!(s, ".IP\nReturns: {}", man_escape(value))?;
+            }
+        }
+
+        for (eclass, title) in [(true, "ECLASS VARIABLES"), (false, "FUNCTION VARIABLES")] {
+            let mut variables = self.variables(eclass).peekable();
+            if variables.peek().is_some() {
+                writeln!(s, ".SH \"{title}\"")?;
+            }
+            for var in variables {
+                writeln!(s, ".TP")?;
+                let attrs = attributes(var);
+                if attrs.is_empty() {
+                    writeln!(s, "\\fB{}\\fR", var.name())?;
+                } else {
+                    writeln!(s, "\\fB{}\\fR ({})", var.name(), attrs.join(", "))?;
+                }
+                if let Some(value) = var.deprecated() {
+                    writeln!(s, "{}", deprecation(value))?;
+                    writeln!(s, ".IP")?;
+                }
+                if let Some(text) = var.description() {
+                    writeln!(s, "{}", man_text(text, ".IP"))?;
+                }
+            }
+        }
+
+        writeln!(s, ".SH \"MAINTAINERS\"")?;
+        writeln!(s, ".nf\n{}\n.fi", man_lines(doc.maintainers()))?;
+
+        if !doc.authors().is_empty() {
+            writeln!(s, ".SH \"AUTHORS\"")?;
+            writeln!(s, ".nf\n{}\n.fi", man_lines(doc.authors()))?;
+        }
+
+        Ok(s)
+    }
+
+    /// Render the documentation as markdown.
+    fn markdown(&self) -> anyhow::Result<String> {
+        let doc = self.doc;
+        let mut s = String::new();
+        writeln!(s, "# {}.eclass\n", doc.name())?;
+        writeln!(s, "{}", doc.blurb())?;
+
+        if doc.dead() {
+            writeln!(s, "\n**This eclass is dead and pending removal.**")?;
+        }
+        if let Some(value) = doc.deprecated() {
+            writeln!(s, "\n**{}**", deprecation(value))?;
+        }
+        if let Some(text) = doc.description() {
+            writeln!(s, "\n## Description\n\n{}", markdown_text(text))?;
+        }
+        if !doc.supported_eapis().is_empty() {
+            writeln!(s, "\n## Supported EAPIs\n\n{}", doc.supported_eapis().join(" "))?;
+        }
+        if !doc.provides().is_empty() {
+            writeln!(
+                s,
+                "\n## Transitively provided eclasses\n\n{}",
+                doc.provides().join(" ")
+            )?;
+        }
+        if let Some(text) = doc.example() {
+            writeln!(s, "\n## Example\n\n```\n{text}\n```")?;
+        }
+
+        let mut functions = self.functions().peekable();
+        if functions.peek().is_some() {
+            writeln!(s, "\n## Functions")?;
+        }
+        for func in functions {
+            match func.usage() {
+                Some(usage) => writeln!(s, "\n### `{} {usage}`", func.name())?,
+                None => writeln!(s, "\n### `{}`", func.name())?,
+            }
+            if let Some(value) = func.deprecated() {
+                writeln!(s, "\n**{}**", deprecation(value))?;
+            }
+            if let Some(text) = func.description() {
+                writeln!(s, "\n{}", markdown_text(text))?;
+            }
+            if let Some(value) = func.returns() {
+                writeln!(s, "\nReturns: {value}")?;
+            }
+        }
+
+        for (eclass, title) in [(true, "Eclass variables"), (false, "Function variables")] {
+            let mut variables = self.variables(eclass).peekable();
+            if variables.peek().is_some() {
+                writeln!(s, "\n## {title}")?;
+            }
+            for var in variables {
+                writeln!(s, "\n### `{}`", var.name())?;
+                let attrs = attributes(var);
+                if !attrs.is_empty() {
+                    writeln!(s, "\n*{}*", attrs.join(", "))?;
+                }
+                if let Some(value) = var.deprecated() {
+                    writeln!(s, "\n**{}**", deprecation(value))?;
+                }
+                if let Some(text) = var.description() {
+                    writeln!(s, "\n{}", markdown_text(text))?;
+                }
+            }
+        }
+
+        writeln!(s, "\n## Maintainers\n")?;
+        for maintainer in doc.maintainers() {
+            writeln!(s, "- {maintainer}")?;
+        }
+
+        if !doc.authors().is_empty() {
+            writeln!(s, "\n## Authors\n")?;
+            for author in doc.authors() {
+                writeln!(s, "- {author}")?;
+            }
+        }
+
+        Ok(s)
+    }
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        let repo = Targets::new(config)
+            .repo_targets([&self.repo])?
+            .ebuild_repo()?;
+
+        // fail fast for nonexistent eclass selection
+        let eclasses: Vec<_> = if self.eclasses.is_empty() {
+            repo.eclasses().iter().collect()
+        } else {
+            self.eclasses
+                .iter()
+                .map(|name| {
+                    repo.eclasses()
+                        .get(name.as_str())
+                        .ok_or_else(|| anyhow!("unknown eclass: {name}"))
+                })
+                .collect::<anyhow::Result<_>>()?
+        };
+
+        if let Some(dir) = &self.output {
+            fs::create_dir_all(dir)
+                .map_err(|e| anyhow!("failed creating output dir: {dir}: {e}"))?;
+        }
+
+        let mut failed = false;
+        let mut stdout = io::stdout().lock();
+        for eclass in eclasses {
+            let doc = match eclass.doc() {
+                Ok(doc) => doc,
+                Err(e) => {
+                    error!("{e}");
+                    failed = true;
+                    continue;
+                }
+            };
+
+            let renderer = Renderer {
+                doc: &doc,
+                repo: repo.name(),
+                internal: self.internal,
+            };
+            let data = match self.format {
+                Format::Man => renderer.man()?,
+                Format::Markdown => renderer.markdown()?,
+            };
+
+            if let Some(dir) = &self.output {
+                let path = dir.join(format!("{eclass}.eclass.{}", self.format.extension()));
+                fs::write(&path, data)
+                    .map_err(|e| anyhow!("failed writing file: {path}: {e}"))?;
+            } else {
+                write!(stdout, "{data}")?;
+            }
+        }
+
+        Ok(ExitCode::from(failed as u8))
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use anyhow::anyhow;
+use clap::{Args, ValueEnum};
+use pkgcraft::cli::Targets;
+use pkgcraft::config::Config;
+use pkgcraft::repo::ebuild::eclass::graph::EclassGraph;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Format {
+    /// DOT graph output
+    Dot,
+    /// JSON output
+    Json,
+}
+
+#[derive(Args)]
+#[clap(next_help_heading = "Graph options")]
+pub(crate) struct Command {
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Dot)]
+    format: Format,
+
+    /// Ignore invalid packages
+    #[arg(short, long)]
+    ignore: bool,
+
+    /// Output packages directly or transitively inheriting an eclass
+    #[arg(long, value_name = "ECLASS", conflicts_with_all = ["format", "inheritors"])]
+    affected: Option<String>,
+
+    /// Output eclasses directly or transitively inheriting an eclass
+    #[arg(long, value_name = "ECLASS", conflicts_with = "format")]
+    inheritors: Option<String>,
+
+    /// Target repository
+    #[arg(short, long, default_value = ".")]
+    repo: String,
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        let repo = Targets::new(config)
+            .repo_targets([&self.repo])?
+            .ebuild_repo()?;
+
+        // fail fast for nonexistent eclass selection
+        for name in [&self.affected, &self.inheritors].into_iter().flatten() {
+            if !repo.eclasses().contains(name.as_str()) {
+                anyhow::bail!("unknown eclass: {name}");
+            }
+        }
+
+        let graph = EclassGraph::from_repo(&repo, self.ignore)?;
+        let mut stdout = io::stdout().lock();
+        if let Some(name) = &self.affected {
+            for cpv in graph.affected_pkgs(name) {
+                writeln!(stdout, "{cpv}")?;
+            }
+        } else if let Some(name) = &self.inheritors {
+            for eclass in graph.affected_eclasses(name) {
+                writeln!(stdout, "{eclass}")?;
+            }
+        } else {
+            match self.format {
+                Format::Dot => write!(stdout, "{}", graph.to_dot())?,
+                Format::Json => {
+                    let data = serde_json::to_string(&graph)
+                        .map_err(|e| anyhow!("failed serializing graph: {e}"))?;
+                    writeln!(stdout, "{data}")?;
+                }
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
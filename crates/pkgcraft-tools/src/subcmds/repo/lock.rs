@@ -0,0 +1,43 @@
+use std::process::ExitCode;
+
+use camino::Utf8PathBuf;
+use clap::Args;
+use pkgcraft::config::{Config, RepoLock};
+
+#[derive(Args)]
+#[clap(next_help_heading = "Lock options")]
+pub(crate) struct Command {
+    /// Custom lockfile path
+    #[arg(short, long, value_name = "PATH")]
+    file: Option<Utf8PathBuf>,
+
+    /// Repository name
+    ///
+    /// Only git repos are locked since other sync formats can't restore previous revisions.
+    #[arg(value_name = "REPO", help_heading = "Arguments")]
+    repos: Vec<String>,
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        // make sure system config is loaded if custom config wasn't specified
+        config.load()?;
+
+        let path = self
+            .file
+            .clone()
+            .unwrap_or_else(|| config.path().config.join("repos.lock"));
+
+        // update existing lockfile entries when targeting specific repos
+        let mut lock = if !self.repos.is_empty() && path.exists() {
+            RepoLock::from_path(&path)?
+        } else {
+            RepoLock::default()
+        };
+
+        lock.update(config.repos().lock(&self.repos)?);
+        lock.to_path(&path)?;
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use camino::Utf8PathBuf;
+use clap::Args;
+use pkgcraft::cli::Targets;
+use pkgcraft::config::Config;
+use pkgcraft::repo::Repository;
+use pkgcraft::repo::ebuild::manifest::ManifestTree;
+
+#[derive(Args)]
+#[clap(next_help_heading = "Manifest options")]
+pub(crate) struct Command {
+    /// Regenerate the Manifest tree
+    #[arg(short, long)]
+    generate: bool,
+
+    /// Require a valid OpenPGP signature
+    #[arg(long, conflicts_with = "generate")]
+    signed: bool,
+
+    /// Custom OpenPGP keyring
+    #[arg(long, value_name = "PATH", conflicts_with = "generate")]
+    keyring: Option<Utf8PathBuf>,
+
+    // positionals
+    /// Target repository
+    #[arg(default_value = ".")]
+    repo: String,
+}
+
+impl Command {
+    pub(super) fn run(&self, config: &mut Config) -> anyhow::Result<ExitCode> {
+        let repo = Targets::new(config)
+            .repo_targets([&self.repo])?
+            .ebuild_repo()?;
+
+        let mut tree = ManifestTree::new(repo.path()).signed(self.signed);
+        if let Some(path) = &self.keyring {
+            tree = tree.keyring(path);
+        }
+
+        if self.generate {
+            tree.generate(&repo.metadata().config.manifest_hashes)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let errors = tree.verify()?;
+        let mut stdout = io::stdout().lock();
+        for e in &errors {
+            writeln!(stdout, "{e}")?;
+        }
+
+        if errors.is_empty() {
+            Ok(ExitCode::SUCCESS)
+        } else {
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
@@ -5,6 +5,7 @@ use clap::Args;
 use pkgcraft::cli::Targets;
 use pkgcraft::config::Config;
 use pkgcraft::repo::ebuild::cache::{Cache, CacheFormat};
+use pkgcraft::repo::ebuild::{RevDepIndex, SearchIndex};
 use pkgcraft::utils::bounded_thread_pool;
 
 #[derive(Args)]
@@ -71,6 +72,16 @@ impl Command {
             repo.metadata().use_local_update(&repo)?;
         }
 
+        // refresh indexes that are already in use
+        let path = RevDepIndex::default_path(&config.path().cache, &repo);
+        if path.exists() {
+            repo.revdeps_index(path, false)?;
+        }
+        let path = SearchIndex::default_path(&config.path().cache, &repo);
+        if path.exists() {
+            repo.search_index(path, false)?;
+        }
+
         Ok(ExitCode::SUCCESS)
     }
 }
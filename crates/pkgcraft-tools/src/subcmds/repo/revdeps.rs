@@ -4,6 +4,7 @@ use camino::Utf8PathBuf;
 use clap::Args;
 use pkgcraft::cli::Targets;
 use pkgcraft::config::Config;
+use pkgcraft::repo::ebuild::RevDepIndex;
 
 #[derive(Args)]
 #[clap(next_help_heading = "Revdeps options")]
@@ -28,11 +29,12 @@ impl Command {
             .repo_targets([&self.repo])?
             .ebuild_repo()?;
 
-        // TODO: load/update revdeps cache instead of creating
-        let cache = repo.revdeps(self.ignore)?;
+        // load and incrementally update the revdeps index
+        let path = RevDepIndex::default_path(&config.path().cache, &repo);
+        let index = repo.revdeps_index(path, self.ignore)?;
 
         // serialize cache to disk in qa reports format
-        cache.serialize_to_qa(&self.dir)?;
+        index.cache().serialize_to_qa(&self.dir)?;
 
         Ok(ExitCode::SUCCESS)
     }
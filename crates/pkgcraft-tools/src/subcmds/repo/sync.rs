@@ -1,11 +1,20 @@
 use std::process::ExitCode;
 
+use camino::Utf8PathBuf;
 use clap::Args;
-use pkgcraft::config::Config;
+use pkgcraft::config::{Config, RepoLock};
 
 #[derive(Args)]
 #[clap(next_help_heading = "Sync options")]
 pub(crate) struct Command {
+    /// Sync to the revisions recorded in the lockfile
+    #[arg(long)]
+    locked: bool,
+
+    /// Custom lockfile path
+    #[arg(long, value_name = "PATH", requires = "locked")]
+    lockfile: Option<Utf8PathBuf>,
+
     /// Repository name
     #[arg(value_name = "REPO", help_heading = "Arguments")]
     repos: Vec<String>,
@@ -16,8 +25,17 @@ impl Command {
         // make sure system config is loaded if custom config wasn't specified
         config.load()?;
 
-        // sync specified repos
-        config.repos_mut()?.sync(&self.repos)?;
+        // sync specified repos, running any post-sync actions
+        if self.locked {
+            let path = self
+                .lockfile
+                .clone()
+                .unwrap_or_else(|| config.path().config.join("repos.lock"));
+            let lock = RepoLock::from_path(path)?;
+            config.sync_locked(&self.repos, &lock)?;
+        } else {
+            config.sync(&self.repos)?;
+        }
 
         Ok(ExitCode::SUCCESS)
     }
@@ -0,0 +1,171 @@
+use std::cmp::Reverse;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use clap::Args;
+use itertools::Itertools;
+use pkgcraft::cli::{MaybeStdinVec, Targets};
+use pkgcraft::config::Config;
+use pkgcraft::repo::ebuild::search::{SearchField, SearchMatch};
+use pkgcraft::repo::ebuild::{EbuildRepo, SearchIndex};
+use pkgcraft::repo::{RepoFormat, Repository};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+use tracing::warn;
+
+use crate::format::{EnumVariable, FormatString};
+
+#[derive(Args)]
+#[clap(next_help_heading = "Search options")]
+pub(crate) struct Command {
+    /// Ignore invalid packages
+    #[arg(short, long)]
+    ignore: bool,
+
+    /// Update outdated search indexes
+    ///
+    /// By default, outdated indexes are only updated in memory.
+    #[arg(short, long)]
+    update: bool,
+
+    /// Target repositories
+    ///
+    /// By default, all configured ebuild repos are searched.
+    #[arg(short, long)]
+    repo: Vec<String>,
+
+    /// Restrict matching to specific fields
+    #[arg(long, value_name = "FIELD", value_delimiter = ',')]
+    field: Vec<SearchField>,
+
+    /// Limit the number of results
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+
+    /// Output using a custom format
+    #[arg(short, long)]
+    format: Option<String>,
+
+    // positionals
+    /// Search terms
+    ///
+    /// All terms must match for a package to be returned.
+    #[arg(value_name = "TERM", required = true, help_heading = "Arguments")]
+    terms: Vec<MaybeStdinVec<String>>,
+}
+
+/// Search result tied to its source repo.
+pub(crate) struct Match<'a> {
+    repo: &'a EbuildRepo,
+    result: SearchMatch<'a>,
+}
+
+#[derive(Display, EnumIter, EnumString, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[allow(non_camel_case_types)]
+pub(crate) enum Key {
+    CATEGORY,
+    PN,
+    CPN,
+    REPO,
+    DESCRIPTION,
+    LONGDESCRIPTION,
+    MAINTAINERS,
+    REMOTE_IDS,
+    USE,
+    SCORE,
+}
+
+impl<'a> EnumVariable<'a> for Key {
+    type Object = Match<'a>;
+
+    fn value(&self, obj: &'a Self::Object) -> String {
+        let entry = obj.result.entry();
+        match self {
+            Self::CATEGORY => entry.cpn().category().to_string(),
+            Self::PN => entry.cpn().package().to_string(),
+            Self::CPN => entry.cpn().to_string(),
+            Self::REPO => obj.repo.id().to_string(),
+            Self::DESCRIPTION => entry.description().to_string(),
+            Self::LONGDESCRIPTION => entry.long_description().to_string(),
+            Self::MAINTAINERS => entry.maintainers().join(", "),
+            Self::REMOTE_IDS => entry.remote_ids().join(" "),
+            Self::USE => entry.use_flags().join(", "),
+            Self::SCORE => obj.result.score().to_string(),
+        }
+    }
+}
+
+impl<'a> FormatString<'a> for Command {
+    type Object = Match<'a>;
+    type FormatKey = Key;
+}
+
+impl Command {
+    pub(super) fn run(&self, mut config: Config) -> anyhow::Result<ExitCode> {
+        let query = self.terms.iter().flatten().join(" ");
+
+        // default to searching all configured ebuild repos
+        let mut repos = self.repo.clone();
+        if repos.is_empty() {
+            config.load()?;
+            repos = config
+                .repos()
+                .set(Some(RepoFormat::Ebuild))
+                .iter_ebuild()
+                .map(|r| r.id().to_string())
+                .collect();
+        }
+
+        let repos = Targets::new(&mut config)
+            .repo_targets(repos)?
+            .ebuild_repos()?;
+
+        // load the search index for each repo, only updating it in memory if outdated
+        let fields = if self.field.is_empty() {
+            SearchField::iter().collect()
+        } else {
+            self.field.clone()
+        };
+        let mut indexes = vec![];
+        for repo in &repos {
+            let path = SearchIndex::default_path(&config.path().cache, repo);
+            let index = if self.update {
+                repo.search_index(path, self.ignore)?
+            } else {
+                let mut index = SearchIndex::load(path)?;
+                if index.is_stale(repo) {
+                    warn!(
+                        "{repo}: outdated search index, run `pk search --update` to update it"
+                    );
+                    index.update(repo, self.ignore)?;
+                }
+                index
+            };
+            indexes.push((repo, index));
+        }
+
+        // merge results across repos sorted by relevance
+        let matches = indexes
+            .iter()
+            .flat_map(|(repo, index)| {
+                index
+                    .search_fields(&query, fields.iter().copied())
+                    .into_iter()
+                    .map(move |result| Match { repo: *repo, result })
+            })
+            .sorted_by_key(|m| (Reverse(m.result.score()), m.result.entry().cpn()))
+            .take(self.limit.unwrap_or(usize::MAX));
+
+        let mut stdout = io::stdout().lock();
+        for m in matches {
+            if let Some(fmt) = &self.format {
+                writeln!(stdout, "{}", self.format_str(fmt, &m)?)?;
+            } else {
+                let entry = m.result.entry();
+                writeln!(stdout, "{}::{}: {}", entry.cpn(), m.repo, entry.description())?;
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
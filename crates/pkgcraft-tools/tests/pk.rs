@@ -4,4 +4,5 @@ mod dep;
 mod pkg;
 mod predicates;
 mod repo;
+mod search;
 mod version;
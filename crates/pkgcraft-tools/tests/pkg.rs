@@ -1,8 +1,12 @@
+mod depgraph;
+mod distclean;
 mod env;
 mod fetch;
 mod manifest;
 mod metadata;
+mod mirror;
 mod pretend;
+mod revdeps;
 mod showkw;
 mod source;
 
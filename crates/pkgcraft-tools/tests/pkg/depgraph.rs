@@ -0,0 +1,143 @@
+use pkgcraft::repo::ebuild::{EbuildRepoBuilder, EbuildTempRepo};
+use pkgcraft::test::cmd;
+
+super::cmd_arg_tests!("pk pkg depgraph");
+
+/// Create a temporary repo with a dependency cycle and an unresolvable dependency.
+fn repo() -> EbuildTempRepo {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("cat/a-1", &["IUSE=u", "DEPEND=cat/b !cat/x", "RDEPEND=u? ( cat/c )"])
+        .unwrap();
+    repo.create_ebuild("cat/b-1", &[]).unwrap();
+    repo.create_ebuild("cat/b-2", &["DEPEND=cat/missing"])
+        .unwrap();
+    repo.create_ebuild("cat/c-1", &["RDEPEND=cat/a"]).unwrap();
+    repo
+}
+
+#[test]
+fn tree() {
+    let repo = repo();
+    let expected = indoc::indoc! {"
+        cat/a-1::repo
+          DEPEND: cat/b -> cat/b-2::repo
+            DEPEND: cat/missing (unresolved)
+          RDEPEND: cat/c -> cat/c-1::repo
+            RDEPEND: cat/a -> cat/a-1::repo (cycle)
+    "};
+    cmd("pk pkg depgraph -k DEPEND,RDEPEND cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+
+    // depth limit
+    let expected = indoc::indoc! {"
+        cat/a-1::repo
+          DEPEND: cat/b -> cat/b-2::repo
+    "};
+    cmd("pk pkg depgraph -k DEPEND -d 1 cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn use_config() {
+    let repo = repo();
+
+    // conditional deps are followed when enabled
+    cmd("pk pkg depgraph -k RDEPEND -u u cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(predicates::str::contains("RDEPEND: cat/c -> cat/c-1::repo"))
+        .stderr("")
+        .success();
+
+    // and skipped when disabled
+    let expected = "cat/a-1::repo\n";
+    cmd("pk pkg depgraph -k RDEPEND --use=-u cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn formats() {
+    let repo = repo();
+    let expected = indoc::indoc! {r#"
+        digraph deps {
+          "cat/a-1::repo";
+          "cat/a-1::repo" -> "cat/c-1::repo" [label="RDEPEND"];
+          "cat/c-1::repo";
+          "cat/c-1::repo" -> "cat/a-1::repo" [label="RDEPEND", color=red];
+        }
+    "#};
+    cmd("pk pkg depgraph -f dot -k RDEPEND cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+
+    let expected = concat!(
+        r#"{"targets":["cat/a-1::repo"],"#,
+        r#""pkgs":{"cat/a-1::repo":["#,
+        r#"{"key":"DEPEND","dep":"cat/b","target":"cat/b-2::repo"}],"#,
+        r#""cat/b-2::repo":[{"key":"DEPEND","dep":"cat/missing","target":null}]},"#,
+        r#""cycles":[]}"#,
+        "\n",
+    );
+    cmd("pk pkg depgraph -f json -k DEPEND cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn depth() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("cat/a-1", &["DEPEND=cat/b cat/d"])
+        .unwrap();
+    repo.create_ebuild("cat/b-1", &["DEPEND=cat/d"]).unwrap();
+    repo.create_ebuild("cat/d-1", &["DEPEND=cat/e"]).unwrap();
+    repo.create_ebuild("cat/e-1", &[]).unwrap();
+
+    // packages first reached past the depth limit are expanded via shallower paths
+    let expected = indoc::indoc! {"
+        cat/a-1::repo
+          DEPEND: cat/b -> cat/b-1::repo
+            DEPEND: cat/d -> cat/d-1::repo
+              DEPEND: cat/e -> cat/e-1::repo
+          DEPEND: cat/d -> cat/d-1::repo (seen)
+    "};
+    cmd("pk pkg depgraph -k DEPEND -d 2 cat/a")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn keys() {
+    let repo = repo();
+
+    // non-dependency keys are rejected
+    for key in ["SLOT", "DEPEND,LICENSE", "unknown"] {
+        cmd("pk pkg depgraph cat/a")
+            .args(["-k", key, "-r", repo.path().as_str()])
+            .assert()
+            .stdout("")
+            .stderr(predicates::str::contains("invalid value"))
+            .failure()
+            .code(2);
+    }
+}
@@ -0,0 +1,201 @@
+use std::fs;
+
+use camino::Utf8Path;
+use pkgcraft::pkg::ebuild::manifest::HashType;
+use pkgcraft::repo::Repository;
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+#[test]
+fn missing_dir() {
+    cmd("pk pkg distclean")
+        .assert()
+        .stdout("")
+        .stderr(contains("--dir <DIR>"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn pkg_targets() {
+    // only entire repos can be targeted
+    cmd("pk pkg distclean -d dir cat/pkg")
+        .assert()
+        .stdout("")
+        .stderr(contains("unexpected argument 'cat/pkg'"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn nonexistent_dir() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    repo.create_ebuild("cat/pkg-1", &[]).unwrap();
+    cmd("pk pkg distclean -d path/to/nonexistent/dir")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("failed reading dir: path/to/nonexistent/dir"))
+        .failure();
+}
+
+#[test]
+fn clean() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::indoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with distfiles"
+        SRC_URI="https://a.com/a.tar.gz https://a.com/b.tar.gz"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+    let manifest = [("a.tar.gz", b"a"), ("b.tar.gz", b"b")]
+        .into_iter()
+        .map(|(name, data)| {
+            let blake2b = HashType::Blake2b.hash(data);
+            let sha512 = HashType::Sha512.hash(data);
+            format!("DIST {name} 1 BLAKE2B {blake2b} SHA512 {sha512}\n")
+        })
+        .collect::<String>();
+    fs::write(repo.path().join("cat/pkg/Manifest"), manifest).unwrap();
+
+    let tmpdir = tempdir().unwrap();
+    let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+    fs::write(dir.join("a.tar.gz"), "a").unwrap();
+    fs::write(dir.join("b.tar.gz"), "x").unwrap();
+    fs::write(dir.join("b.tar.gz.part"), "").unwrap();
+    fs::write(dir.join("c.tar.gz"), "c").unwrap();
+
+    // pretend mode only outputs files
+    cmd("pk pkg distclean -p -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(indoc::formatdoc! {"
+            {dir}/b.tar.gz.part: partial
+            {dir}/c.tar.gz: unreferenced
+        "})
+        .stderr("")
+        .success();
+
+    // checksum verification
+    cmd("pk pkg distclean -p -c -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(contains(format!("{dir}/b.tar.gz: invalid: ")))
+        .stderr("")
+        .success();
+
+    // remove files
+    cmd("pk pkg distclean -c -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    assert!(dir.join("a.tar.gz").exists());
+    assert!(!dir.join("b.tar.gz").exists());
+    assert!(!dir.join("b.tar.gz.part").exists());
+    assert!(!dir.join("c.tar.gz").exists());
+}
+
+#[test]
+fn filters() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    for (cpv, keywords, uri) in
+        [("cat/pkg-1", "~amd64", "a.tar.gz"), ("cat/pkg-2", "amd64", "b.tar.gz")]
+    {
+        let data = indoc::formatdoc! {r#"
+            EAPI=8
+            DESCRIPTION="ebuild with distfiles"
+            SRC_URI="https://a.com/{uri}"
+            SLOT=0
+            KEYWORDS="{keywords}"
+        "#};
+        repo.create_ebuild_from_str(cpv, &data).unwrap();
+    }
+    let profile = repo.path().join("profiles/amd64");
+    fs::create_dir_all(&profile).unwrap();
+    fs::write(profile.join("make.defaults"), "ARCH=amd64\nACCEPT_KEYWORDS=\"${ARCH}\"\n")
+        .unwrap();
+
+    let tmpdir = tempdir().unwrap();
+    let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+    let distdir = dir.join("distfiles");
+    fs::create_dir(&distdir).unwrap();
+    fs::write(distdir.join("a.tar.gz"), "a").unwrap();
+    fs::write(distdir.join("b.tar.gz"), "b").unwrap();
+
+    // nonexistent profile
+    cmd("pk pkg distclean -p --visible unknown -d")
+        .arg(&distdir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("nonexistent profile: unknown"))
+        .failure();
+
+    // unstable packages aren't visible to stable profiles
+    cmd("pk pkg distclean -p --visible amd64 -d")
+        .arg(&distdir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(format!("{distdir}/a.tar.gz: unreferenced\n"))
+        .stderr("")
+        .success();
+
+    // only installed packages
+    let vdb = dir.join("vdb");
+    fs::create_dir_all(vdb.join("cat/pkg-1")).unwrap();
+    cmd("pk pkg distclean -p -d")
+        .arg(&distdir)
+        .arg(format!("--installed={vdb}"))
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(format!("{distdir}/b.tar.gz: unreferenced\n"))
+        .stderr("")
+        .success();
+
+    // nonexistent package database
+    cmd("pk pkg distclean -p -d")
+        .arg(&distdir)
+        .arg(format!("--installed={dir}/nonexistent"))
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("failed reading dir: "))
+        .failure();
+}
+
+#[test]
+fn layout_subdirs() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::indoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with distfiles"
+        SRC_URI="https://a.com/a.tar.gz"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+
+    let tmpdir = tempdir().unwrap();
+    let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+    fs::write(dir.join("layout.conf"), "[structure]\n0=filename-hash BLAKE2B 8\n").unwrap();
+    for path in ["1a/a.tar.gz", "2b/b.tar.gz"] {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+
+    cmd("pk pkg distclean -p -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(format!("{dir}/2b/b.tar.gz: unreferenced\n"))
+        .stderr("")
+        .success();
+}
@@ -1,6 +1,10 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
 use std::time::Duration;
-use std::{env, fs};
+use std::{env, fs, thread};
 
+use pkgcraft::fetch::MirrorLayout;
+use pkgcraft::pkg::ebuild::manifest::HashType;
 use pkgcraft::repo::ebuild::EbuildRepoBuilder;
 use pkgcraft::test::{cmd, test_data};
 use predicates::prelude::*;
@@ -12,6 +16,50 @@ use wiremock::{Mock, MockServer, ResponseTemplate};
 
 super::cmd_arg_tests!("pk pkg fetch");
 
+/// Serve files for a single session using a minimal, anonymous FTP server.
+fn ftp_server(files: &'static [(&'static str, &'static str)]) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+        let (mut ctrl, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(ctrl.try_clone().unwrap());
+        let mut data = None;
+        write!(ctrl, "220 ready\r\n").unwrap();
+
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 0 {
+            let (cmd, arg) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            let file = files.iter().find(|(name, _)| arg == format!("/{name}"));
+            let response = match (cmd, file) {
+                ("USER", _) => "331 password required".to_string(),
+                ("PASS", _) => "230 logged in".to_string(),
+                ("TYPE", _) => "200 binary mode".to_string(),
+                ("SIZE", Some((_, content))) => format!("213 {}", content.len()),
+                ("PASV", _) => {
+                    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+                    let port = listener.local_addr().unwrap().port();
+                    data = Some(listener);
+                    let (p1, p2) = (port >> 8, port & 0xff);
+                    format!("227 Entering Passive Mode (127,0,0,1,{p1},{p2})")
+                }
+                ("RETR", Some((_, content))) => {
+                    write!(ctrl, "150 opening data connection\r\n").unwrap();
+                    let (mut conn, _) = data.take().unwrap().accept().unwrap();
+                    conn.write_all(content.as_bytes()).unwrap();
+                    "226 transfer complete".to_string()
+                }
+                ("QUIT", _) => "221 goodbye".to_string(),
+                _ => "550 unavailable".to_string(),
+            };
+            write!(ctrl, "{response}\r\n").unwrap();
+            line.clear();
+        }
+    });
+
+    port
+}
+
 #[test]
 fn invalid_pkgs() {
     let data = test_data();
@@ -57,18 +105,31 @@ async fn unsupported() {
     let data = indoc::formatdoc! {r#"
         EAPI=8
         DESCRIPTION="ebuild with unsupported URI"
-        SRC_URI="ftp://pkgcraft.pkgcraft/file"
+        SRC_URI="gopher://pkgcraft.pkgcraft/file"
         SLOT=0
     "#};
     repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
 
-    // FTP is not supported
+    // gopher is not supported
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains(
+            "invalid fetchable: unsupported protocol: gopher://pkgcraft.pkgcraft/file",
+        ))
+        .failure()
+        .code(1);
+
+    // sftp lacks a native backend
+    let data = data.replace("gopher://", "sftp://");
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
     cmd("pk pkg fetch")
         .arg(&repo)
         .assert()
         .stdout("")
         .stderr(contains(
-            "invalid fetchable: unsupported protocol: ftp://pkgcraft.pkgcraft/file",
+            "invalid fetchable: unsupported protocol: sftp://pkgcraft.pkgcraft/file",
         ))
         .failure()
         .code(1);
@@ -307,6 +368,185 @@ async fn fetch() {
     assert_eq!(&data, "test2");
 }
 
+#[test]
+fn file_uri() {
+    let tmpdir = tempdir().unwrap();
+    let src = tmpdir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("file1"), "test1").unwrap();
+    let uri = format!("file://{}", src.to_str().unwrap());
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with file SRC_URI"
+        SRC_URI="{uri}/file1 {uri}/file2"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    // nonexistent files fail
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains(format!("fetch failed: {uri}/file2: ")))
+        .failure()
+        .code(1);
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "test1");
+
+    // resume partial files
+    fs::write(src.join("file2"), "test resume").unwrap();
+    fs::write("file2.part", "test").unwrap();
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file2").unwrap();
+    assert_eq!(&data, "test resume");
+}
+
+#[test]
+fn ftp_uri() {
+    let port = ftp_server(&[("file1", "test1")]);
+    let uri = format!("ftp://127.0.0.1:{port}");
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with FTP SRC_URI"
+        SRC_URI="{uri}/file1"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+    let manifest = format!(
+        "DIST file1 5 BLAKE2B {} SHA512 {}\n",
+        HashType::Blake2b.hash(b"test1"),
+        HashType::Sha512.hash(b"test1"),
+    );
+    fs::write(repo.path().join("cat/pkg/Manifest"), manifest).unwrap();
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "test1");
+}
+
+#[test]
+fn verify() {
+    let tmpdir = tempdir().unwrap();
+    let src = tmpdir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("file1"), "test1").unwrap();
+    fs::write(src.join("file2"), "invalid").unwrap();
+    let uri = format!("file://{}", src.to_str().unwrap());
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with Manifest entries"
+        SRC_URI="{uri}/file1 {uri}/file2"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+    let manifest: String = [("file1", b"test1"), ("file2", b"test2")]
+        .into_iter()
+        .map(|(name, data)| {
+            let blake2b = HashType::Blake2b.hash(data);
+            let sha512 = HashType::Sha512.hash(data);
+            format!("DIST {name} 5 BLAKE2B {blake2b} SHA512 {sha512}\n")
+        })
+        .collect();
+    fs::write(repo.path().join("cat/pkg/Manifest"), manifest).unwrap();
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    // resumed downloads include existing data when verifying
+    fs::write("file1.part", "test").unwrap();
+    // mismatched files are quarantined
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("file2: failed verifying: size: expected: 5, got: 7, BLAKE2B: "))
+        .stderr(contains("file2.failed"))
+        .failure()
+        .code(1);
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "test1");
+    assert!(!dir.path().join("file2").exists());
+    let data = fs::read_to_string("file2.failed").unwrap();
+    assert_eq!(&data, "invalid");
+
+    // corrupted prefix data causes failures for resumed downloads
+    fs::remove_file("file1").unwrap();
+    fs::write("file1.part", "xxxx").unwrap();
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("file1: failed verifying: size: expected: 5, got: 5, BLAKE2B: "))
+        .failure()
+        .code(1);
+    assert!(!dir.path().join("file1").exists());
+    assert!(dir.path().join("file1.failed").exists());
+}
+
+#[test]
+fn fetch_command() {
+    let tmpdir = tempdir().unwrap();
+    let src = tmpdir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    fs::write(src.join("file1"), "test1").unwrap();
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with SRC_URI using custom fetch command"
+        SRC_URI="ftp://pkgcraft.pkgcraft/file1"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    // failing command
+    cmd("pk pkg fetch --fetch-command false")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("fetch failed: ftp://pkgcraft.pkgcraft/file1: exit status: 1"))
+        .failure()
+        .code(1);
+
+    // command using the related environment variables
+    let fetch = format!(r#"cp "{}/${{URI##*/}}" "${{DISTDIR}}/${{FILE}}""#, src.display());
+    cmd("pk pkg fetch --command-schemes ftp --fetch-command")
+        .arg(fetch)
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "test1");
+}
+
 #[tokio::test]
 async fn rename() {
     let server = MockServer::start().await;
@@ -458,6 +698,141 @@ async fn custom_mirror() {
     assert_eq!(&data, "test2");
 }
 
+#[tokio::test]
+async fn mirror_layout() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let server = MockServer::start().await;
+    let uri = server.uri();
+
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild using default mirrors"
+        SRC_URI="{uri}/upstream/file1"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+
+    // register default mirror
+    fs::create_dir_all(repo.path().join("profiles")).unwrap();
+    fs::write(repo.path().join("profiles/thirdpartymirrors"), format!("test {uri}/distfiles"))
+        .unwrap();
+
+    // mirror using a filename hash layout
+    let conf = "[structure]\n0=filename-hash BLAKE2B 8\n1=flat\n";
+    let layout = MirrorLayout::parse(conf).unwrap();
+    Mock::given(method("GET"))
+        .and(path("/distfiles/layout.conf"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(conf))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/distfiles/{}", layout.path("file1"))))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"mirrored"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    // upstream URLs fail without using mirrors
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("404 Not Found"))
+        .failure()
+        .code(1);
+
+    // default mirrors use their layout
+    cmd("pk pkg fetch --mirrors")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "mirrored");
+
+    // default mirrors lacking layouts fall back to the gentoo layout
+    fs::remove_file("file1").unwrap();
+    let gentoo = MirrorLayout::gentoo();
+    Mock::given(method("GET"))
+        .and(path(format!("/gentoo/{}", gentoo.path("file1"))))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"gentoo"))
+        .mount(&server)
+        .await;
+    fs::write(repo.path().join("profiles/thirdpartymirrors"), format!("test {uri}/gentoo"))
+        .unwrap();
+    cmd("pk pkg fetch --mirrors")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file1").unwrap();
+    assert_eq!(&data, "gentoo");
+
+    // third party mirrors use their layouts
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild using third party mirrors"
+        SRC_URI="mirror://third/path/to/file2"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+    fs::write(repo.path().join("profiles/thirdpartymirrors"), format!("third {uri}/third"))
+        .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/third/layout.conf"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(conf))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/third/{}", layout.path("file2"))))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"third"))
+        .mount(&server)
+        .await;
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file2").unwrap();
+    assert_eq!(&data, "third");
+
+    // layouts are only determined for mirrors that are tried
+    fs::remove_file("file2").unwrap();
+    let mirrors = format!("third {uri}/third {uri}/unused");
+    fs::write(repo.path().join("profiles/thirdpartymirrors"), mirrors).unwrap();
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let requests = server.received_requests().await.unwrap();
+    assert!(!requests.iter().any(|r| r.url.path().starts_with("/unused")));
+
+    // local mirrors use their layouts
+    fs::remove_file("file2").unwrap();
+    let mirror = tempdir().unwrap();
+    fs::write(mirror.path().join("layout.conf"), conf).unwrap();
+    let file = mirror.path().join(layout.path("file2"));
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(&file, "local").unwrap();
+    let mirrors = format!("third file://{}", mirror.path().to_str().unwrap());
+    fs::write(repo.path().join("profiles/thirdpartymirrors"), mirrors).unwrap();
+    cmd("pk pkg fetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string("file2").unwrap();
+    assert_eq!(&data, "local");
+}
+
 #[tokio::test]
 async fn redirect() {
     let server = MockServer::start().await;
@@ -569,6 +944,69 @@ async fn restrict() {
     assert_eq!(&data, "file");
 }
 
+#[test]
+fn nofetch() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::indoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with restricted files"
+        SRC_URI="https://pkgcraft.pkgcraft/file1 file2"
+        SLOT=0
+        RESTRICT="fetch"
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+
+    let dir = tempdir().unwrap();
+    env::set_current_dir(&dir).unwrap();
+
+    // missing files are output via pkg_nofetch
+    cmd("pk pkg fetch --nofetch")
+        .arg(&repo)
+        .assert()
+        .stdout(contains("The following files must be manually downloaded for cat/pkg-1::"))
+        .stdout(contains("https://pkgcraft.pkgcraft/file1\nfile2\n"))
+        .stderr("")
+        .failure()
+        .code(1);
+
+    // pkg_nofetch isn't run when pretending
+    cmd("pk pkg fetch --nofetch --pretend")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    // only missing files are output
+    fs::write("file1", "").unwrap();
+    cmd("pk pkg fetch --nofetch")
+        .arg(&repo)
+        .assert()
+        .stdout(contains("https://pkgcraft.pkgcraft/file1").not())
+        .stdout(contains("file2"))
+        .stderr("")
+        .failure()
+        .code(1);
+
+    // pkg_nofetch isn't run when all files exist
+    fs::write("file2", "").unwrap();
+    cmd("pk pkg fetch --nofetch")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    // incompatible with forcibly processing restricted files
+    cmd("pk pkg fetch --nofetch --restrict")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("cannot be used with"))
+        .failure()
+        .code(2);
+}
+
 #[tokio::test]
 async fn selective_restrict() {
     let server = MockServer::start().await;
@@ -43,7 +43,7 @@ async fn unsupported() {
     let data = indoc::formatdoc! {r#"
         EAPI=8
         DESCRIPTION="ebuild with unsupported URI"
-        SRC_URI="ftp://pkgcraft.pkgcraft/file"
+        SRC_URI="gopher://pkgcraft.pkgcraft/file"
         SLOT=0
     "#};
     repo.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
@@ -53,7 +53,7 @@ async fn unsupported() {
         .assert()
         .stdout("")
         .stderr(contains(
-            "invalid fetchable: unsupported protocol: ftp://pkgcraft.pkgcraft/file",
+            "invalid fetchable: unsupported protocol: gopher://pkgcraft.pkgcraft/file",
         ))
         .failure()
         .code(1);
@@ -0,0 +1,209 @@
+use std::fs;
+
+use camino::Utf8Path;
+use pkgcraft::fetch::{MirrorLayout, Structure};
+use pkgcraft::pkg::ebuild::manifest::HashType;
+use pkgcraft::repo::Repository;
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use tempfile::tempdir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Return the Manifest entry for a distfile.
+fn manifest_entry(name: &str, data: &[u8]) -> String {
+    let blake2b = HashType::Blake2b.hash(data);
+    let sha512 = HashType::Sha512.hash(data);
+    format!("DIST {name} {} BLAKE2B {blake2b} SHA512 {sha512}\n", data.len())
+}
+
+#[test]
+fn missing_dir() {
+    cmd("pk pkg mirror")
+        .assert()
+        .stdout("")
+        .stderr(contains("--dir <DIR>"))
+        .failure()
+        .code(2);
+}
+
+#[tokio::test]
+async fn populate() {
+    let server = MockServer::start().await;
+    let uri = server.uri();
+
+    for (name, data) in [("file1", "test1"), ("file2", "test2"), ("file3", "test3")] {
+        Mock::given(method("GET"))
+            .and(path(format!("/{name}")))
+            .respond_with(ResponseTemplate::new(200).set_body_string(data))
+            .mount(&server)
+            .await;
+    }
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with mocked SRC_URI"
+        SRC_URI="{uri}/file1"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/a-1", &data).unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with mirror restricted SRC_URI"
+        SRC_URI="{uri}/file2"
+        SLOT=0
+        RESTRICT="mirror"
+    "#};
+    repo.create_ebuild_from_str("cat/b-1", &data).unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with invalid Manifest entry"
+        SRC_URI="{uri}/file3"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/c-1", &data).unwrap();
+    fs::write(repo.path().join("cat/a/Manifest"), manifest_entry("file1", b"test1")).unwrap();
+    fs::write(repo.path().join("cat/b/Manifest"), manifest_entry("file2", b"test2")).unwrap();
+    fs::write(repo.path().join("cat/c/Manifest"), manifest_entry("file3", b"invalid"))
+        .unwrap();
+
+    let tmpdir = tempdir().unwrap();
+    let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+
+    // pretend mode doesn't create files
+    cmd("pk pkg mirror -p -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(format!("{uri}/file1\n{uri}/file3\n"))
+        .stderr("")
+        .success();
+    assert!(!dir.join("layout.conf").exists());
+
+    // populate the mirror, skipping mirror restricted files and failing on invalid files
+    cmd("pk pkg mirror -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("file3: failed verifying: size: expected: 7, got: 5, BLAKE2B: "))
+        .failure()
+        .code(1);
+    let layout = MirrorLayout::from_path(dir).unwrap();
+    assert_ne!(layout, MirrorLayout::default());
+    let data = fs::read_to_string(layout.distfile_path(dir, "file1")).unwrap();
+    assert_eq!(&data, "test1");
+    assert!(!layout.distfile_path(dir, "file2").exists());
+    assert!(!layout.distfile_path(dir, "file3").exists());
+    assert!(
+        dir.join(format!("{}.failed", layout.path("file3")))
+            .exists()
+    );
+
+    // existing files not matching their Manifest hashes are refetched
+    fs::write(layout.distfile_path(dir, "file1"), "tset1").unwrap();
+    cmd("pk pkg mirror -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .arg("cat/a")
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string(layout.distfile_path(dir, "file1")).unwrap();
+    assert_eq!(&data, "test1");
+
+    // cleaning requires targeting an entire repo
+    cmd("pk pkg mirror --clean -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .arg("cat/a")
+        .assert()
+        .stdout("")
+        .stderr(contains("cannot be used with"))
+        .failure()
+        .code(2);
+    cmd("pk pkg mirror --clean -d")
+        .arg(dir)
+        .assert()
+        .stdout("")
+        .stderr(contains("--repo <REPO>"))
+        .failure()
+        .code(2);
+
+    cmd("pk pkg mirror --clean -i -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("cannot be used with"))
+        .failure()
+        .code(2);
+
+    // cleaning is skipped when failures occur
+    let path = layout.distfile_path(dir, "unreferenced");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "data").unwrap();
+    cmd("pk pkg mirror --clean -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("skipping cleaning due to failures"))
+        .failure();
+    assert!(path.exists());
+
+    // unreferenced files are removed when cleaning
+    fs::write(repo.path().join("cat/c/Manifest"), manifest_entry("file3", b"test3")).unwrap();
+    cmd("pk pkg mirror --clean -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    assert!(!path.exists());
+    assert!(layout.distfile_path(dir, "file1").exists());
+    assert!(layout.distfile_path(dir, "file3").exists());
+    assert!(
+        !dir.join(format!("{}.failed", layout.path("file3")))
+            .exists()
+    );
+}
+
+#[tokio::test]
+async fn partial_files() {
+    let server = MockServer::start().await;
+    let uri = server.uri();
+
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    let data = indoc::formatdoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with unavailable SRC_URI"
+        SRC_URI="{uri}/file1"
+        SLOT=0
+    "#};
+    repo.create_ebuild_from_str("cat/a-1", &data).unwrap();
+    fs::write(repo.path().join("cat/a/Manifest"), manifest_entry("file1", b"test1")).unwrap();
+
+    let tmpdir = tempdir().unwrap();
+    let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+    let layout = MirrorLayout::new([Structure::Flat]);
+    layout.to_path(dir).unwrap();
+    let part = dir.join("file1.part");
+    fs::write(&part, "test").unwrap();
+
+    // partial files are kept for resuming after transient failures
+    cmd("pk pkg mirror -d")
+        .arg(dir)
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("404 Not Found"))
+        .failure()
+        .code(1);
+    assert!(part.exists());
+}
@@ -0,0 +1,108 @@
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use tempfile::tempdir;
+
+#[test]
+fn nonexistent_repo() {
+    cmd("pk pkg revdeps -r path/to/nonexistent/repo cat/pkg")
+        .assert()
+        .stdout("")
+        .stderr(predicates::str::contains("nonexistent repo: path/to/nonexistent/repo"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn queries() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("a/b-1", &["SLOT=1"]).unwrap();
+    repo.create_ebuild("a/b-2", &["SLOT=2"]).unwrap();
+    let data = indoc::indoc! {r#"
+        EAPI=8
+        DESCRIPTION="ebuild with revdeps"
+        IUSE="u"
+        SLOT=0
+        DEPEND="a/b:1[x] !a/b:3"
+        RDEPEND="u? ( >=a/b-2:2 )"
+    "#};
+    repo.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+    let cache = tempdir().unwrap();
+    let dir = tempdir().unwrap();
+    let index = cache.path().join("pkgcraft/revdeps");
+
+    // missing index files are built in memory without being written
+    cmd("pk pkg revdeps")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-r", repo.path().as_str(), "a/b-1"])
+        .assert()
+        .stdout("cat/pkg-1::repo: a/b:1[x]\n")
+        .stderr("")
+        .success();
+    assert!(!index.exists());
+
+    // create the index
+    cmd("pk repo revdeps")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-d", dir.path().to_str().unwrap(), repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    assert!(index.exists());
+
+    for (target, expected) in [
+        ("a/b", "cat/pkg-1::repo: >=a/b-2:2\ncat/pkg-1::repo: a/b:1[x]\n"),
+        ("a/b-1", "cat/pkg-1::repo: a/b:1[x]\n"),
+        ("a/b:2", "cat/pkg-1::repo: >=a/b-2:2\n"),
+        ("a/b[x]", "cat/pkg-1::repo: a/b:1[x]\n"),
+    ] {
+        cmd("pk pkg revdeps")
+            .env("XDG_CACHE_HOME", cache.path())
+            .args(["-r", repo.path().as_str()])
+            .arg(target)
+            .assert()
+            .stdout(expected)
+            .stderr("")
+            .success();
+    }
+
+    // no matches
+    for target in ["a/b:2[x]", "c/d"] {
+        cmd("pk pkg revdeps")
+            .env("XDG_CACHE_HOME", cache.path())
+            .args(["-r", repo.path().as_str()])
+            .arg(target)
+            .assert()
+            .stdout("")
+            .stderr("")
+            .success();
+    }
+}
+
+#[test]
+fn invalid_pkgs() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("a/b-1", &[]).unwrap();
+    repo.create_ebuild("cat/pkg-1", &["RDEPEND=a/b"]).unwrap();
+    repo.create_ebuild("cat/pkg-2", &["EAPI=invalid"]).ok();
+    let cache = tempdir().unwrap();
+
+    // matches are output while invalid packages cause failure
+    cmd("pk pkg revdeps")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-r", repo.path().as_str(), "a/b"])
+        .assert()
+        .stdout("cat/pkg-1::repo: a/b\n")
+        .stderr(predicates::str::contains("invalid pkg: cat/pkg-2"))
+        .failure()
+        .code(1);
+
+    // invalid packages are ignored
+    cmd("pk pkg revdeps -i")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-r", repo.path().as_str(), "a/b"])
+        .assert()
+        .stdout("cat/pkg-1::repo: a/b\n")
+        .stderr("")
+        .success();
+}
@@ -4,6 +4,8 @@ mod eclass;
 mod leaf;
 mod license;
 mod list;
+mod lock;
+mod manifest;
 mod metadata;
 mod mirror;
 mod remove;
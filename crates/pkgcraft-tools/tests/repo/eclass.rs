@@ -1,3 +1,6 @@
+mod doc;
+mod graph;
+
 use std::env;
 
 use pkgcraft::repo::Repository;
@@ -0,0 +1,134 @@
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+const ECLASS: &str = indoc::indoc! {r#"
+    # @ECLASS: foo.eclass
+    # @MAINTAINER:
+    # A Developer <dev@gentoo.org>
+    # @SUPPORTED_EAPIS: 8
+    # @BLURB: example eclass
+    # @DESCRIPTION:
+    # Eclass description.
+
+    # @ECLASS_VARIABLE: FOO_VAR
+    # @REQUIRED
+    # @DESCRIPTION:
+    # Variable description.
+
+    # @FUNCTION: foo_func
+    # @USAGE: <arg>
+    # @DESCRIPTION:
+    # Function description.
+    foo_func() { :; }
+
+    # @FUNCTION: _foo_internal
+    # @INTERNAL
+    # @DESCRIPTION:
+    # Internal function.
+    _foo_internal() { :; }
+"#};
+
+#[test]
+fn nonexistent_eclass() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    repo.create_eclass("foo", ECLASS).unwrap();
+    cmd("pk repo eclass doc")
+        .args(["-r", repo.path().as_str()])
+        .arg("bar")
+        .assert()
+        .stdout("")
+        .stderr(contains("unknown eclass: bar"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn invalid() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    repo.create_eclass("foo", "# @ECLASS: foo.eclass\n")
+        .unwrap();
+    cmd("pk repo eclass doc")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("foo: invalid doc: line 1: missing @MAINTAINER"))
+        .failure()
+        .code(1);
+}
+
+#[test]
+fn markdown() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    repo.create_eclass("foo", ECLASS).unwrap();
+    let expected = indoc::indoc! {"
+        # foo.eclass
+
+        example eclass
+
+        ## Description
+
+        Eclass description.
+
+        ## Supported EAPIs
+
+        8
+
+        ## Functions
+
+        ### `foo_func <arg>`
+
+        Function description.
+
+        ## Eclass variables
+
+        ### `FOO_VAR`
+
+        *required*
+
+        Variable description.
+
+        ## Maintainers
+
+        - A Developer <dev@gentoo.org>
+    "};
+
+    cmd("pk repo eclass doc -f markdown")
+        .args(["-r", repo.path().as_str()])
+        .arg("foo")
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+
+    // internal functions are optionally included
+    cmd("pk repo eclass doc -f markdown --internal")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(contains("### `_foo_internal`"))
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn man() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_eclass("foo", ECLASS).unwrap();
+    let dir = tempdir().unwrap();
+
+    cmd("pk repo eclass doc")
+        .args(["-r", repo.path().as_str()])
+        .args(["-o", dir.path().to_str().unwrap()])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    let data = std::fs::read_to_string(dir.path().join("foo.eclass.5")).unwrap();
+    assert!(data.starts_with(".TH \"FOO.ECLASS\" 5 \"\" \"repo\"\n"));
+    assert!(data.contains("foo.eclass \\- example eclass\n"));
+    assert!(data.contains(".TP\n\\fBfoo_func\\fR <arg>\nFunction description.\n"));
+    assert!(data.contains(".TP\n\\fBFOO_VAR\\fR (required)\nVariable description.\n"));
+    assert!(!data.contains("_foo_internal"));
+}
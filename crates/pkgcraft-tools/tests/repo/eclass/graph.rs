@@ -0,0 +1,95 @@
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+
+/// Create a temporary repo with nested eclass inherits.
+fn repo() -> pkgcraft::repo::ebuild::EbuildTempRepo {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_eclass("a", "inherit b").unwrap();
+    repo.create_eclass("b", "# stub eclass").unwrap();
+    repo.create_eclass("c", "# stub eclass").unwrap();
+    for (cpv, eclass) in [("cat/a-1", "a"), ("cat/b-1", "b"), ("cat/c-1", "c")] {
+        let data = indoc::formatdoc! {r#"
+            EAPI=8
+            inherit {eclass}
+            DESCRIPTION="testing eclass graph"
+            SLOT=0
+        "#};
+        repo.create_ebuild_from_str(cpv, &data).unwrap();
+    }
+    repo
+}
+
+#[test]
+fn nonexistent_eclass() {
+    let repo = repo();
+    cmd("pk repo eclass graph --affected nonexistent")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("")
+        .stderr(contains("unknown eclass: nonexistent"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn affected() {
+    let repo = repo();
+    for (eclass, expected) in
+        [("a", "cat/a-1\n"), ("b", "cat/a-1\ncat/b-1\n"), ("c", "cat/c-1\n")]
+    {
+        cmd("pk repo eclass graph --affected")
+            .arg(eclass)
+            .args(["-r", repo.path().as_str()])
+            .assert()
+            .stdout(expected)
+            .stderr("")
+            .success();
+    }
+}
+
+#[test]
+fn inheritors() {
+    let repo = repo();
+    for (eclass, expected) in [("a", ""), ("b", "a\n")] {
+        cmd("pk repo eclass graph --inheritors")
+            .arg(eclass)
+            .args(["-r", repo.path().as_str()])
+            .assert()
+            .stdout(expected)
+            .stderr("")
+            .success();
+    }
+}
+
+#[test]
+fn formats() {
+    let repo = repo();
+    let expected = indoc::indoc! {r#"
+        digraph eclasses {
+          "a" [label="a\n1 pkg"];
+          "b" [label="b\n1 pkg"];
+          "c" [label="c\n1 pkg"];
+          "a" -> "b";
+        }
+    "#};
+    cmd("pk repo eclass graph")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+
+    let expected = concat!(
+        r#"{"eclasses":{"a":{"inherit":["b"],"pkgs":["cat/a-1"]},"#,
+        r#""b":{"inherit":[],"pkgs":["cat/b-1"]},"#,
+        r#""c":{"inherit":[],"pkgs":["cat/c-1"]}}}"#,
+        "\n",
+    );
+    cmd("pk repo eclass graph -f json")
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout(expected)
+        .stderr("")
+        .success();
+}
@@ -0,0 +1,70 @@
+use std::fs;
+
+use pkgcraft::test::{cmd, test_data};
+use predicates::str::contains;
+use tempfile::tempdir;
+
+#[test]
+fn nonexistent_repo() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().to_str().unwrap();
+
+    cmd("pk repo lock nonexistent")
+        .args(["--config", config_dir])
+        .assert()
+        .stdout("")
+        .stderr(contains("nonexistent repo: nonexistent"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn local_repo() {
+    let data = test_data();
+    let repo = data.ebuild_repo("qa-primary").unwrap();
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().to_str().unwrap();
+    let lockfile = temp_dir.path().join("repos.lock");
+    let lockfile = lockfile.to_str().unwrap();
+
+    cmd("pk repo add -n test")
+        .args(["--config", config_dir])
+        .arg(repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    // local repos don't have revisions
+    cmd("pk repo lock -f")
+        .arg(lockfile)
+        .args(["--config", config_dir])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    assert_eq!(fs::read_to_string(lockfile).unwrap(), "");
+
+    // locked syncing falls back to regular syncing for local repos
+    cmd("pk repo sync --locked --lockfile")
+        .arg(lockfile)
+        .args(["--config", config_dir])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn missing_lockfile() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().to_str().unwrap();
+
+    cmd("pk repo sync --locked --lockfile nonexistent")
+        .args(["--config", config_dir])
+        .assert()
+        .stdout("")
+        .stderr(contains("failed loading lockfile: nonexistent"))
+        .failure()
+        .code(2);
+}
@@ -0,0 +1,77 @@
+use std::fs;
+
+use pkgcraft::repo::Repository;
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+
+#[test]
+fn nonexistent_repo() {
+    cmd("pk repo manifest path/to/nonexistent/repo")
+        .assert()
+        .stdout("")
+        .stderr(contains("nonexistent repo: path/to/nonexistent/repo"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn generate_and_verify() {
+    let mut repo = EbuildRepoBuilder::new().build().unwrap();
+    repo.create_ebuild("cat/pkg-1", &[]).unwrap();
+
+    // missing top-level Manifest
+    cmd("pk repo manifest")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("failed reading"))
+        .failure();
+
+    // generate the Manifest tree
+    for opt in ["-g", "--generate"] {
+        cmd("pk repo manifest")
+            .arg(opt)
+            .arg(&repo)
+            .assert()
+            .stdout("")
+            .stderr("")
+            .success();
+    }
+
+    // valid tree
+    cmd("pk repo manifest")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    // unsigned top-level Manifest
+    cmd("pk repo manifest --signed")
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr(contains("unsigned Manifest"))
+        .failure();
+
+    // unlisted file
+    repo.create_ebuild("cat/pkg-2", &[]).unwrap();
+    cmd("pk repo manifest")
+        .arg(&repo)
+        .assert()
+        .stdout("cat/pkg/pkg-2.ebuild: unlisted file\n")
+        .stderr("")
+        .failure()
+        .code(1);
+
+    // missing file
+    fs::remove_file(repo.path().join("cat/pkg/pkg-1.ebuild")).unwrap();
+    cmd("pk repo manifest")
+        .arg(&repo)
+        .assert()
+        .stdout(contains("cat/pkg/pkg-1.ebuild: missing file"))
+        .stderr("")
+        .failure()
+        .code(1);
+}
@@ -220,6 +220,60 @@ fn multiple() {
     assert!(!path.join("other").exists());
 }
 
+#[test]
+fn formats() {
+    let mut temp = EbuildRepoBuilder::new().build().unwrap();
+    temp.create_ebuild("cat/a-1", &[]).unwrap();
+    temp.create_ebuild("cat/b-1", &[]).unwrap();
+    let path = temp.path();
+
+    // pms flat list cache
+    cmd("pk repo metadata regen --format pms")
+        .arg(path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let cache = path.join("metadata/cache");
+    assert!(cache.join("cat/a-1").exists());
+    assert!(cache.join("cat/b-1").exists());
+    let data = fs::read_to_string(cache.join("cat/a-1")).unwrap();
+    assert_eq!(data.lines().count(), 22);
+
+    // binary single file cache
+    cmd("pk repo metadata regen --format binary")
+        .arg(path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let cache = path.join("metadata/pkgcraft-cache");
+    assert!(cache.is_file());
+    assert!(!path.join("metadata/pkgcraft-cache.staging").exists());
+
+    // verifying existing entries doesn't alter the cache
+    let prev_modified = fs::metadata(&cache).unwrap().modified().unwrap();
+    cmd("pk repo metadata regen --format binary")
+        .arg(path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let modified = fs::metadata(&cache).unwrap().modified().unwrap();
+    assert_eq!(modified, prev_modified);
+
+    // outdated entries are removed
+    fs::remove_dir_all(path.join("cat/b")).unwrap();
+    cmd("pk repo metadata regen --format binary")
+        .arg(path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let modified = fs::metadata(&cache).unwrap().modified().unwrap();
+    assert_ne!(modified, prev_modified);
+}
+
 #[test]
 fn pkg_with_invalid_eapi() {
     let mut config = Config::default();
@@ -114,3 +114,40 @@ fn current_dir_repo() {
     assert_ordered_eq!(file_to_list("revdeps/pdepend/a/b"), ["cat/pkg-1:u1"]);
     assert_ordered_eq!(file_to_list("revdeps/pdepend/c/d"), ["cat/pkg-1:u1+!u2"]);
 }
+
+#[test]
+fn index() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("a/b-1", &[]).unwrap();
+    repo.create_ebuild("cat/pkg-1", &["DEPEND=a/b"]).unwrap();
+    let cache = tempdir().unwrap();
+    let dir = tempdir().unwrap();
+    env::set_current_dir(dir.path()).unwrap();
+
+    // the index is created in the user cache directory
+    cmd("pk repo revdeps")
+        .env("XDG_CACHE_HOME", cache.path())
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let id = repo.path().as_str().trim_start_matches('/');
+    let index = cache.path().join("pkgcraft/revdeps").join(id).join("index");
+    let data = fs::read_to_string(&index).unwrap();
+    assert!(data.contains("cat/pkg-1 "));
+    assert!(data.contains("\tDEPEND\t\ta/b\n"));
+
+    // changed packages update the index
+    repo.create_ebuild("cat/pkg-1", &["DEPEND=c/d"]).unwrap();
+    cmd("pk repo revdeps")
+        .env("XDG_CACHE_HOME", cache.path())
+        .arg(&repo)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let data = fs::read_to_string(&index).unwrap();
+    assert!(data.contains("\tDEPEND\t\tc/d\n"));
+    assert!(dir.path().join("revdeps/depend/c/d").exists());
+}
@@ -0,0 +1,86 @@
+use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+use pkgcraft::test::cmd;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+#[test]
+fn nonexistent_repo() {
+    cmd("pk search -r path/to/nonexistent/repo pkg")
+        .assert()
+        .stdout("")
+        .stderr(contains("nonexistent repo: path/to/nonexistent/repo"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn missing_terms() {
+    cmd("pk search")
+        .assert()
+        .stdout("")
+        .stderr(contains("required arguments were not provided"))
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn queries() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("dev-util/foo-1", &["DESCRIPTION=a build tool"])
+        .unwrap();
+    repo.create_ebuild("dev-util/bar-1", &["DESCRIPTION=foo helper library"])
+        .unwrap();
+    repo.create_ebuild("app-misc/baz-1", &["DESCRIPTION=misc tool"])
+        .unwrap();
+    let cache = tempdir().unwrap();
+
+    for (args, expected) in [
+        (
+            vec!["foo"],
+            "dev-util/foo::repo: a build tool\ndev-util/bar::repo: foo helper library\n",
+        ),
+        (vec!["tool", "misc"], "app-misc/baz::repo: misc tool\n"),
+        (vec!["nonexistent"], ""),
+        (vec!["-n", "1", "foo"], "dev-util/foo::repo: a build tool\n"),
+        (vec!["--field", "name", "foo"], "dev-util/foo::repo: a build tool\n"),
+        (vec!["-f", "{CPN}: {SCORE}", "library"], "dev-util/bar: 16\n"),
+        (vec!["-f", "{PN} {LONGDESCRIPTION} [USE]", "baz"], "baz <unset> \n"),
+    ] {
+        cmd("pk search")
+            .env("XDG_CACHE_HOME", cache.path())
+            .args(["-r", repo.path().as_str()])
+            .args(args)
+            .assert()
+            .stdout(expected)
+            .stderr("")
+            .success();
+    }
+}
+
+#[test]
+fn update() {
+    let mut repo = EbuildRepoBuilder::new().name("repo").build().unwrap();
+    repo.create_ebuild("dev-util/foo-1", &["DESCRIPTION=a build tool"])
+        .unwrap();
+    let cache = tempdir().unwrap();
+    let index_dir = cache.path().join("pkgcraft/search");
+
+    // queries don't write outdated indexes
+    cmd("pk search foo")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("dev-util/foo::repo: a build tool\n")
+        .success();
+    assert!(!index_dir.exists());
+
+    // indexes are explicitly updated
+    cmd("pk search --update foo")
+        .env("XDG_CACHE_HOME", cache.path())
+        .args(["-r", repo.path().as_str()])
+        .assert()
+        .stdout("dev-util/foo::repo: a build tool\n")
+        .stderr("")
+        .success();
+    assert!(index_dir.exists());
+}
@@ -11,7 +11,10 @@ use crate::utils::find_existing_path;
 use crate::{Error, shell};
 pub(crate) use repo::RepoConfig;
 
+mod lock;
+pub use lock::RepoLock;
 mod portage;
+mod post_sync;
 mod repo;
 pub(crate) use repo::ConfigRepos;
 
@@ -390,6 +393,66 @@ impl Config<ConfigInner> {
         }
     }
 
+    /// Sync repos, running post-sync actions for repos with altered content.
+    ///
+    /// Note that this finalizes the config when post-sync actions are run.
+    pub fn sync<I>(&mut self, values: I) -> crate::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: std::fmt::Display,
+    {
+        let changed = self.repos_mut()?.sync(values)?;
+        self.post_sync(changed)
+    }
+
+    /// Sync repos to locked revisions, running post-sync actions for repos with altered content.
+    ///
+    /// Note that this finalizes the config when post-sync actions are run.
+    pub fn sync_locked<I>(&mut self, values: I, lock: &RepoLock) -> crate::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: std::fmt::Display,
+    {
+        let changed = self.repos_mut()?.sync_locked(values, lock)?;
+        self.post_sync(changed)
+    }
+
+    /// Reload altered repos and run their post-sync actions.
+    fn post_sync(&mut self, changed: IndexSet<String>) -> crate::Result<()> {
+        // reload altered repos so their metadata reflects the synced content
+        let settings = self.settings().clone();
+        let mut repos = vec![];
+        for id in changed {
+            self.inner.repos.reload(&id, &settings)?;
+            let repo = self.repos().get(&id)?;
+            if repo.config().post_sync().is_some() {
+                repos.push(repo.clone());
+            }
+        }
+
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        self.finalize()?;
+
+        let mut failed = vec![];
+        for repo in repos {
+            if let Some(actions) = repo.config().post_sync()
+                && let Err(e) = actions.run(&repo, self.path())
+            {
+                failed.push(format!("{}: {e}", repo.id()));
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            let errors = failed.join("\n\t");
+            Err(Error::Config(format!("failed post-sync actions:\n\t{errors}")))
+        }
+    }
+
     // TODO: Move to ConfigFinalized once repo is generic over Config.
 
     /// Finalize the config repos and start the build pool.
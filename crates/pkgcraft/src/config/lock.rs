@@ -0,0 +1,150 @@
+use std::{fmt, fs};
+
+use camino::Utf8Path;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+use crate::sync::Revision;
+
+/// Locked repo entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct LockedRepo {
+    pub(super) sync: String,
+    #[serde(flatten)]
+    pub(super) revision: Revision,
+}
+
+/// Lockfile recording the resolved revisions of synced repos.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RepoLock {
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    repos: IndexMap<String, LockedRepo>,
+}
+
+impl fmt::Display for RepoLock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", toml::to_string(self).unwrap())
+    }
+}
+
+impl RepoLock {
+    /// Load a lockfile from a path.
+    pub fn from_path<P: AsRef<Utf8Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("failed loading lockfile: {path}: {e}")))?;
+        toml::from_str(&data)
+            .map_err(|e| Error::Config(format!("invalid lockfile: {path}: {e}")))
+    }
+
+    /// Write the lockfile to a path.
+    pub fn to_path<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .map_err(|e| Error::Config(format!("failed writing lockfile: {path}: {e}")))
+    }
+
+    /// Return the locked entry for a repo if it exists.
+    pub(crate) fn get(&self, id: &str) -> Option<&LockedRepo> {
+        self.repos.get(id)
+    }
+
+    /// Add a locked entry for a repo, replacing any existing entry.
+    pub(crate) fn insert(&mut self, id: &str, sync: String, revision: Revision) {
+        self.repos
+            .insert(id.to_string(), LockedRepo { sync, revision });
+        self.repos.sort_unstable_keys();
+    }
+
+    /// Update the lockfile with entries from another lockfile.
+    pub fn update(&mut self, other: Self) {
+        self.repos.extend(other.repos);
+        self.repos.sort_unstable_keys();
+    }
+
+    /// Return true if the lockfile has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.repos.is_empty()
+    }
+
+    /// Return the number of locked repos.
+    pub fn len(&self) -> usize {
+        self.repos.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::NamedTempFile;
+
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    #[test]
+    fn lockfile() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+
+        // nonexistent
+        let r = RepoLock::from_path("nonexistent");
+        assert_err_re!(r, "failed loading lockfile: nonexistent");
+
+        // empty
+        let lock = RepoLock::from_path(path).unwrap();
+        assert!(lock.is_empty());
+
+        // invalid
+        fs::write(path, "[repos.a]\nsync = 1\n").unwrap();
+        let r = RepoLock::from_path(path);
+        assert_err_re!(r, "invalid lockfile: ");
+
+        // tarball revisions are unsupported
+        let data = indoc::indoc! {r#"
+            [repos.a]
+            sync = "tar+https://pkgcraft.pkgcraft/a.tar.gz"
+            blake2b = "abcdef"
+        "#};
+        fs::write(path, data).unwrap();
+        let r = RepoLock::from_path(path);
+        assert_err_re!(r, "invalid lockfile: ");
+
+        // valid
+        let data = indoc::indoc! {r#"
+            [repos.a]
+            sync = "https://github.com/pkgcraft/a.git"
+            commit = "0123456789abcdef0123456789abcdef01234567"
+
+            [repos.b]
+            sync = "https://github.com/pkgcraft/b.git"
+            commit = "fedcba9876543210fedcba9876543210fedcba98"
+        "#};
+        fs::write(path, data).unwrap();
+        let lock = RepoLock::from_path(path).unwrap();
+        assert_eq!(lock.len(), 2);
+        assert!(matches!(lock.get("a").unwrap().revision, Revision::Git { .. }));
+        assert!(matches!(lock.get("b").unwrap().revision, Revision::Git { .. }));
+
+        // round trip
+        lock.to_path(path).unwrap();
+        assert_eq!(RepoLock::from_path(path).unwrap(), lock);
+
+        // updating replaces existing entries
+        let mut updated = RepoLock::default();
+        updated.insert(
+            "a",
+            "https://github.com/pkgcraft/a.git".to_string(),
+            Revision::Git {
+                commit: "fedcba9876543210fedcba9876543210fedcba98".to_string(),
+            },
+        );
+        let mut lock = lock;
+        lock.update(updated);
+        assert_eq!(lock.len(), 2);
+        assert_eq!(
+            lock.get("a").unwrap().revision.to_string(),
+            "fedcba9876543210fedcba9876543210fedcba98"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::process::Command;
+
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+use crate::repo::ebuild::cache::Cache;
+use crate::repo::ebuild::{RevDepIndex, SearchIndex};
+use crate::repo::{Repo, Repository};
+
+use super::ConfigPath;
+
+/// Actions run after a sync alters a repo's content.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct PostSync {
+    /// Regenerate the metadata cache.
+    metadata: bool,
+    /// Rebuild the revdeps cache.
+    revdeps: bool,
+    /// Update the package search index.
+    search: bool,
+    /// External scripts run from the repo directory.
+    scripts: Vec<Utf8PathBuf>,
+}
+
+impl PostSync {
+    /// Run all enabled actions for a repo.
+    pub(crate) fn run(&self, repo: &Repo, path: &ConfigPath) -> crate::Result<()> {
+        if let Repo::Ebuild(repo) = repo {
+            if self.metadata {
+                repo.metadata().cache().regen(repo).run()?;
+            }
+
+            if self.revdeps {
+                let index_path = RevDepIndex::default_path(&path.cache, repo);
+                let index = repo.revdeps_index(&index_path, false)?;
+                index
+                    .cache()
+                    .serialize_to_qa(index_path.parent().unwrap())?;
+            }
+
+            if self.search {
+                let index_path = SearchIndex::default_path(&path.cache, repo);
+                repo.search_index(&index_path, false)?;
+            }
+        }
+
+        for script in &self.scripts {
+            let status = Command::new(script)
+                .current_dir(repo.path())
+                .env("PKGCRAFT_REPO_ID", repo.id())
+                .env("PKGCRAFT_REPO_PATH", repo.path())
+                .status()
+                .map_err(|e| Error::RepoSync(format!("failed running: {script}: {e}")))?;
+
+            if !status.success() {
+                return Err(Error::RepoSync(format!("failed running: {script}: {status}")));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -14,6 +14,9 @@ use crate::repo::set::RepoSet;
 use crate::repo::{Repo, RepoFormat, Repository};
 use crate::sync::Syncer;
 
+use super::lock::RepoLock;
+use super::post_sync::PostSync;
+
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct RepoConfig {
@@ -22,6 +25,8 @@ pub struct RepoConfig {
     pub(crate) format: RepoFormat,
     pub(crate) priority: Option<i32>,
     pub(crate) sync: Option<Syncer>,
+    #[serde(rename = "post-sync")]
+    pub(crate) post_sync: Option<PostSync>,
 }
 
 impl fmt::Display for RepoConfig {
@@ -37,6 +42,7 @@ impl From<RepoFormat> for RepoConfig {
             format,
             priority: Default::default(),
             sync: Default::default(),
+            post_sync: Default::default(),
         }
     }
 }
@@ -70,14 +76,39 @@ impl RepoConfig {
         }
     }
 
-    /// Sync repository to its configured location.
-    pub(crate) fn sync(&self) -> crate::Result<()> {
+    /// Sync repository to its configured location, returning true if it was altered.
+    pub(crate) fn sync(&self) -> crate::Result<bool> {
         if let Some(syncer) = &self.sync {
             futures::executor::block_on(syncer.sync(&self.location))
         } else {
-            Ok(())
+            Ok(false)
+        }
+    }
+
+    /// Sync repository to a locked revision, returning true if it was altered.
+    fn sync_locked(&self, id: &str, lock: &RepoLock) -> crate::Result<bool> {
+        let Some(syncer) = &self.sync else {
+            return Ok(false);
+        };
+
+        match lock.get(id) {
+            Some(locked) if locked.sync != syncer.to_string() => {
+                Err(Error::RepoSync(format!("locked sync URI mismatch: {}", locked.sync)))
+            }
+            Some(locked) => futures::executor::block_on(
+                syncer.sync_revision(&self.location, &locked.revision),
+            ),
+            None if syncer.lockable() => {
+                Err(Error::RepoSync("missing locked revision".to_string()))
+            }
+            None => self.sync(),
         }
     }
+
+    /// Return the actions to run after a sync alters the repo.
+    pub(crate) fn post_sync(&self) -> Option<&PostSync> {
+        self.post_sync.as_ref()
+    }
 }
 
 /// Builder for adding repos to a configuration.
@@ -267,8 +298,69 @@ impl ConfigRepos {
         }
     }
 
+    /// Sync repos, returning the IDs of repos with altered content.
+    pub fn sync<I>(&mut self, values: I) -> crate::Result<IndexSet<String>>
+    where
+        I: IntoIterator,
+        I::Item: std::fmt::Display,
+    {
+        self.sync_repos(values, None)
+    }
+
+    /// Sync repos to their locked revisions, returning the IDs of repos with altered content.
+    pub fn sync_locked<I>(
+        &mut self,
+        values: I,
+        lock: &RepoLock,
+    ) -> crate::Result<IndexSet<String>>
+    where
+        I: IntoIterator,
+        I::Item: std::fmt::Display,
+    {
+        self.sync_repos(values, Some(lock))
+    }
+
+    /// Create a lockfile recording the resolved revisions of synced repos.
+    pub fn lock<I>(&self, values: I) -> crate::Result<RepoLock>
+    where
+        I: IntoIterator,
+        I::Item: std::fmt::Display,
+    {
+        let mut repos = vec![];
+        for id in values {
+            let id = id.to_string();
+            let repo = self
+                .repos
+                .get(&id)
+                .ok_or_else(|| Error::NonexistentRepo(id.clone()))?;
+            repos.push((id, repo.config()));
+        }
+
+        // lock all repos if none were passed
+        if repos.is_empty() {
+            repos.extend(self.repos.iter().map(|(id, r)| (id.clone(), r.config())));
+        }
+
+        let mut lock = RepoLock::default();
+        for (id, config) in repos {
+            if let Some(syncer) = &config.sync
+                && let Some(revision) = syncer
+                    .revision(&config.location)
+                    .map_err(|e| Error::Config(format!("failed locking: {id}: {e}")))?
+            {
+                lock.insert(&id, syncer.to_string(), revision);
+            }
+        }
+
+        Ok(lock)
+    }
+
     // TODO: add concurrent syncing support with output progress
-    pub fn sync<I>(&mut self, values: I) -> crate::Result<()>
+    fn sync_repos<I>(
+        &mut self,
+        values: I,
+        lock: Option<&RepoLock>,
+    ) -> crate::Result<IndexSet<String>>
     where
         I: IntoIterator,
         I::Item: std::fmt::Display,
@@ -291,15 +383,25 @@ impl ConfigRepos {
             repos.extend(self.nonexistent.iter().map(|(id, c)| (id.clone(), c)));
         }
 
+        let mut changed = IndexSet::new();
         let mut failed = vec![];
         for (name, repo) in repos {
-            if let Err(e) = repo.sync() {
-                failed.push((name, e));
+            let result = match lock {
+                Some(lock) => repo.sync_locked(&name, lock),
+                None => repo.sync(),
+            };
+
+            match result {
+                Ok(true) => {
+                    changed.insert(name);
+                }
+                Ok(false) => (),
+                Err(e) => failed.push((name, e)),
             }
         }
 
         if failed.is_empty() {
-            Ok(())
+            Ok(changed)
         } else {
             let errors = failed
                 .iter()
@@ -309,6 +411,25 @@ impl ConfigRepos {
         }
     }
 
+    /// Reload a repo from its config, replacing any existing instance.
+    pub(crate) fn reload(
+        &mut self,
+        id: &str,
+        settings: &Arc<super::Settings>,
+    ) -> crate::Result<()> {
+        let config = if let Some(repo) = self.repos.shift_remove(id) {
+            self.configured.retain(|r| r.id() != id);
+            repo.config().clone()
+        } else if let Some(config) = self.nonexistent.shift_remove(id) {
+            config
+        } else {
+            return Err(Error::NonexistentRepo(id.to_string()));
+        };
+
+        let repo = Repo::from_config(id, &config)?;
+        self.extend([repo], settings)
+    }
+
     /// Return true if no repos exist.
     pub fn is_empty(&self) -> bool {
         self.repos.is_empty()
@@ -405,7 +526,9 @@ impl<'a> Iterator for ReposIter<'a> {
 
 #[cfg(test)]
 mod tests {
-    use tempfile::NamedTempFile;
+    use std::os::unix::fs::PermissionsExt;
+
+    use tempfile::{NamedTempFile, tempdir};
 
     use crate::config::Config;
     use crate::repo::FakeRepo;
@@ -455,9 +578,27 @@ mod tests {
             format = "ebuild"
             priority = 0
             sync = "tar+https://pkgcraft.pkgcraft/repo.tar.gz"
+
+            [post-sync]
+            metadata = true
+            revdeps = true
+            search = true
+            scripts = ["/path/to/script"]
         "#};
         fs::write(&file, data).unwrap();
         RepoConfig::from_path(path).unwrap();
+
+        // invalid (unknown post-sync action)
+        let data = indoc::indoc! {r#"
+            location = "/path/to/repo"
+            format = "ebuild"
+
+            [post-sync]
+            unknown = true
+        "#};
+        fs::write(&file, data).unwrap();
+        let r = RepoConfig::from_path(path);
+        assert_err_re!(r, "unknown field `unknown`");
     }
 
     #[test]
@@ -477,4 +618,47 @@ mod tests {
         let repos: [&str; 0] = [];
         assert!(config.repos_mut().unwrap().sync(repos).is_ok());
     }
+
+    #[test]
+    fn post_sync() {
+        let data = test_data();
+        let repo = data.ebuild_repo("qa-primary").unwrap();
+        let tmpdir = tempdir().unwrap();
+        let prefix = tmpdir.path().to_str().unwrap();
+        let mut config = Config::new("pkgcraft", prefix);
+
+        // create a script that records the synced repo
+        let marker = tmpdir.path().join("marker");
+        let script = tmpdir.path().join("script");
+        let data = indoc::formatdoc! {r#"
+            #!/bin/sh
+            echo "$PKGCRAFT_REPO_ID" > {}
+        "#, marker.display()};
+        fs::write(&script, data).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // create a local repo config running the script after syncing
+        let config_dir = config.path().config.join("repos");
+        fs::create_dir_all(&config_dir).unwrap();
+        let data = indoc::formatdoc! {r#"
+            location = "{}"
+            format = "ebuild"
+            sync = "{}"
+
+            [post-sync]
+            scripts = ["{}"]
+        "#, config.path().db.join("repos/test"), repo.path(), script.display()};
+        fs::write(config_dir.join("test"), data).unwrap();
+        config.load_path(prefix).unwrap();
+
+        // initial sync alters the repo, running post-sync actions
+        config.sync(["test"]).unwrap();
+        assert_eq!(fs::read_to_string(&marker).unwrap().trim(), "test");
+        assert!(config.repos().get("test").is_ok());
+
+        // unaltered repos skip post-sync actions
+        fs::remove_file(&marker).unwrap();
+        config.sync(["test"]).unwrap();
+        assert!(!marker.exists());
+    }
 }
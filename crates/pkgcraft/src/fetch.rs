@@ -1,17 +1,18 @@
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::fs;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
-use camino::Utf8Path;
-use futures::StreamExt;
+use camino::{Utf8Path, Utf8PathBuf};
+use dashmap::DashMap;
 use indexmap::IndexSet;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use reqwest::{Client, ClientBuilder, StatusCode};
+use reqwest::{Client, ClientBuilder};
 use serde::{Deserialize, Serialize};
-use tokio::io::AsyncWriteExt;
+use tempfile::tempdir;
 use tracing::warn;
 use url::Url;
 
@@ -19,39 +20,30 @@ use crate::dep::Uri;
 use crate::eapi::Feature::SrcUriUnrestrict;
 use crate::error::Error;
 use crate::pkg::ebuild::EbuildPkg;
-use crate::pkg::ebuild::manifest::HashType;
+use crate::pkg::ebuild::manifest::ManifestEntry;
 use crate::pkg::{Package, RepoPackage};
 use crate::repo::Repository;
 use crate::repo::ebuild::Mirror;
 use crate::traits::Contains;
 
+mod backend;
+pub use backend::{
+    CommandBackend, FetchBackend, FileBackend, FtpBackend, HttpBackend, Target, TargetFile,
+};
+mod distdir;
+pub use distdir::{CleanReason, DistdirCleaner, Distfile, installed_pkgs};
+mod layout;
+mod nofetch;
+pub use layout::{LayoutHash, MirrorLayout, Structure};
+pub use nofetch::{ManualDownload, Nofetch};
+
 static SUPPORTED_PROTOCOLS: LazyLock<HashSet<String>> = LazyLock::new(|| {
-    ["http", "https", "mirror"]
+    ["file", "ftp", "http", "https", "mirror"]
         .into_iter()
         .map(Into::into)
         .collect()
 });
 
-/// Convert an error into an error string.
-trait IntoReason {
-    fn into_reason(self) -> String;
-}
-
-impl IntoReason for reqwest::Error {
-    fn into_reason(self) -> String {
-        if self.is_timeout() {
-            "request timed out".to_string()
-        } else if self.is_builder() {
-            "unsupported URI".to_string()
-        } else if let Some(value) = self.status() {
-            value.to_string()
-        } else {
-            // drop URL from error to avoid potentially leaking authentication parameters
-            self.without_url().to_string()
-        }
-    }
-}
-
 /// Wrapper for URI objects to generate valid URLs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fetchable {
@@ -59,6 +51,7 @@ pub struct Fetchable {
     rename: Option<String>,
     mirrors: IndexSet<Mirror>,
     default_mirror: String,
+    mirror_restricted: bool,
 }
 
 impl Fetchable {
@@ -133,6 +126,7 @@ impl Fetchable {
             rename: uri.rename().map(Into::into),
             mirrors,
             default_mirror,
+            mirror_restricted,
         };
 
         if fetch_restricted {
@@ -143,12 +137,12 @@ impl Fetchable {
     }
 
     /// Create a new fetchable using a mirror.
-    fn mirrored(&self, mirror: &Mirror) -> crate::Result<Self> {
-        // TODO: properly implement GLEP 75 by fetching and parsing layout.conf
-        let path = if mirror.name() == "gentoo" {
-            let filename = self.filename();
-            let hash = HashType::Blake2b.hash(filename.as_bytes());
-            format!("{}/{filename}", &hash[..2])
+    ///
+    /// Distfile mirrors with known layouts use the related file name path, otherwise
+    /// the URL path is appended to the mirror.
+    fn mirrored(&self, mirror: &Mirror, layout: Option<&MirrorLayout>) -> crate::Result<Self> {
+        let path = if let Some(layout) = layout {
+            layout.path(self.filename())
         } else {
             self.url.path().to_string()
         };
@@ -158,6 +152,7 @@ impl Fetchable {
             rename: self.rename.clone(),
             mirrors: Default::default(),
             default_mirror: Default::default(),
+            mirror_restricted: self.mirror_restricted,
         })
     }
 
@@ -182,6 +177,11 @@ impl Fetchable {
         })
     }
 
+    /// Return true if the [`Fetchable`] can't be distributed via mirrors.
+    pub fn mirror_restricted(&self) -> bool {
+        self.mirror_restricted
+    }
+
     /// Return the mirrors for the [`Fetchable`].
     pub fn mirrors(&self) -> &IndexSet<Mirror> {
         &self.mirrors
@@ -227,7 +227,7 @@ impl<'a> Iterator for IterFetchable<'a> {
                     None
                 } else {
                     self.fetchable
-                        .mirrored(mirror)
+                        .mirrored(mirror, None)
                         .ok()
                         .map(|f| (Some(mirror), f))
                 }
@@ -273,9 +273,11 @@ impl fmt::Display for Fetchable {
     }
 }
 
-/// HTTP client wrapper handling mirror support.
+/// Fetch support using per-scheme backends and handling mirror support.
 pub struct Fetcher {
     client: Client,
+    backends: HashMap<String, Arc<dyn FetchBackend>>,
+    layouts: DashMap<Mirror, Option<MirrorLayout>>,
 }
 
 impl Deref for Fetcher {
@@ -306,23 +308,121 @@ impl Fetcher {
         let client = builder
             .build()
             .map_err(|e| Error::InvalidValue(format!("failed creating fetcher: {e}")))?;
-        Ok(Self { client })
+
+        // register default backends
+        let http: Arc<dyn FetchBackend> = Arc::new(HttpBackend::new(client.clone()));
+        let backends = [
+            ("file", Arc::new(FileBackend) as Arc<dyn FetchBackend>),
+            ("ftp", Arc::new(FtpBackend)),
+            ("http", http.clone()),
+            ("https", http),
+        ]
+        .into_iter()
+        .map(|(scheme, backend)| (scheme.to_string(), backend))
+        .collect();
+
+        Ok(Self {
+            client,
+            backends,
+            layouts: Default::default(),
+        })
+    }
+
+    /// Use a backend for a URL scheme, replacing any existing backend.
+    pub fn backend<B: FetchBackend + 'static>(mut self, scheme: &str, backend: B) -> Self {
+        self.backends.insert(scheme.to_string(), Arc::new(backend));
+        self
+    }
+
+    /// Fetch the file related to a [`Fetchable`] using its related backend.
+    async fn fetch_internal(&self, f: &Fetchable, target: &mut Target) -> crate::Result<()> {
+        let scheme = f.url.scheme();
+        let Some(backend) = self.backends.get(scheme) else {
+            return Err(Error::FetchFailed {
+                url: f.url.to_string(),
+                reason: format!("unsupported protocol: {scheme}"),
+            });
+        };
+
+        backend.fetch(f, target).await
+    }
+
+    /// Fetch and parse the layout.conf file for a mirror using its related backend.
+    async fn fetch_layout(&self, mirror: &Mirror) -> crate::Result<MirrorLayout> {
+        let f = Fetchable {
+            url: mirror.get_url("layout.conf")?,
+            rename: None,
+            mirrors: Default::default(),
+            default_mirror: Default::default(),
+            mirror_restricted: false,
+        };
+
+        let tmp_dir =
+            tempdir().map_err(|e| Error::IO(format!("failed creating tempdir: {e}")))?;
+        let path = Utf8PathBuf::from_path_buf(tmp_dir.path().join("layout.conf"))
+            .map_err(|p| Error::IO(format!("invalid utf8 path: {p:?}")))?;
+        let mut target = Target::new(&path, &ProgressBar::hidden(), None);
+        self.fetch_internal(&f, &mut target).await?;
+
+        let data = fs::read_to_string(&path)
+            .map_err(|e| Error::IO(format!("failed reading layout: {path}: {e}")))?;
+        MirrorLayout::parse(&data)
+    }
+
+    /// Determine the layout for a mirror as defined by GLEP 75.
+    ///
+    /// Default distfile mirrors lacking a valid layout fall back to the layout used by
+    /// Gentoo mirrors while other mirrors without layouts use URL paths.
+    async fn layout(&self, mirror: &Mirror, default: bool) -> Option<MirrorLayout> {
+        if let Some(layout) = self.layouts.get(mirror) {
+            return layout.clone();
+        }
+
+        let layout = match self.fetch_layout(mirror).await {
+            Ok(layout) => Some(layout),
+            Err(e) if default => {
+                warn!("{}: falling back to default layout: {e}", mirror.name());
+                Some(MirrorLayout::gentoo())
+            }
+            Err(Error::FetchFailed { .. }) => None,
+            Err(e) => {
+                warn!("{}: invalid layout.conf: {e}", mirror.name());
+                None
+            }
+        };
+
+        self.layouts.insert(mirror.clone(), layout.clone());
+        layout
     }
 
     /// Fetch the file related to a [`Fetchable`], iterating over mirrors.
+    ///
+    /// If a manifest entry is passed, its hashes are computed while downloading and
+    /// mismatched files are moved to a quarantine path using a `.failed` suffix.
     pub async fn fetch(
         &self,
         fetchable: &Fetchable,
         path: &Utf8Path,
         mb: &MultiProgress,
-        size: Option<u64>,
+        entry: Option<&ManifestEntry>,
     ) -> crate::Result<()> {
         let mut result = Ok(());
         let pb = mb.add(progress_bar(mb.is_hidden()));
+        let mut target = Target::new(path, &pb, entry);
 
         let mut fetchables = fetchable.into_iter();
-        while let Some((mirror, f)) = fetchables.next() {
-            match self.fetch_internal(&f, path, &pb, size).await {
+        while let Some((mirror, mut f)) = fetchables.next() {
+            // determine the layout for a mirror as defined by GLEP 75 when it's first tried
+            if let Some(mirror) = mirror
+                && let Some(layout) = self
+                    .layout(mirror, mirror.name() == fetchable.default_mirror)
+                    .await
+                && let Ok(value) = fetchable.mirrored(mirror, Some(&layout))
+            {
+                f = value;
+            }
+
+            match self.fetch_internal(&f, &mut target).await {
                 Err(e @ Error::FetchFailed { .. }) => {
                     // skip all alternative URLs from failed, default mirrors
                     if let Some(name) = mirror.map(|x| x.name())
@@ -343,89 +443,12 @@ impl Fetcher {
             }
         }
 
-        mb.remove(&pb);
-        result
-    }
-
-    /// Fetch the file related to a [`Fetchable`].
-    async fn fetch_internal(
-        &self,
-        f: &Fetchable,
-        path: &Utf8Path,
-        pb: &ProgressBar,
-        mut size: Option<u64>,
-    ) -> crate::Result<()> {
-        // determine the file position to start at supporting resumed downloads
-        let mut request = self.get(f.url.clone());
-        let mut position = if let Ok(meta) = tokio::fs::metadata(path).await {
-            // determine the target size for existing files without manifest entries
-            if size.is_none() {
-                let response = self.get(f.url.clone()).send().await;
-                size = response.ok().and_then(|r| r.content_length());
-            }
-
-            // check if completed or invalid
-            let current_size = meta.len();
-            if current_size != 0 && current_size == size.unwrap_or_default() {
-                return Ok(());
-            } else if let Some(value) = size
-                && current_size > value
-            {
-                return Err(Error::InvalidValue(format!("file larger than expected: {path}")));
-            }
-
-            // request remaining data assuming sequential downloads
-            request = request.header("Range", format!("bytes={current_size}-"));
-            current_size
-        } else {
-            0
-        };
-
-        let response = request
-            .send()
-            .await
-            .and_then(|r| r.error_for_status())
-            .map_err(|e| Error::FetchFailed {
-                url: f.url.to_string(),
-                reason: e.into_reason(),
-            })?;
-
-        // create file or open it for appending
-        let mut file = match response.status() {
-            StatusCode::PARTIAL_CONTENT => {
-                pb.set_message(format!("Resuming {f}"));
-                tokio::fs::OpenOptions::new().append(true).open(path).await
-            }
-            _ => {
-                pb.set_message(format!("Downloading {f}"));
-                position = 0;
-                tokio::fs::File::create(path).await
-            }
-        }?;
-
-        // initialize progress bar
-        // enable completion progress if content size is available
-        if let Some(value) = size.or(response.content_length()) {
-            pb.set_length(value);
-        }
-        pb.set_position(position);
-        // reset progress bar state so resumed download speed is accurate
-        pb.reset();
-
-        // download chunks while tracking progress
-        let mut stream = response.bytes_stream();
-        while let Some(item) = stream.next().await {
-            let chunk = item.map_err(|e| {
-                Error::InvalidValue(format!("error while downloading file: {e}"))
-            })?;
-            file.write_all(&chunk).await?;
-            position += chunk.len() as u64;
-            // TODO: handle progress differently for unsized downloads?
-            pb.set_position(position);
+        // verify the file against its manifest entry
+        if result.is_ok() {
+            result = target.verify().await;
         }
 
-        file.flush().await?;
-        pb.finish_and_clear();
-        Ok(())
+        mb.remove(&pb);
+        result
     }
 }
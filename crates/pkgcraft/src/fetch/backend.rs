@@ -0,0 +1,642 @@
+use std::process::Stdio;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use indicatif::ProgressBar;
+use reqwest::{Client, StatusCode};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::error::Error;
+use crate::pkg::ebuild::manifest::{ManifestEntry, ManifestHasher};
+
+use super::Fetchable;
+
+/// Convert an error into an error string.
+trait IntoReason {
+    fn into_reason(self) -> String;
+}
+
+impl IntoReason for reqwest::Error {
+    fn into_reason(self) -> String {
+        if self.is_timeout() {
+            "request timed out".to_string()
+        } else if self.is_builder() {
+            "unsupported URI".to_string()
+        } else if let Some(value) = self.status() {
+            value.to_string()
+        } else {
+            // drop URL from error to avoid potentially leaking authentication parameters
+            self.without_url().to_string()
+        }
+    }
+}
+
+impl IntoReason for std::io::Error {
+    fn into_reason(self) -> String {
+        self.to_string()
+    }
+}
+
+/// Create a fetch failure error for a [`Fetchable`].
+fn failed<R: IntoReason>(f: &Fetchable, err: R) -> Error {
+    Error::FetchFailed {
+        url: f.url.to_string(),
+        reason: err.into_reason(),
+    }
+}
+
+/// Determine the existing file size for resumed downloads.
+///
+/// Returns None when the file is already complete.
+async fn resume_position(path: &Utf8Path, size: Option<u64>) -> crate::Result<Option<u64>> {
+    let current_size = match tokio::fs::metadata(path).await {
+        Ok(meta) => meta.len(),
+        Err(_) => return Ok(Some(0)),
+    };
+
+    if current_size != 0 && current_size == size.unwrap_or_default() {
+        Ok(None)
+    } else if let Some(value) = size
+        && current_size > value
+    {
+        Err(Error::InvalidValue(format!("file larger than expected: {path}")))
+    } else {
+        Ok(Some(current_size))
+    }
+}
+
+/// Run blocking file hashing outside of the async runtime's worker threads.
+async fn blocking<F, T>(func: F) -> crate::Result<T>
+where
+    F: FnOnce() -> crate::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(func)
+        .await
+        .map_err(|e| Error::IO(format!("failed hashing: {e}")))?
+}
+
+/// Download target verifying data against its Manifest entry while streaming.
+pub struct Target {
+    path: Utf8PathBuf,
+    pb: ProgressBar,
+    entry: Option<ManifestEntry>,
+    hasher: Option<ManifestHasher>,
+}
+
+impl Target {
+    pub(super) fn new(
+        path: &Utf8Path,
+        pb: &ProgressBar,
+        entry: Option<&ManifestEntry>,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            pb: pb.clone(),
+            entry: entry.cloned(),
+            hasher: None,
+        }
+    }
+
+    /// Return the path the file is downloaded to.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Return the expected file size if known.
+    pub fn size(&self) -> Option<u64> {
+        self.entry.as_ref().map(|x| x.size())
+    }
+
+    /// Return the progress bar for the download.
+    pub fn progress(&self) -> &ProgressBar {
+        &self.pb
+    }
+
+    /// Open the file for writing, appending to existing data when resuming.
+    ///
+    /// Existing data is hashed before appending so resumed downloads are fully verified.
+    pub async fn open(&mut self, position: u64) -> crate::Result<TargetFile<'_>> {
+        self.hasher = self.entry.as_ref().map(|x| x.hasher());
+        let file = if position > 0 {
+            if let Some(mut hasher) = self.hasher.take() {
+                let path = self.path.clone();
+                let hasher = blocking(move || {
+                    hasher.update_path(&path, Some(position))?;
+                    Ok(hasher)
+                })
+                .await?;
+                self.hasher = Some(hasher);
+            }
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(&self.path)
+                .await
+        } else {
+            tokio::fs::File::create(&self.path).await
+        }?;
+
+        Ok(TargetFile {
+            file,
+            hasher: self.hasher.as_mut(),
+            pb: &self.pb,
+            position,
+        })
+    }
+
+    /// Return the path mismatched files are moved to.
+    fn quarantine_path(&self) -> Utf8PathBuf {
+        let path = self.path.as_str();
+        let path = path.strip_suffix(".part").unwrap_or(path);
+        Utf8PathBuf::from(format!("{path}.failed"))
+    }
+
+    /// Verify the downloaded file against its Manifest entry, quarantining it on failure.
+    ///
+    /// Files that weren't streamed, e.g. previously completed downloads or those
+    /// fetched by external commands, are hashed from disk.
+    pub(super) async fn verify(mut self) -> crate::Result<()> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(());
+        };
+
+        let result = match self.hasher.take() {
+            Some(hasher) => hasher.verify(),
+            None => {
+                let (entry, path) = (entry.clone(), self.path.clone());
+                blocking(move || entry.verify_path(path)).await
+            }
+        };
+
+        result.map_err(|e| {
+            let path = self.quarantine_path();
+            match std::fs::rename(&self.path, &path) {
+                Ok(_) => Error::InvalidValue(format!("{e}: quarantined: {path}")),
+                Err(_) => e,
+            }
+        })
+    }
+}
+
+/// Open download file that hashes data and tracks progress while writing.
+pub struct TargetFile<'a> {
+    file: tokio::fs::File,
+    hasher: Option<&'a mut ManifestHasher>,
+    pb: &'a ProgressBar,
+    position: u64,
+}
+
+impl TargetFile<'_> {
+    /// Write a chunk of data to the file.
+    pub async fn write(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.file.write_all(data).await?;
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(data);
+        }
+        self.position += data.len() as u64;
+        // TODO: handle progress differently for unsized downloads?
+        self.pb.set_position(self.position);
+        Ok(())
+    }
+
+    /// Flush all written data to the file.
+    pub async fn finish(mut self) -> crate::Result<()> {
+        self.file.flush().await?;
+        self.pb.finish_and_clear();
+        Ok(())
+    }
+}
+
+/// Initialize a progress bar for a download.
+fn start_progress(pb: &ProgressBar, f: &Fetchable, size: Option<u64>, position: u64) {
+    if position > 0 {
+        pb.set_message(format!("Resuming {f}"));
+    } else {
+        pb.set_message(format!("Downloading {f}"));
+    }
+
+    // enable completion progress if content size is available
+    if let Some(value) = size {
+        pb.set_length(value);
+    }
+    pb.set_position(position);
+    // reset progress bar state so resumed download speed is accurate
+    pb.reset();
+}
+
+/// Backend used to download files for a given set of URL schemes.
+pub trait FetchBackend: Send + Sync {
+    /// Download the file for a [`Fetchable`] to a target.
+    ///
+    /// Existing data at the target path is resumed from if supported by the backend.
+    /// Data should be written via [`Target::open`] so it's verified while streaming.
+    fn fetch<'a>(
+        &'a self,
+        f: &'a Fetchable,
+        target: &'a mut Target,
+    ) -> BoxFuture<'a, crate::Result<()>>;
+}
+
+/// HTTP(S) backend.
+#[derive(Debug, Clone)]
+pub struct HttpBackend {
+    client: Client,
+}
+
+impl HttpBackend {
+    /// Create a backend using a given client.
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    async fn fetch_internal(&self, f: &Fetchable, target: &mut Target) -> crate::Result<()> {
+        // determine the target size for existing files without manifest entries
+        let path = target.path();
+        let mut size = target.size();
+        if size.is_none() && path.exists() {
+            let response = self.client.get(f.url.clone()).send().await;
+            size = response.ok().and_then(|r| r.content_length());
+        }
+
+        // determine the file position to start at supporting resumed downloads
+        let Some(mut position) = resume_position(path, size).await? else {
+            return Ok(());
+        };
+
+        // request remaining data assuming sequential downloads
+        let mut request = self.client.get(f.url.clone());
+        if position > 0 {
+            request = request.header("Range", format!("bytes={position}-"));
+        }
+
+        let response = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| failed(f, e))?;
+
+        // restart the download if the server doesn't support partial content
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            position = 0;
+        }
+
+        start_progress(target.progress(), f, size.or(response.content_length()), position);
+        let mut file = target.open(position).await?;
+
+        // download chunks while tracking progress
+        let mut stream = response.bytes_stream();
+        while let Some(item) = stream.next().await {
+            let chunk = item.map_err(|e| {
+                Error::InvalidValue(format!("error while downloading file: {e}"))
+            })?;
+            file.write(&chunk).await?;
+        }
+
+        file.finish().await
+    }
+}
+
+impl FetchBackend for HttpBackend {
+    fn fetch<'a>(
+        &'a self,
+        f: &'a Fetchable,
+        target: &'a mut Target,
+    ) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(self.fetch_internal(f, target))
+    }
+}
+
+/// Local file backend for file:// URLs.
+#[derive(Debug, Default, Clone)]
+pub struct FileBackend;
+
+impl FileBackend {
+    async fn fetch_internal(&self, f: &Fetchable, target: &mut Target) -> crate::Result<()> {
+        let src = f
+            .url
+            .to_file_path()
+            .map_err(|_| failed(f, std::io::Error::other("invalid file path")))?;
+        let mut src = tokio::fs::File::open(&src)
+            .await
+            .map_err(|e| failed(f, e))?;
+        let src_size = src.metadata().await.map_err(|e| failed(f, e))?.len();
+        let size = target.size().or(Some(src_size));
+
+        let Some(mut position) = resume_position(target.path(), size).await? else {
+            return Ok(());
+        };
+
+        // skip existing data when resuming
+        if position > 0 && position <= src_size {
+            src.seek(std::io::SeekFrom::Start(position)).await?;
+        } else {
+            position = 0;
+        }
+
+        start_progress(target.progress(), f, size, position);
+        let mut file = target.open(position).await?;
+
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = src.read(&mut buf).await.map_err(|e| failed(f, e))?;
+            if n == 0 {
+                break;
+            }
+            file.write(&buf[..n]).await?;
+        }
+
+        file.finish().await
+    }
+}
+
+impl FetchBackend for FileBackend {
+    fn fetch<'a>(
+        &'a self,
+        f: &'a Fetchable,
+        target: &'a mut Target,
+    ) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(self.fetch_internal(f, target))
+    }
+}
+
+/// FTP control connection.
+struct FtpControl {
+    reader: BufReader<tokio::net::tcp::OwnedReadHalf>,
+    writer: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl FtpControl {
+    /// Read a potentially multiline response, returning its code and final line.
+    async fn response(&mut self) -> std::io::Result<(u16, String)> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(std::io::Error::other("connection closed"));
+        }
+
+        let code = line.get(..3).and_then(|s| s.parse().ok()).ok_or_else(|| {
+            std::io::Error::other(format!("invalid response: {}", line.trim()))
+        })?;
+
+        // consume multiline responses
+        if line.as_bytes().get(3) == Some(&b'-') {
+            let end = format!("{code} ");
+            loop {
+                line.clear();
+                if self.reader.read_line(&mut line).await? == 0 {
+                    return Err(std::io::Error::other("connection closed"));
+                } else if line.starts_with(&end) {
+                    break;
+                }
+            }
+        }
+
+        Ok((code, line.trim().to_string()))
+    }
+
+    /// Send a command, returning its response.
+    async fn command(&mut self, cmd: &str) -> std::io::Result<(u16, String)> {
+        self.writer
+            .write_all(format!("{cmd}\r\n").as_bytes())
+            .await?;
+        self.response().await
+    }
+
+    /// Send a command, erroring out if the response code doesn't match.
+    async fn expect(&mut self, cmd: &str, codes: &[u16]) -> std::io::Result<(u16, String)> {
+        let (code, msg) = self.command(cmd).await?;
+        if codes.contains(&code) {
+            Ok((code, msg))
+        } else {
+            Err(std::io::Error::other(msg))
+        }
+    }
+
+    /// Verify a data transfer completed successfully.
+    async fn expect_transfer(&mut self) -> std::io::Result<()> {
+        match self.response().await? {
+            (226 | 250, _) => Ok(()),
+            (_, msg) => Err(std::io::Error::other(msg)),
+        }
+    }
+}
+
+/// Parse the data connection port from a passive mode response.
+fn pasv_port(msg: &str) -> Option<u16> {
+    let start = msg.find('(')?;
+    let end = msg[start..].find(')')? + start;
+    let fields: Vec<u8> = msg[start + 1..end]
+        .split(',')
+        .map(|s| s.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match &fields[..] {
+        [_, _, _, _, p1, p2] => Some(u16::from(*p1) << 8 | u16::from(*p2)),
+        _ => None,
+    }
+}
+
+/// Anonymous FTP backend using passive mode transfers.
+#[derive(Debug, Default, Clone)]
+pub struct FtpBackend;
+
+impl FtpBackend {
+    async fn fetch_internal(&self, f: &Fetchable, target: &mut Target) -> crate::Result<()> {
+        let host = f
+            .url
+            .host_str()
+            .ok_or_else(|| failed(f, std::io::Error::other("missing host")))?;
+        let port = f.url.port().unwrap_or(21);
+        let stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| failed(f, e))?;
+        let peer = stream.peer_addr()?.ip();
+        let (reader, writer) = stream.into_split();
+        let mut ctrl = FtpControl {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        let result = async {
+            match ctrl.response().await? {
+                (220, _) => (),
+                (_, msg) => return Err(std::io::Error::other(msg)),
+            }
+
+            // log in, defaulting to anonymous access
+            let user = match f.url.username() {
+                "" => "anonymous",
+                s => s,
+            };
+            let pass = f.url.password().unwrap_or("anonymous@");
+            if ctrl.expect(&format!("USER {user}"), &[230, 331]).await?.0 == 331 {
+                ctrl.expect(&format!("PASS {pass}"), &[230, 202]).await?;
+            }
+            ctrl.expect("TYPE I", &[200]).await?;
+
+            // determine the target size if the server supports it
+            let remote = f.url.path();
+            let size = match ctrl.command(&format!("SIZE {remote}")).await? {
+                (213, msg) => target
+                    .size()
+                    .or(msg.split_whitespace().nth(1).and_then(|s| s.parse().ok())),
+                _ => target.size(),
+            };
+
+            let Some(mut position) = resume_position(target.path(), size)
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?
+            else {
+                return Ok(());
+            };
+
+            // open the data connection using the control connection's address
+            let (_, msg) = ctrl.expect("PASV", &[227]).await?;
+            let port = pasv_port(&msg).ok_or_else(|| {
+                std::io::Error::other(format!("invalid PASV response: {msg}"))
+            })?;
+            let mut data = TcpStream::connect((peer, port)).await?;
+
+            // request remaining data, restarting if unsupported
+            if position > 0 && ctrl.command(&format!("REST {position}")).await?.0 != 350 {
+                position = 0;
+            }
+            ctrl.expect(&format!("RETR {remote}"), &[125, 150]).await?;
+
+            start_progress(target.progress(), f, size, position);
+            let mut file = target
+                .open(position)
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+            let mut buf = vec![0; 64 * 1024];
+            loop {
+                let n = data.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                file.write(&buf[..n])
+                    .await
+                    .map_err(|e| std::io::Error::other(e.to_string()))?;
+            }
+            file.finish()
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            drop(data);
+
+            ctrl.expect_transfer().await?;
+            ctrl.command("QUIT").await.ok();
+            Ok(())
+        }
+        .await;
+
+        result.map_err(|e| failed(f, e))
+    }
+}
+
+impl FetchBackend for FtpBackend {
+    fn fetch<'a>(
+        &'a self,
+        f: &'a Fetchable,
+        target: &'a mut Target,
+    ) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(self.fetch_internal(f, target))
+    }
+}
+
+/// External command backend similar to FETCHCOMMAND and RESUMECOMMAND support.
+///
+/// Commands are run via `sh -c` with the URI, DISTDIR, and FILE environment
+/// variables set to the URL, target directory, and target file name respectively.
+#[derive(Debug, Clone)]
+pub struct CommandBackend {
+    fetch: String,
+    resume: Option<String>,
+}
+
+impl CommandBackend {
+    /// Create a backend using a given fetch command.
+    pub fn new<S: std::fmt::Display>(fetch: S) -> Self {
+        Self {
+            fetch: fetch.to_string(),
+            resume: None,
+        }
+    }
+
+    /// Set the command used to resume partial downloads.
+    pub fn resume<S: std::fmt::Display>(mut self, value: S) -> Self {
+        self.resume = Some(value.to_string());
+        self
+    }
+
+    async fn fetch_internal(&self, f: &Fetchable, target: &mut Target) -> crate::Result<()> {
+        let (path, pb, size) = (target.path(), target.progress(), target.size());
+        let Some(position) = resume_position(path, size).await? else {
+            return Ok(());
+        };
+
+        // fallback to refetching when resuming isn't supported
+        let cmd = match (position, self.resume.as_deref()) {
+            (1.., Some(cmd)) => cmd,
+            (1.., None) => {
+                tokio::fs::remove_file(path).await?;
+                self.fetch.as_str()
+            }
+            _ => self.fetch.as_str(),
+        };
+
+        start_progress(pb, f, size, position);
+        let (dir, file) = (path.parent().unwrap_or(Utf8Path::new(".")), path.file_name());
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .env("URI", f.url.as_str())
+            .env("DISTDIR", dir)
+            .env("FILE", file.unwrap_or_default())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| failed(f, e))?;
+
+        if !output.status.success() {
+            let msg = String::from_utf8_lossy(&output.stderr);
+            let reason = match msg.trim().lines().last() {
+                Some(line) => format!("{}: {line}", output.status),
+                None => output.status.to_string(),
+            };
+            return Err(Error::FetchFailed { url: f.url.to_string(), reason });
+        }
+
+        if let Ok(meta) = tokio::fs::metadata(path).await {
+            pb.set_position(meta.len());
+        }
+        pb.finish_and_clear();
+        Ok(())
+    }
+}
+
+impl FetchBackend for CommandBackend {
+    fn fetch<'a>(
+        &'a self,
+        f: &'a Fetchable,
+        target: &'a mut Target,
+    ) -> BoxFuture<'a, crate::Result<()>> {
+        Box::pin(self.fetch_internal(f, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasv() {
+        assert_eq!(pasv_port("227 Entering Passive Mode (127,0,0,1,4,1)"), Some(1025));
+        assert_eq!(pasv_port("227 Entering Passive Mode (127,0,0,1,255,255)"), Some(65535));
+        assert_eq!(pasv_port("227 Entering Passive Mode (127,0,0,1,256,1)"), None);
+        assert_eq!(pasv_port("227 Entering Passive Mode (127,0,0,1,4)"), None);
+        assert_eq!(pasv_port("227 Entering Passive Mode"), None);
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::{HashMap, HashSet};
+use std::{fmt, fs};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use rayon::prelude::*;
+
+use crate::Error;
+use crate::dep::Cpv;
+use crate::files::sorted_dir_list_utf8;
+use crate::pkg::ebuild::EbuildPkg;
+use crate::pkg::ebuild::manifest::ManifestEntry;
+
+/// Reason a distfile is targeted for cleaning.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CleanReason {
+    /// File isn't referenced by any package.
+    Unreferenced,
+    /// File is an incomplete download.
+    Partial,
+    /// File doesn't match its Manifest entry.
+    Invalid(String),
+}
+
+impl fmt::Display for CleanReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unreferenced => write!(f, "unreferenced"),
+            Self::Partial => write!(f, "partial"),
+            Self::Invalid(err) => write!(f, "invalid: {err}"),
+        }
+    }
+}
+
+/// Distfile targeted for cleaning.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Distfile {
+    path: Utf8PathBuf,
+    reason: CleanReason,
+}
+
+impl Distfile {
+    /// Return the path of the distfile.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Return the reason the distfile is targeted for cleaning.
+    pub fn reason(&self) -> &CleanReason {
+        &self.reason
+    }
+
+    /// Remove the distfile.
+    pub fn remove(&self) -> crate::Result<()> {
+        let path = &self.path;
+        fs::remove_file(path).map_err(|e| Error::IO(format!("failed removing: {path}: {e}")))
+    }
+}
+
+impl fmt::Display for Distfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.reason)
+    }
+}
+
+/// Return the package versions installed in a package database, e.g. /var/db/pkg.
+///
+/// Entries that aren't valid package directories, such as in-progress merges, are skipped.
+pub fn installed_pkgs<P: AsRef<Utf8Path>>(path: P) -> crate::Result<HashSet<Cpv>> {
+    let mut cpvs = HashSet::new();
+    for category in sorted_dir_list_utf8(path.as_ref())? {
+        if !category.path().is_dir() {
+            continue;
+        }
+        for pkg in sorted_dir_list_utf8(category.path())? {
+            let cpv = format!("{}/{}", category.file_name(), pkg.file_name());
+            if let Ok(cpv) = Cpv::try_new(cpv)
+                && pkg.path().is_dir()
+            {
+                cpvs.insert(cpv);
+            }
+        }
+    }
+    Ok(cpvs)
+}
+
+/// Return the non-hidden files under a directory, including those in subdirectories.
+fn files(dir: &Utf8Path) -> crate::Result<Vec<Utf8PathBuf>> {
+    let mut files = vec![];
+    for entry in sorted_dir_list_utf8(dir)? {
+        let path = entry.path();
+        if entry.file_name().starts_with('.') {
+            continue;
+        } else if path.is_dir() {
+            files.extend(self::files(path)?);
+        } else if path.is_file() {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// Determine distfiles that can be removed from a distfile directory.
+#[derive(Debug)]
+pub struct DistdirCleaner {
+    path: Utf8PathBuf,
+    checksums: bool,
+    distfiles: HashMap<String, Option<ManifestEntry>>,
+}
+
+impl DistdirCleaner {
+    /// Create a cleaner for a distfile directory.
+    pub fn new<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            checksums: false,
+            distfiles: Default::default(),
+        }
+    }
+
+    /// Verify file checksums against their Manifest entries.
+    pub fn checksums(mut self, value: bool) -> Self {
+        self.checksums = value;
+        self
+    }
+
+    /// Mark all distfiles referenced by a package.
+    pub fn add_pkg(&mut self, pkg: &EbuildPkg) {
+        let manifest = pkg.manifest();
+        for name in pkg.distfiles() {
+            let entry = manifest.get(name).cloned();
+            match self.distfiles.get_mut(name) {
+                Some(value @ None) => *value = entry,
+                Some(Some(_)) => (),
+                None => {
+                    self.distfiles.insert(name.to_string(), entry);
+                }
+            }
+        }
+    }
+
+    /// Return the number of referenced distfiles.
+    pub fn len(&self) -> usize {
+        self.distfiles.len()
+    }
+
+    /// Return true if no distfiles are referenced.
+    pub fn is_empty(&self) -> bool {
+        self.distfiles.is_empty()
+    }
+
+    /// Determine the cleaning status for a file.
+    fn status(&self, name: &str, path: &Utf8Path) -> Option<CleanReason> {
+        // leftover files from interrupted or failed downloads
+        for (suffix, reason) in [
+            (".part", CleanReason::Partial),
+            (".failed", CleanReason::Invalid("failed download".to_string())),
+        ] {
+            if let Some(name) = name.strip_suffix(suffix) {
+                if self.distfiles.contains_key(name) {
+                    return Some(reason);
+                } else {
+                    return Some(CleanReason::Unreferenced);
+                }
+            }
+        }
+
+        let entry = match self.distfiles.get(name) {
+            None => return Some(CleanReason::Unreferenced),
+            Some(None) => return None,
+            Some(Some(entry)) => entry,
+        };
+
+        let size = match fs::metadata(path) {
+            Ok(meta) => meta.len(),
+            Err(e) => return Some(CleanReason::Invalid(e.to_string())),
+        };
+
+        if size < entry.size() {
+            Some(CleanReason::Partial)
+        } else if size > entry.size() {
+            let err = format!("size mismatch: expected: {}, got: {size}", entry.size());
+            Some(CleanReason::Invalid(err))
+        } else if self.checksums {
+            entry
+                .verify_path(path)
+                .err()
+                .map(|e| CleanReason::Invalid(e.to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Scan the distfile directory for files that can be removed.
+    ///
+    /// Subdirectories are scanned to support GLEP 75 mirror layouts.
+    pub fn scan(&self) -> crate::Result<Vec<Distfile>> {
+        // the mirror layout file isn't a distfile
+        let layout = self.path.join("layout.conf");
+        let paths = files(&self.path)?;
+        let mut distfiles: Vec<_> = paths
+            .into_par_iter()
+            .filter(|path| path != &layout)
+            .filter_map(|path| {
+                let reason = self.status(path.file_name()?, &path)?;
+                Some(Distfile { path, reason })
+            })
+            .collect();
+        distfiles.sort();
+        Ok(distfiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::config::Config;
+    use crate::pkg::ebuild::manifest::HashType;
+    use crate::repo::Repository;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    #[test]
+    fn scan() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            DESCRIPTION="ebuild with distfiles"
+            SRC_URI="https://a.com/a.tar.gz https://a.com/b.tar.gz https://a.com/c.tar.gz"
+            SLOT=0
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+        let entry = |name: &str, data: &[u8]| {
+            let hashes = [HashType::Blake2b, HashType::Sha512];
+            let values = hashes.iter().map(|h| format!("{h} {}", h.hash(data)));
+            let values = values.collect::<Vec<_>>().join(" ");
+            format!("DIST {name} {} {values}\n", data.len())
+        };
+        let manifest =
+            [entry("a.tar.gz", b"a"), entry("b.tar.gz", b"bbb"), entry("c.tar.gz", b"c")]
+                .join("");
+        fs::write(repo.path().join("cat/pkg/Manifest"), manifest).unwrap();
+
+        let tmpdir = tempdir().unwrap();
+        let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+
+        // nonexistent dir
+        let cleaner = DistdirCleaner::new(dir.join("nonexistent"));
+        assert_err_re!(cleaner.scan(), "failed reading dir: ");
+
+        fs::write(dir.join("a.tar.gz"), "a").unwrap();
+        fs::write(dir.join("b.tar.gz"), "b").unwrap();
+        fs::write(dir.join("c.tar.gz"), "x").unwrap();
+        fs::write(dir.join("c.tar.gz.part"), "").unwrap();
+        fs::write(dir.join("d.tar.gz"), "d").unwrap();
+        fs::write(dir.join(".hidden"), "").unwrap();
+        fs::write(dir.join("layout.conf"), "[structure]\n0=flat\n").unwrap();
+        fs::create_dir(dir.join("e0")).unwrap();
+        fs::write(dir.join("e0/e.tar.gz"), "e").unwrap();
+
+        // no referenced files
+        let mut cleaner = DistdirCleaner::new(dir);
+        assert!(cleaner.is_empty());
+        let files = cleaner.scan().unwrap();
+        assert_eq!(files.len(), 6);
+        assert!(
+            files
+                .iter()
+                .all(|x| x.reason() == &CleanReason::Unreferenced)
+        );
+
+        // referenced files without checksum verification
+        let pkg = repo.get_pkg("cat/pkg-1").unwrap();
+        cleaner.add_pkg(&pkg);
+        assert_eq!(cleaner.len(), 3);
+        let files: Vec<_> = cleaner
+            .scan()
+            .unwrap()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            files,
+            [
+                format!("{dir}/b.tar.gz: partial"),
+                format!("{dir}/c.tar.gz.part: partial"),
+                format!("{dir}/d.tar.gz: unreferenced"),
+                format!("{dir}/e0/e.tar.gz: unreferenced"),
+            ]
+        );
+
+        // checksum verification
+        let cleaner = cleaner.checksums(true);
+        let files = cleaner.scan().unwrap();
+        assert_eq!(files.len(), 5);
+        let file = files
+            .iter()
+            .find(|x| x.path().ends_with("c.tar.gz"))
+            .unwrap();
+        assert!(matches!(file.reason(), CleanReason::Invalid(_)));
+
+        // removal
+        for file in &files {
+            file.remove().unwrap();
+        }
+        assert!(cleaner.scan().unwrap().is_empty());
+        assert!(dir.join("a.tar.gz").exists());
+        assert!(dir.join("layout.conf").exists());
+    }
+
+    #[test]
+    fn installed() {
+        let tmpdir = tempdir().unwrap();
+        let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+
+        // nonexistent dir
+        let r = installed_pkgs(dir.join("nonexistent"));
+        assert_err_re!(r, "failed reading dir: ");
+
+        for path in ["cat/pkg-1", "cat/-MERGING-pkg-2", "cat/invalid", "a/b-1.2-r3"] {
+            fs::create_dir_all(dir.join(path)).unwrap();
+        }
+        fs::write(dir.join("file"), "").unwrap();
+        let cpvs = installed_pkgs(dir).unwrap();
+        assert_eq!(cpvs.len(), 2);
+        for cpv in ["cat/pkg-1", "a/b-1.2-r3"] {
+            assert!(cpvs.contains(&Cpv::try_new(cpv).unwrap()));
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
+use strum::{Display, EnumString};
+
+use crate::Error;
+use crate::utils::digest;
+
+/// Hash used to determine filename-based distfile directories.
+#[derive(Display, EnumString, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum LayoutHash {
+    Blake2b,
+    Sha1,
+}
+
+impl LayoutHash {
+    /// Return the number of bits in a hash value.
+    fn bits(&self) -> usize {
+        match self {
+            Self::Blake2b => 512,
+            Self::Sha1 => 160,
+        }
+    }
+
+    /// Generate a hash value from data.
+    fn hash(&self, data: &[u8]) -> String {
+        match self {
+            Self::Blake2b => digest::<blake2::Blake2b512>(data),
+            Self::Sha1 => digest::<sha1::Sha1>(data),
+        }
+    }
+}
+
+/// Distfile directory structure as defined by GLEP 75.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Structure {
+    /// All files stored in the top-level directory.
+    Flat,
+    /// Files stored in directories named using hashed file names.
+    FilenameHash {
+        hash: LayoutHash,
+        cutoffs: Vec<usize>,
+    },
+}
+
+impl Structure {
+    /// Return the relative path for a file name.
+    pub fn path(&self, filename: &str) -> String {
+        match self {
+            Self::Flat => filename.to_string(),
+            Self::FilenameHash { hash, cutoffs } => {
+                let value = hash.hash(filename.as_bytes());
+                let mut path = String::new();
+                let mut start = 0;
+                for bits in cutoffs {
+                    let end = start + bits / 4;
+                    path.push_str(&value[start..end]);
+                    path.push('/');
+                    start = end;
+                }
+                path.push_str(filename);
+                path
+            }
+        }
+    }
+}
+
+impl FromStr for Structure {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            ["flat"] => Ok(Self::Flat),
+            ["filename-hash", hash, cutoffs] => {
+                let hash: LayoutHash = hash
+                    .parse()
+                    .map_err(|_| Error::InvalidValue(format!("unsupported hash: {hash}")))?;
+                let cutoffs: Vec<_> = cutoffs
+                    .split(':')
+                    .map(|x| match x.parse::<usize>() {
+                        Ok(bits) if bits > 0 && bits % 4 == 0 => Ok(bits),
+                        _ => Err(Error::InvalidValue(format!("invalid cutoff: {x}"))),
+                    })
+                    .try_collect()?;
+                if cutoffs.iter().sum::<usize>() > hash.bits() {
+                    return Err(Error::InvalidValue(format!("cutoffs exceed {hash} size")));
+                }
+                Ok(Self::FilenameHash { hash, cutoffs })
+            }
+            _ => Err(Error::InvalidValue(format!("unsupported structure: {s}"))),
+        }
+    }
+}
+
+impl fmt::Display for Structure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Flat => write!(f, "flat"),
+            Self::FilenameHash { hash, cutoffs } => {
+                write!(f, "filename-hash {hash} {}", cutoffs.iter().join(":"))
+            }
+        }
+    }
+}
+
+/// Distfile mirror layout parsed from a mirror's layout.conf file.
+///
+/// Structures are stored in order of preference with unsupported entries being
+/// skipped. Mirrors lacking a layout.conf file use a flat structure.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MirrorLayout(Vec<Structure>);
+
+impl Default for MirrorLayout {
+    fn default() -> Self {
+        Self(vec![Structure::Flat])
+    }
+}
+
+impl MirrorLayout {
+    /// Create a layout from an ordered list of structures.
+    pub fn new<I: IntoIterator<Item = Structure>>(structures: I) -> Self {
+        let structures: Vec<_> = structures.into_iter().collect();
+        if structures.is_empty() {
+            Default::default()
+        } else {
+            Self(structures)
+        }
+    }
+
+    /// Return the layout used by Gentoo distfile mirrors.
+    pub fn gentoo() -> Self {
+        let hash = LayoutHash::Blake2b;
+        Self(vec![Structure::FilenameHash { hash, cutoffs: vec![8] }])
+    }
+
+    /// Parse a layout.conf file's content.
+    pub fn parse(data: &str) -> crate::Result<Self> {
+        let ini = ini::Ini::load_from_str(data)
+            .map_err(|e| Error::InvalidValue(format!("failed parsing INI: {e}")))?;
+
+        let structures = ini
+            .section(Some("structure"))
+            .into_iter()
+            .flat_map(|section| section.iter())
+            .filter_map(|(key, value)| key.parse::<usize>().ok().map(|i| (i, value)))
+            .sorted_by_key(|(i, _)| *i)
+            // skip structures that aren't supported
+            .filter_map(|(_, value)| value.parse().ok());
+
+        Ok(Self::new(structures))
+    }
+
+    /// Load the layout for a local mirror directory.
+    pub fn from_path<P: AsRef<Utf8Path>>(dir: P) -> crate::Result<Self> {
+        let path = dir.as_ref().join("layout.conf");
+        match fs::read_to_string(&path) {
+            Ok(data) => {
+                Self::parse(&data).map_err(|e| Error::InvalidValue(format!("{path}: {e}")))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(Error::IO(format!("failed reading: {path}: {e}"))),
+        }
+    }
+
+    /// Write the layout.conf file for a local mirror directory.
+    pub fn to_path<P: AsRef<Utf8Path>>(&self, dir: P) -> crate::Result<()> {
+        let path = dir.as_ref().join("layout.conf");
+        fs::write(&path, self.to_string())
+            .map_err(|e| Error::IO(format!("failed writing: {path}: {e}")))
+    }
+
+    /// Return the preferred structure.
+    pub fn structure(&self) -> &Structure {
+        &self.0[0]
+    }
+
+    /// Return the preferred relative path for a file name.
+    pub fn path(&self, filename: &str) -> String {
+        self.structure().path(filename)
+    }
+
+    /// Return the preferred path for a file name in a local mirror directory.
+    pub fn distfile_path<P: AsRef<Utf8Path>>(&self, dir: P, filename: &str) -> Utf8PathBuf {
+        dir.as_ref().join(self.path(filename))
+    }
+}
+
+impl fmt::Display for MirrorLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[structure]")?;
+        for (i, structure) in self.0.iter().enumerate() {
+            writeln!(f, "{i}={structure}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    #[test]
+    fn structure() {
+        // invalid
+        for s in [
+            "",
+            "content-hash SHA512 8",
+            "filename-hash MD5 8",
+            "filename-hash BLAKE2B 3",
+            "filename-hash SHA1 160:4",
+        ] {
+            assert!(s.parse::<Structure>().is_err(), "{s} didn't fail");
+        }
+
+        // flat
+        let structure: Structure = "flat".parse().unwrap();
+        assert_eq!(structure.path("a.tar.gz"), "a.tar.gz");
+        assert_eq!(structure.to_string(), "flat");
+
+        // filename hashes
+        let structure: Structure = "filename-hash BLAKE2B 8".parse().unwrap();
+        let hash = LayoutHash::Blake2b.hash(b"a.tar.gz");
+        assert_eq!(structure.path("a.tar.gz"), format!("{}/a.tar.gz", &hash[..2]));
+        assert_eq!(structure.to_string(), "filename-hash BLAKE2B 8");
+
+        let structure: Structure = "filename-hash SHA1 4:8".parse().unwrap();
+        let hash = LayoutHash::Sha1.hash(b"a.tar.gz");
+        let path = format!("{}/{}/a.tar.gz", &hash[..1], &hash[1..3]);
+        assert_eq!(structure.path("a.tar.gz"), path);
+        assert_eq!(structure.to_string(), "filename-hash SHA1 4:8");
+    }
+
+    #[test]
+    fn layout() {
+        // invalid
+        let r = MirrorLayout::parse("data");
+        assert_err_re!(r, "failed parsing INI: ");
+
+        // empty
+        let layout = MirrorLayout::parse("").unwrap();
+        assert_eq!(layout, MirrorLayout::default());
+        assert_eq!(layout.path("a"), "a");
+
+        // gentoo mirrors
+        let layout = MirrorLayout::gentoo();
+        assert_eq!(layout.structure().to_string(), "filename-hash BLAKE2B 8");
+
+        // unsupported structures are skipped
+        let data = indoc::indoc! {"
+            [structure]
+            1=filename-hash BLAKE2B 8
+            0=content-hash SHA512 8:8:8
+            2=flat
+        "};
+        let layout = MirrorLayout::parse(data).unwrap();
+        assert_eq!(
+            layout,
+            MirrorLayout::new(["filename-hash BLAKE2B 8".parse().unwrap(), Structure::Flat])
+        );
+
+        // local mirror directories
+        let dir = tempdir().unwrap();
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        assert_eq!(MirrorLayout::from_path(dir).unwrap(), MirrorLayout::default());
+        layout.to_path(dir).unwrap();
+        assert_eq!(MirrorLayout::from_path(dir).unwrap(), layout);
+        let path = layout.distfile_path(dir, "a.tar.gz");
+        assert_eq!(path, dir.join(layout.path("a.tar.gz")));
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Fetch-restricted distfile requiring manual download.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct ManualDownload {
+    filename: String,
+    uri: String,
+}
+
+impl ManualDownload {
+    pub(crate) fn new<S1, S2>(filename: S1, uri: S2) -> Self
+    where
+        S1: fmt::Display,
+        S2: fmt::Display,
+    {
+        Self {
+            filename: filename.to_string(),
+            uri: uri.to_string(),
+        }
+    }
+
+    /// Return the distfile name.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Return the related URI, using the file name for restricted files lacking a URL.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
+impl fmt::Display for ManualDownload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.uri)
+    }
+}
+
+/// Manual download requirements for a fetch-restricted package.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Nofetch {
+    downloads: Vec<ManualDownload>,
+    output: Option<String>,
+}
+
+impl Nofetch {
+    pub(crate) fn new(downloads: Vec<ManualDownload>, output: Option<String>) -> Self {
+        Self { downloads, output }
+    }
+
+    /// Return the distfiles that must be manually downloaded.
+    pub fn downloads(&self) -> &[ManualDownload] {
+        &self.downloads
+    }
+
+    /// Return the output from the package's pkg_nofetch phase.
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Return true if no manual downloads are required.
+    pub fn is_empty(&self) -> bool {
+        self.downloads.is_empty()
+    }
+}
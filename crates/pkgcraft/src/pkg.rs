@@ -96,6 +96,11 @@ pub(crate) trait PkgPretend: Package {
     fn pkg_pretend(&self) -> scallop::Result<Option<String>>;
 }
 
+pub(crate) trait PkgNofetch: Package {
+    /// Run the pkg_nofetch operation for a package's missing fetch-restricted URIs.
+    fn pkg_nofetch(&self, uris: &[String]) -> scallop::Result<Option<String>>;
+}
+
 pub trait Source: Package {
     /// Source a package.
     fn source(&self) -> scallop::Result<ExecStatus>;
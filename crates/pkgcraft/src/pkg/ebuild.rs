@@ -1,14 +1,14 @@
 use std::sync::{Arc, OnceLock};
 use std::{fmt, fs};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::{IndexMap, IndexSet};
 use tracing::warn;
 
 use crate::dep::{Cpv, Dep, Slot};
 use crate::dep::{DependencySet, Uri};
 use crate::eapi::Eapi;
-use crate::fetch::Fetchable;
+use crate::fetch::{Fetchable, ManualDownload, Nofetch};
 use crate::macros::bool_not_equal;
 use crate::repo::Repository;
 use crate::repo::ebuild::{EbuildRepo, Eclass};
@@ -324,6 +324,42 @@ impl EbuildPkg {
             .map(|x| x.filename())
             .filter(|x| !x.is_empty())
     }
+
+    /// Return the fetch-restricted distfiles missing from a distfile directory.
+    pub fn manual_downloads<P: AsRef<Utf8Path>>(&self, distdir: P) -> Vec<ManualDownload> {
+        let distdir = distdir.as_ref();
+        let mut downloads = IndexMap::new();
+        for uri in self.src_uri().iter_flatten() {
+            let download = match Fetchable::from_uri(uri, self, false) {
+                Err(Error::RestrictedFetchable(f)) => ManualDownload::new(f.filename(), &f),
+                Err(Error::RestrictedFile(uri)) => ManualDownload::new(uri.filename(), &uri),
+                _ => continue,
+            };
+
+            if !distdir.join(download.filename()).exists() {
+                downloads
+                    .entry(download.filename().to_string())
+                    .or_insert(download);
+            }
+        }
+        downloads.into_values().collect()
+    }
+
+    /// Run the pkg_nofetch phase for fetch-restricted distfiles missing from a
+    /// distfile directory.
+    ///
+    /// The phase is skipped when no manual downloads are required.
+    pub fn nofetch<P: AsRef<Utf8Path>>(&self, distdir: P) -> crate::Result<Nofetch> {
+        let downloads = self.manual_downloads(distdir);
+        if downloads.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let repo = &self.0.repo;
+        let uris = downloads.iter().map(|x| x.to_string()).collect();
+        let output = self.0.repo.pool().nofetch(repo, &self.0.cpv, uris)?;
+        Ok(Nofetch::new(downloads, output))
+    }
 }
 
 pub struct IterFetchable<'a> {
@@ -404,6 +440,8 @@ impl Intersects<Dep> for EbuildPkg {
 
 #[cfg(test)]
 mod tests {
+    use tempfile::tempdir;
+
     use crate::config::Config;
     use crate::eapi::EAPI_LATEST_OFFICIAL;
     use crate::repo::PkgRepository;
@@ -972,4 +1010,72 @@ mod tests {
         assert_ordered_eq!(pkg1.distfiles(), ["a.tar.gz"]);
         assert_ordered_eq!(pkg2.distfiles(), ["b.tar.gz"]);
     }
+
+    #[test]
+    fn nofetch() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+        let tmpdir = tempdir().unwrap();
+        let distdir = Utf8Path::from_path(tmpdir.path()).unwrap();
+
+        // unrestricted
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            DESCRIPTION="testing nofetch"
+            SLOT=0
+            SRC_URI="https://a.com/a.tar.gz"
+        "#};
+        temp.create_ebuild_from_str("cat/unrestricted-1", data)
+            .unwrap();
+        let pkg = repo.get_pkg("cat/unrestricted-1").unwrap();
+        assert!(pkg.manual_downloads(distdir).is_empty());
+        assert!(pkg.nofetch(distdir).unwrap().is_empty());
+
+        // selectively unrestricted
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            DESCRIPTION="testing nofetch"
+            SLOT=0
+            SRC_URI="
+                https://a.com/a.tar.gz
+                b.tar.gz
+                https://a.com/c -> c.tar.gz
+                fetch+https://a.com/d.tar.gz
+            "
+            RESTRICT="fetch"
+            pkg_nofetch() {
+                echo "custom nofetch"
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/restricted-1", data)
+            .unwrap();
+        let pkg = repo.get_pkg("cat/restricted-1").unwrap();
+        let downloads: Vec<_> = pkg
+            .manual_downloads(distdir)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            downloads,
+            ["https://a.com/a.tar.gz", "b.tar.gz", "https://a.com/c -> c.tar.gz"]
+        );
+        let nofetch = pkg.nofetch(distdir).unwrap();
+        assert_eq!(nofetch.downloads().len(), 3);
+        assert_eq!(nofetch.output(), Some("custom nofetch"));
+
+        // existing files are skipped
+        fs::write(distdir.join("a.tar.gz"), "").unwrap();
+        fs::write(distdir.join("b.tar.gz"), "").unwrap();
+        let nofetch = pkg.nofetch(distdir).unwrap();
+        let files: Vec<_> = nofetch.downloads().iter().map(|x| x.filename()).collect();
+        assert_eq!(files, ["c.tar.gz"]);
+
+        // pkg_nofetch isn't run when all files exist
+        fs::write(distdir.join("c.tar.gz"), "").unwrap();
+        let nofetch = pkg.nofetch(distdir).unwrap();
+        assert!(nofetch.is_empty());
+        assert!(nofetch.output().is_none());
+    }
 }
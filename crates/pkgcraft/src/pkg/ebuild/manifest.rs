@@ -1,9 +1,11 @@
 use std::borrow::Borrow;
 use std::hash::{Hash, Hasher};
+use std::io::Read;
 use std::str::FromStr;
 use std::{fmt, fs, io};
 
 use camino::{Utf8Path, Utf8PathBuf};
+use digest::Digest;
 use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use ordermap::OrderMap;
@@ -24,6 +26,15 @@ pub enum HashType {
 }
 
 impl HashType {
+    /// Create an incremental hasher.
+    fn hasher(&self) -> IncrementalHash {
+        match self {
+            HashType::Blake2b => IncrementalHash::Blake2b(Default::default()),
+            HashType::Blake3 => IncrementalHash::Blake3(Default::default()),
+            HashType::Sha512 => IncrementalHash::Sha512(Default::default()),
+        }
+    }
+
     /// Generate a hash value from data.
     pub fn hash(&self, data: &[u8]) -> String {
         match self {
@@ -43,7 +54,7 @@ impl HashType {
     }
 
     /// Verify the hash matches the given data.
-    fn verify(&self, data: &[u8], value: &str) -> crate::Result<()> {
+    pub(crate) fn verify(&self, data: &[u8], value: &str) -> crate::Result<()> {
         let hash = self.hash(data);
 
         if value != hash {
@@ -56,14 +67,109 @@ impl HashType {
     }
 }
 
+/// Incremental hasher for a supported hash type.
+#[derive(Clone)]
+enum IncrementalHash {
+    Blake2b(blake2::Blake2b512),
+    Blake3(Box<blake3::Hasher>),
+    Sha512(sha2::Sha512),
+}
+
+impl IncrementalHash {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake2b(h) => Digest::update(h, data),
+            Self::Blake3(h) => Digest::update(h.as_mut(), data),
+            Self::Sha512(h) => Digest::update(h, data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Self::Blake2b(h) => hex::encode(h.finalize()),
+            Self::Blake3(h) => hex::encode(Digest::finalize(*h)),
+            Self::Sha512(h) => hex::encode(h.finalize()),
+        }
+    }
+}
+
+/// Incrementally verify data against a [`ManifestEntry`].
+#[derive(Clone)]
+pub struct ManifestHasher {
+    entry: ManifestEntry,
+    size: u64,
+    hashers: Vec<(HashType, IncrementalHash)>,
+}
+
+impl ManifestHasher {
+    /// Update the hashes using more data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        for (_, hasher) in &mut self.hashers {
+            hasher.update(data);
+        }
+    }
+
+    /// Update the hashes using data read from a file, optionally limited to a given length.
+    pub fn update_path<P: AsRef<Utf8Path>>(
+        &mut self,
+        path: P,
+        limit: Option<u64>,
+    ) -> crate::Result<()> {
+        let path = path.as_ref();
+        let err = |e: io::Error| Error::IO(format!("failed reading: {path}: {e}"));
+        let file = fs::File::open(path).map_err(err)?;
+        let mut file = file.take(limit.unwrap_or(u64::MAX));
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            match file.read(&mut buf).map_err(err)? {
+                0 => break Ok(()),
+                n => self.update(&buf[..n]),
+            }
+        }
+    }
+
+    /// Return the amount of data processed.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Verify the processed data matches the entry's size and hashes.
+    ///
+    /// On failure, the error includes the expected and actual size along with any
+    /// mismatched digests.
+    pub fn verify(self) -> crate::Result<()> {
+        let mut failures = vec![];
+        for (kind, hasher) in self.hashers {
+            let value = hasher.finalize();
+            if let Some(expected) = self.entry.hashes.get(&kind)
+                && *expected != value
+            {
+                failures.push(format!("{kind}: expected: {expected}, got: {value}"));
+            }
+        }
+
+        let (name, expected) = (self.entry.name(), self.entry.size());
+        if self.size != expected || !failures.is_empty() {
+            failures.insert(0, format!("size: expected: {expected}, got: {}", self.size));
+            let failures = failures.join(", ");
+            Err(Error::InvalidValue(format!("{name}: failed verifying: {failures}")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 #[derive(
     Display, EnumString, EnumIter, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone,
 )]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum ManifestType {
     Aux,
+    Data,
     Dist,
     Ebuild,
+    Manifest,
     Misc,
 }
 
@@ -100,7 +206,7 @@ impl ManifestEntry {
         })
     }
 
-    fn from_path<'a, I, P, S>(
+    pub(crate) fn from_path<'a, I, P, S>(
         kind: ManifestType,
         name: S,
         path: P,
@@ -143,6 +249,35 @@ impl ManifestEntry {
         &self.hashes
     }
 
+    /// Create an incremental hasher to verify data against the entry.
+    pub fn hasher(&self) -> ManifestHasher {
+        ManifestHasher {
+            entry: self.clone(),
+            size: 0,
+            hashers: self.hashes.keys().map(|x| (*x, x.hasher())).collect(),
+        }
+    }
+
+    /// Verify a file matches the entry's size and hashes, streaming its data.
+    ///
+    /// Files with mismatched sizes are rejected without being hashed.
+    pub fn verify_path<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<()> {
+        let path = path.as_ref();
+        let size = fs::metadata(path)
+            .map_err(|e| Error::IO(format!("failed reading: {path}: {e}")))?
+            .len();
+        if size != self.size {
+            let (name, expected) = (self.name(), self.size);
+            return Err(Error::InvalidValue(format!(
+                "{name}: failed verifying: size: expected: {expected}, got: {size}"
+            )));
+        }
+
+        let mut hasher = self.hasher();
+        hasher.update_path(path, None)?;
+        hasher.verify()
+    }
+
     pub fn verify(&self, data: &[u8]) -> crate::Result<()> {
         let name = self.name();
         self.hashes.iter().try_for_each(|(hash, value)| {
@@ -362,7 +497,10 @@ impl Manifest {
             let name = entry.name();
             match entry.kind() {
                 ManifestType::Aux => files_path.join(name).exists(),
-                ManifestType::Ebuild | ManifestType::Misc => pkgdir.join(name).exists(),
+                ManifestType::Data
+                | ManifestType::Ebuild
+                | ManifestType::Manifest
+                | ManifestType::Misc => pkgdir.join(name).exists(),
                 ManifestType::Dist => distfiles.contains_key(name),
             }
         });
@@ -462,6 +600,61 @@ mod tests {
         assert!(manifest.verify(distdir, distdir).is_ok());
     }
 
+    #[test]
+    fn incremental_verification() {
+        let data = b"incremental data";
+        let hashes = [HashType::Blake2b, HashType::Blake3, HashType::Sha512];
+        let values = hashes
+            .iter()
+            .map(|h| format!("{h} {}", h.hash(data)))
+            .join(" ");
+        let manifest = Manifest::parse(&format!("DIST a 16 {values}")).unwrap();
+        let entry = manifest.get("a").unwrap();
+
+        // chunked data
+        let mut hasher = entry.hasher();
+        for chunk in data.chunks(5) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.size(), 16);
+        assert!(hasher.verify().is_ok());
+
+        // truncated data
+        let mut hasher = entry.hasher();
+        hasher.update(&data[..10]);
+        let err = hasher.verify().unwrap_err().to_string();
+        assert!(
+            err.starts_with("a: failed verifying: size: expected: 16, got: 10, BLAKE2B: ")
+        );
+        for hash in hashes {
+            let (expected, value) = (hash.hash(data), hash.hash(&data[..10]));
+            assert!(err.contains(&format!("{hash}: expected: {expected}, got: {value}")));
+        }
+
+        // corrupted data with matching size
+        let mut hasher = entry.hasher();
+        hasher.update(b"incremental dat4");
+        let r = hasher.verify();
+        assert_err_re!(r, "size: expected: 16, got: 16, BLAKE2B: expected: ");
+
+        // files
+        let dir = tempdir().unwrap();
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("a");
+        let r = entry.verify_path(&path);
+        assert_err_re!(r, "failed reading: ");
+        fs::write(&path, data).unwrap();
+        assert!(entry.verify_path(&path).is_ok());
+        let mut hasher = entry.hasher();
+        hasher.update_path(&path, Some(10)).unwrap();
+        assert_eq!(hasher.size(), 10);
+        fs::write(&path, &data[..10]).unwrap();
+        let r = entry.verify_path(&path);
+        assert_err_re!(r, "^a: failed verifying: size: expected: 16, got: 10$");
+        fs::write(&path, b"incremental dat4").unwrap();
+        let r = entry.verify_path(&path);
+        assert_err_re!(r, "size: expected: 16, got: 16, BLAKE2B: expected: ");
+    }
+
     #[test]
     fn is_thick() {
         // thin
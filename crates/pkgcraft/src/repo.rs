@@ -469,8 +469,8 @@ pub trait Repository: PkgRepository + fmt::Display {
         None
     }
 
-    /// Try to sync the repo.
-    fn sync(&self) -> crate::Result<()> {
+    /// Try to sync the repo, returning true if its content was altered.
+    fn sync(&self) -> crate::Result<bool> {
         self.config().sync()
     }
 }
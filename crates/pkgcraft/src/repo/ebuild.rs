@@ -29,12 +29,18 @@ use super::{PkgRepository, RepoFormat, Repository, make_repo_traits};
 
 pub mod cache;
 pub(crate) mod configured;
-mod eclass;
+pub mod eclass;
+pub mod manifest;
 pub use eclass::Eclass;
+mod index;
 mod metadata;
 pub use metadata::{Metadata, Mirror};
+pub mod profile;
+pub use profile::Profile;
 pub mod revdeps;
-pub use revdeps::RevDepCache;
+pub use revdeps::{RevDepCache, RevDepIndex};
+pub mod search;
+pub use search::SearchIndex;
 mod temp;
 pub use temp::{EbuildRepoBuilder, EbuildTempRepo};
 
@@ -512,6 +518,35 @@ impl EbuildRepo {
     pub fn revdeps(&self, ignore: bool) -> crate::Result<RevDepCache> {
         RevDepCache::from_repo(self, ignore)
     }
+
+    /// Load the persistent RevDepIndex from a given path, updating it as required.
+    ///
+    /// This walks the entire repo and should only be used to refresh the index, e.g.
+    /// after syncing or regenerating metadata. Queries should use [`RevDepIndex::load`].
+    pub fn revdeps_index<P: AsRef<Utf8Path>>(
+        &self,
+        path: P,
+        ignore: bool,
+    ) -> crate::Result<RevDepIndex> {
+        let mut index = RevDepIndex::load(path)?;
+        if index.update(self, ignore)? > 0 || index.is_stale(self) {
+            index.save()?;
+        }
+        Ok(index)
+    }
+
+    /// Load the persistent SearchIndex from a given path, updating it as required.
+    pub fn search_index<P: AsRef<Utf8Path>>(
+        &self,
+        path: P,
+        ignore: bool,
+    ) -> crate::Result<SearchIndex> {
+        let mut index = SearchIndex::load(path)?;
+        if index.update(self, ignore)? > 0 || index.is_stale(self) {
+            index.save()?;
+        }
+        Ok(index)
+    }
 }
 
 impl fmt::Display for EbuildRepo {
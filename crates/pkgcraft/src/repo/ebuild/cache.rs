@@ -1,13 +1,17 @@
-use camino::Utf8Path;
+use std::{fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::IndexSet;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 use tracing::error;
+use walkdir::WalkDir;
 
 use crate::dep::Cpv;
 use crate::error::Error;
+use crate::files::is_file;
 use crate::pkg::ebuild::EbuildRawPkg;
 use crate::pkg::ebuild::metadata::Metadata;
 use crate::repo::PkgRepository;
@@ -17,13 +21,17 @@ use crate::traits::Contains;
 
 use super::EbuildRepo;
 
+pub(crate) mod binary;
 pub(crate) mod md5_dict;
+pub(crate) mod pms;
 
 pub trait CacheEntry {
     /// Deserialize a cache entry to package metadata.
     fn to_metadata(&self, pkg: &EbuildRawPkg) -> crate::Result<Metadata>;
     /// Verify a cache entry is valid.
     fn verify(&self, pkg: &EbuildRawPkg) -> crate::Result<()>;
+    /// Return the names of all eclasses inherited by the package.
+    fn inherited(&self) -> Vec<&str>;
 }
 
 pub trait Cache {
@@ -44,6 +52,79 @@ pub trait Cache {
     fn remove_entry(&self, cpv: &Cpv) -> crate::Result<()>;
     /// Remove outdated entries from the cache.
     fn clean<C: for<'a> Contains<&'a Cpv> + Sync>(&self, collection: C) -> crate::Result<()>;
+    /// Write pending updates and removals to the cache.
+    ///
+    /// This is only required for formats that don't store entries as separate files.
+    fn sync(&self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// Remove the cache entry file for a Cpv from a directory-based cache.
+fn remove_file_entry(path: &Utf8Path, cpv: &Cpv) -> crate::Result<()> {
+    let path = path.join(cpv.category()).join(cpv.pf());
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            Err(Error::IO(format!("failed removing cache entry: {cpv}: {e}")))
+        }
+        _ => {
+            // remove empty parent directory
+            let _ = fs::remove_dir(path.parent().unwrap());
+            Ok(())
+        }
+    }
+}
+
+/// Remove outdated, invalid, and unrelated files from a directory-based cache.
+fn clean_file_entries<C>(path: &Utf8Path, collection: C) -> crate::Result<()>
+where
+    C: for<'a> Contains<&'a Cpv> + Sync,
+{
+    // TODO: replace with parallelized cache iterator
+    let entries: Vec<_> = WalkDir::new(path)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .collect();
+
+    // remove invalid file and parent directory if empty
+    let remove_file = |path: &Utf8Path| -> crate::Result<()> {
+        fs::remove_file(path)
+            .map_err(|e| Error::IO(format!("failed removing old cache entry: {path}: {e}")))?;
+
+        let dir = path.parent().unwrap();
+        if let Err(e) = fs::remove_dir(dir)
+            && e.kind() != io::ErrorKind::DirectoryNotEmpty
+        {
+            return Err(Error::IO(format!("failed removing cache dir: {dir}: {e}")));
+        }
+
+        Ok(())
+    };
+
+    // Remove outdated, invalid, and unrelated files as well as their parent
+    // directories if empty.
+    entries
+        .into_par_iter()
+        .filter_map(Result::ok)
+        .filter(is_file)
+        .filter_map(|e| Utf8PathBuf::from_path_buf(e.into_path()).ok())
+        .try_for_each(|file| -> crate::Result<()> {
+            // convert to relative path
+            let relpath = file.strip_prefix(path).expect("invalid cache path");
+
+            // determine if a cache file is valid, relating to an existing pkg
+            let valid = Cpv::try_new(relpath)
+                .ok()
+                .map(|cpv| collection.contains(&cpv))
+                .unwrap_or_default();
+
+            if !valid {
+                remove_file(&file)?;
+            }
+
+            Ok(())
+        })
 }
 
 #[derive(
@@ -53,6 +134,9 @@ pub trait Cache {
 pub enum CacheFormat {
     #[default]
     Md5Dict,
+    /// Export-only format lacking inherit data and eclass validation.
+    Pms,
+    Binary,
 }
 
 impl CacheFormat {
@@ -60,6 +144,8 @@ impl CacheFormat {
     pub fn from_repo<P: AsRef<Utf8Path>>(&self, path: P) -> MetadataCache {
         match self {
             Self::Md5Dict => MetadataCache::Md5Dict(md5_dict::Md5Dict::from_repo(path)),
+            Self::Pms => MetadataCache::Pms(pms::Pms::from_repo(path)),
+            Self::Binary => MetadataCache::Binary(binary::Binary::from_repo(path)),
         }
     }
 
@@ -67,6 +153,8 @@ impl CacheFormat {
     pub fn from_path<P: AsRef<Utf8Path>>(&self, path: P) -> MetadataCache {
         match self {
             Self::Md5Dict => MetadataCache::Md5Dict(md5_dict::Md5Dict::from_path(path)),
+            Self::Pms => MetadataCache::Pms(pms::Pms::from_path(path)),
+            Self::Binary => MetadataCache::Binary(binary::Binary::from_path(path)),
         }
     }
 }
@@ -74,18 +162,28 @@ impl CacheFormat {
 #[derive(Debug)]
 pub enum MetadataCacheEntry {
     Md5Dict(md5_dict::Md5DictEntry),
+    Pms(pms::PmsEntry),
 }
 
 impl CacheEntry for MetadataCacheEntry {
     fn to_metadata(&self, pkg: &EbuildRawPkg) -> crate::Result<Metadata> {
         match self {
             Self::Md5Dict(entry) => entry.to_metadata(pkg),
+            Self::Pms(entry) => entry.to_metadata(pkg),
         }
     }
 
     fn verify(&self, pkg: &EbuildRawPkg) -> crate::Result<()> {
         match self {
             Self::Md5Dict(entry) => entry.verify(pkg),
+            Self::Pms(entry) => entry.verify(pkg),
+        }
+    }
+
+    fn inherited(&self) -> Vec<&str> {
+        match self {
+            Self::Md5Dict(entry) => entry.inherited(),
+            Self::Pms(entry) => entry.inherited(),
         }
     }
 }
@@ -93,6 +191,8 @@ impl CacheEntry for MetadataCacheEntry {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MetadataCache {
     Md5Dict(md5_dict::Md5Dict),
+    Pms(pms::Pms),
+    Binary(binary::Binary),
 }
 
 impl Cache for MetadataCache {
@@ -101,18 +201,24 @@ impl Cache for MetadataCache {
     fn chksum<S: AsRef<[u8]>>(&self, data: S) -> String {
         match self {
             Self::Md5Dict(cache) => cache.chksum(data),
+            Self::Pms(cache) => cache.chksum(data),
+            Self::Binary(cache) => cache.chksum(data),
         }
     }
 
     fn format(&self) -> CacheFormat {
         match self {
             Self::Md5Dict(cache) => cache.format(),
+            Self::Pms(cache) => cache.format(),
+            Self::Binary(cache) => cache.format(),
         }
     }
 
     fn path(&self) -> &Utf8Path {
         match self {
             Self::Md5Dict(cache) => cache.path(),
+            Self::Pms(cache) => cache.path(),
+            Self::Binary(cache) => cache.path(),
         }
     }
 
@@ -121,12 +227,20 @@ impl Cache for MetadataCache {
             Self::Md5Dict(cache) => cache
                 .get(pkg)
                 .map(|result| result.map(MetadataCacheEntry::Md5Dict)),
+            Self::Pms(cache) => cache
+                .get(pkg)
+                .map(|result| result.map(MetadataCacheEntry::Pms)),
+            Self::Binary(cache) => cache
+                .get(pkg)
+                .map(|result| result.map(MetadataCacheEntry::Md5Dict)),
         }
     }
 
     fn update(&self, pkg: &EbuildRawPkg, meta: &Metadata) -> crate::Result<()> {
         match self {
             Self::Md5Dict(cache) => cache.update(pkg, meta),
+            Self::Pms(cache) => cache.update(pkg, meta),
+            Self::Binary(cache) => cache.update(pkg, meta),
         }
     }
 
@@ -140,18 +254,32 @@ impl Cache for MetadataCache {
 
         match self {
             Self::Md5Dict(cache) => cache.remove(repo),
+            Self::Pms(cache) => cache.remove(repo),
+            Self::Binary(cache) => cache.remove(repo),
         }
     }
 
     fn remove_entry(&self, cpv: &Cpv) -> crate::Result<()> {
         match self {
             Self::Md5Dict(cache) => cache.remove_entry(cpv),
+            Self::Pms(cache) => cache.remove_entry(cpv),
+            Self::Binary(cache) => cache.remove_entry(cpv),
         }
     }
 
     fn clean<C: for<'a> Contains<&'a Cpv> + Sync>(&self, collection: C) -> crate::Result<()> {
         match self {
             Self::Md5Dict(cache) => cache.clean(collection),
+            Self::Pms(cache) => cache.clean(collection),
+            Self::Binary(cache) => cache.clean(collection),
+        }
+    }
+
+    fn sync(&self) -> crate::Result<()> {
+        match self {
+            Self::Md5Dict(cache) => cache.sync(),
+            Self::Pms(cache) => cache.sync(),
+            Self::Binary(cache) => cache.sync(),
         }
     }
 }
@@ -281,6 +409,10 @@ impl MetadataCacheRegen<'_> {
             .count();
 
         progress.finish_and_clear();
+
+        // write pending updates for single file cache formats
+        self.cache.sync()?;
+
         if errors > 0 {
             Err(Error::InvalidValue("metadata failures occurred".to_string()))
         } else {
@@ -0,0 +1,539 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, RwLock};
+use std::{fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use walkdir::WalkDir;
+
+use crate::Error;
+use crate::dep::Cpv;
+use crate::files::{atomic_write_file, is_file};
+use crate::pkg::ebuild::EbuildRawPkg;
+use crate::pkg::ebuild::metadata::{Key, Metadata};
+use crate::pkg::{Package, RepoPackage};
+use crate::repo::EbuildRepo;
+use crate::traits::Contains;
+use crate::utils::digest;
+
+use super::md5_dict::{Md5DictEntry, Md5DictKey, serialize};
+use super::{Cache, CacheEntry, CacheFormat};
+
+/// Magic bytes identifying a binary cache file.
+const MAGIC: &[u8; 8] = b"PKGCRAFT";
+/// Binary cache file format version.
+const VERSION: u32 = 2;
+/// Header size: magic, version, and record count.
+const HEADER_SIZE: usize = 16;
+/// Record size: key offset and length followed by data offset and length.
+const RECORD_SIZE: usize = 16;
+
+/// Read a little-endian u32 value at a given offset.
+fn read_u32(data: &[u8], offset: usize) -> usize {
+    let bytes = data[offset..offset + 4].try_into().unwrap();
+    u32::from_le_bytes(bytes) as usize
+}
+
+/// Append a LEB128 encoded value.
+fn write_varint(data: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+/// Read a LEB128 encoded value, returning it and its encoded length.
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0;
+    for (i, byte) in data.iter().take(5).enumerate() {
+        value |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Encode package metadata as a sequence of key indices and length-prefixed values.
+fn encode(meta: &Metadata) -> Vec<u8> {
+    let mut data = vec![];
+    for (i, key) in Key::iter().enumerate() {
+        if let Some((_, value)) = serialize(meta, key) {
+            data.push(i as u8);
+            write_varint(&mut data, value.len());
+            data.extend(value.as_bytes());
+        }
+    }
+    data
+}
+
+/// Decode an encoded cache entry.
+fn decode(mut data: &[u8]) -> crate::Result<Md5DictEntry> {
+    let invalid = |e: &str| Error::InvalidValue(format!("invalid cache entry: {e}"));
+    let mut entry = vec![];
+    while let Some((&idx, remaining)) = data.split_first() {
+        let key = Key::iter()
+            .nth(idx.into())
+            .ok_or_else(|| invalid(&format!("unknown key: {idx}")))?;
+        let (len, n) = read_varint(remaining).ok_or_else(|| invalid("invalid length"))?;
+        let value = remaining
+            .get(n..n + len)
+            .ok_or_else(|| invalid("truncated value"))?;
+        let value = str::from_utf8(value).map_err(|e| invalid(&e.to_string()))?;
+        entry.push((Md5DictKey(key), value.to_string()));
+        data = &remaining[n + len..];
+    }
+    Ok(entry.into_iter().collect())
+}
+
+/// Memory-mapped binary cache file index.
+///
+/// The file layout consists of a header, a sequence of fixed-size records sorted by
+/// Cpv string, and a data section holding Cpv strings and encoded entries.
+#[derive(Debug)]
+struct Index {
+    mmap: Mmap,
+    count: usize,
+}
+
+impl Index {
+    /// Load the index for a cache file, returning None if it doesn't exist.
+    fn load(path: &Utf8Path) -> crate::Result<Option<Self>> {
+        let invalid =
+            |e: &str| Error::InvalidValue(format!("invalid binary cache: {path}: {e}"));
+
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::IO(format!("failed opening cache: {path}: {e}"))),
+        };
+
+        // SAFETY: cache files are only replaced atomically via renames
+        let mmap = unsafe { Mmap::map(&file) }
+            .map_err(|e| Error::IO(format!("failed mapping cache: {path}: {e}")))?;
+
+        if mmap.len() < HEADER_SIZE || &mmap[..8] != MAGIC {
+            return Err(invalid("invalid header"));
+        }
+
+        let version = read_u32(&mmap, 8) as u32;
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported version: {version}")));
+        }
+
+        let count = read_u32(&mmap, 12);
+        let index = Self { mmap, count };
+
+        // verify all records point to valid data
+        let records_end = count
+            .checked_mul(RECORD_SIZE)
+            .and_then(|x| x.checked_add(HEADER_SIZE))
+            .ok_or_else(|| invalid("invalid record count"))?;
+        if records_end > index.mmap.len() {
+            return Err(invalid("truncated records"));
+        }
+        for i in 0..count {
+            let (key, data) = index.ranges(i);
+            if key.end > index.mmap.len() || data.end > index.mmap.len() {
+                return Err(invalid("truncated data"));
+            }
+            if str::from_utf8(&index.mmap[key]).is_err() {
+                return Err(invalid("invalid key"));
+            }
+        }
+
+        Ok(Some(index))
+    }
+
+    /// Return the key and data byte ranges for a record.
+    fn ranges(&self, i: usize) -> (Range<usize>, Range<usize>) {
+        let offset = HEADER_SIZE + i * RECORD_SIZE;
+        let key_off = read_u32(&self.mmap, offset);
+        let key_len = read_u32(&self.mmap, offset + 4);
+        let data_off = read_u32(&self.mmap, offset + 8);
+        let data_len = read_u32(&self.mmap, offset + 12);
+        (key_off..key_off + key_len, data_off..data_off + data_len)
+    }
+
+    /// Return the key for a record.
+    fn key(&self, i: usize) -> &str {
+        let (key, _) = self.ranges(i);
+        // keys are validated on load
+        str::from_utf8(&self.mmap[key]).unwrap()
+    }
+
+    /// Return the entry data for a given key if it exists.
+    fn get(&self, key: &str) -> Option<&[u8]> {
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.key(mid).cmp(key) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => {
+                    let (_, data) = self.ranges(mid);
+                    return Some(&self.mmap[data]);
+                }
+            }
+        }
+        None
+    }
+
+    /// Iterate over all key and entry data pairs.
+    fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        (0..self.count).map(|i| {
+            let (_, data) = self.ranges(i);
+            (self.key(i), &self.mmap[data])
+        })
+    }
+}
+
+/// Serialize cache entries into the binary cache file format.
+fn pack(entries: &BTreeMap<String, Vec<u8>>) -> crate::Result<Vec<u8>> {
+    let overflow = || Error::InvalidValue("binary cache size overflow".to_string());
+    let to_u32 = |x: usize| -> crate::Result<[u8; 4]> {
+        u32::try_from(x)
+            .map(u32::to_le_bytes)
+            .map_err(|_| overflow())
+    };
+
+    let mut header = Vec::with_capacity(HEADER_SIZE + entries.len() * RECORD_SIZE);
+    header.extend(MAGIC);
+    header.extend(VERSION.to_le_bytes());
+    header.extend(to_u32(entries.len())?);
+
+    let mut blob = vec![];
+    let data_start = HEADER_SIZE + entries.len() * RECORD_SIZE;
+    for (key, data) in entries {
+        header.extend(to_u32(data_start + blob.len())?);
+        header.extend(to_u32(key.len())?);
+        blob.extend(key.as_bytes());
+        header.extend(to_u32(data_start + blob.len())?);
+        header.extend(to_u32(data.len())?);
+        blob.extend(data);
+    }
+
+    header.extend(blob);
+    Ok(header)
+}
+
+/// The binary metadata cache.
+///
+/// All entries are stored in a single, memory-mapped file indexed by Cpv. Updates are
+/// written to a staging directory, allowing parallel regeneration, while removals are
+/// batched in memory with both merged into the cache file on sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Binary {
+    path: Utf8PathBuf,
+    #[serde(skip)]
+    index: Arc<RwLock<Option<Arc<Index>>>>,
+    #[serde(skip)]
+    removed: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Binary {
+    /// Load a metadata cache from the default repo location.
+    pub(super) fn from_repo<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self::from_path(path.as_ref().join("metadata/pkgcraft-cache"))
+    }
+
+    /// Load a metadata cache from a custom location.
+    pub(super) fn from_path<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            index: Default::default(),
+            removed: Default::default(),
+        }
+    }
+
+    /// Return the staging directory for pending updates.
+    fn staging(&self) -> Utf8PathBuf {
+        Utf8PathBuf::from(format!("{}.staging", self.path))
+    }
+
+    /// Return the index for the cache file, loading it if necessary.
+    fn index(&self) -> crate::Result<Option<Arc<Index>>> {
+        if let Some(index) = self.index.read().unwrap().as_ref() {
+            return Ok(Some(index.clone()));
+        }
+
+        let mut guard = self.index.write().unwrap();
+        if guard.is_none() {
+            *guard = Index::load(&self.path)?.map(Arc::new);
+        }
+        Ok(guard.clone())
+    }
+
+    /// Return all cache entries including pending updates and removals.
+    fn entries(&self) -> crate::Result<BTreeMap<String, Vec<u8>>> {
+        let mut entries = BTreeMap::new();
+        if let Some(index) = self.index()? {
+            let removed = self.removed.lock().unwrap();
+            for (key, data) in index.iter().filter(|(key, _)| !removed.contains(*key)) {
+                entries.insert(key.to_string(), data.to_vec());
+            }
+        }
+
+        let staging = self.staging();
+        for entry in WalkDir::new(&staging)
+            .min_depth(2)
+            .max_depth(2)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(is_file)
+        {
+            let path = entry.path();
+            let data = fs::read(path).map_err(|e| {
+                Error::IO(format!("failed reading cache entry: {}: {e}", path.display()))
+            })?;
+            let relpath = path.strip_prefix(&staging).unwrap();
+            if let Some(key) = relpath.to_str() {
+                entries.insert(key.to_string(), data);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Atomically replace the cache file with the given entries.
+    fn write(&self, entries: &BTreeMap<String, Vec<u8>>) -> crate::Result<()> {
+        atomic_write_file(&self.path, pack(entries)?)?;
+
+        // drop pending updates and force reloading the index
+        self.clear()
+    }
+
+    /// Drop all pending updates and removals, forcing the index to be reloaded.
+    fn clear(&self) -> crate::Result<()> {
+        let staging = self.staging();
+        if let Err(e) = fs::remove_dir_all(&staging)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            return Err(Error::IO(format!("failed removing cache staging: {staging}: {e}")));
+        }
+        self.removed.lock().unwrap().clear();
+        *self.index.write().unwrap() = None;
+        Ok(())
+    }
+}
+
+impl Cache for Binary {
+    type Entry = Md5DictEntry;
+
+    fn chksum<S: AsRef<[u8]>>(&self, data: S) -> String {
+        digest::<md5::Md5>(data.as_ref())
+    }
+
+    fn format(&self) -> CacheFormat {
+        CacheFormat::Binary
+    }
+
+    fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    fn get(&self, pkg: &EbuildRawPkg) -> Option<crate::Result<Self::Entry>> {
+        let parse = |data: &[u8]| -> crate::Result<Self::Entry> {
+            let entry = decode(data)?;
+            entry.verify(pkg).and(Ok(entry))
+        };
+
+        let key = pkg.cpv().to_string();
+        let result = match self.index() {
+            Ok(Some(index)) => index.get(&key).map(parse),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        };
+
+        // pending updates are only checked for missing or outdated entries
+        if let Some(Ok(_)) = result {
+            return result;
+        }
+        let path = self.staging().join(&key);
+        match fs::read(&path) {
+            Ok(data) => Some(parse(&data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => result,
+            Err(e) => Some(Err(Error::IO(format!("failed loading metadata: {path}: {e}")))),
+        }
+    }
+
+    fn update(&self, pkg: &EbuildRawPkg, meta: &Metadata) -> crate::Result<()> {
+        // atomically create staging file
+        let path = self
+            .staging()
+            .join(pkg.cpv().category())
+            .join(pkg.cpv().pf());
+        atomic_write_file(path, encode(meta))
+    }
+
+    fn remove(&self, _repo: &EbuildRepo) -> crate::Result<()> {
+        let path = &self.path;
+        fs::remove_file(path)
+            .map_err(|e| Error::IO(format!("failed removing metadata cache: {path}: {e}")))?;
+        self.clear()
+    }
+
+    fn remove_entry(&self, cpv: &Cpv) -> crate::Result<()> {
+        // drop any pending update and batch the removal until the cache is synced
+        let path = self.staging().join(cpv.category()).join(cpv.pf());
+        if let Err(e) = fs::remove_file(&path)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            return Err(Error::IO(format!("failed removing cache entry: {cpv}: {e}")));
+        }
+        self.removed.lock().unwrap().insert(cpv.to_string());
+        Ok(())
+    }
+
+    fn clean<C: for<'a> Contains<&'a Cpv> + Sync>(&self, collection: C) -> crate::Result<()> {
+        let mut entries = self.entries()?;
+        let len = entries.len();
+        entries.retain(|key, _| {
+            Cpv::try_new(key)
+                .ok()
+                .map(|cpv| collection.contains(&cpv))
+                .unwrap_or_default()
+        });
+
+        // avoid rewriting the cache file if nothing changed
+        if entries.len() != len
+            || self.staging().exists()
+            || !self.removed.lock().unwrap().is_empty()
+        {
+            self.write(&entries)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn sync(&self) -> crate::Result<()> {
+        if self.staging().exists() || !self.removed.lock().unwrap().is_empty() {
+            self.write(&self.entries()?)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexSet;
+    use tempfile::tempdir;
+
+    use crate::test::*;
+
+    use super::*;
+
+    #[test]
+    fn update_and_get() {
+        let data = test_data();
+        let repo = data.ebuild_repo("metadata").unwrap();
+        let dir = tempdir().unwrap();
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("cache");
+        let cache = Binary::from_path(&path);
+        let pkgs: Vec<_> = repo.iter_raw().collect::<Result<_, _>>().unwrap();
+
+        // pending updates are visible before syncing
+        for pkg in &pkgs {
+            let meta = pkg.metadata(false).unwrap();
+            cache.update(pkg, &meta).unwrap();
+            let entry = cache.get(pkg).unwrap().unwrap();
+            assert_eq!(entry.to_metadata(pkg).unwrap(), meta);
+        }
+        assert!(!path.exists());
+
+        // entries are loaded from the cache file after syncing
+        cache.sync().unwrap();
+        assert!(path.exists());
+        assert!(!cache.staging().exists());
+        let cache = Binary::from_path(&path);
+        for pkg in &pkgs {
+            let meta = pkg.metadata(false).unwrap();
+            let entry = cache.get(pkg).unwrap().unwrap();
+            assert_eq!(entry.to_metadata(pkg).unwrap(), meta);
+        }
+
+        // removals are batched until syncing
+        let pkg = &pkgs[0];
+        cache.remove_entry(pkg.cpv()).unwrap();
+        assert!(cache.get(pkg).is_some());
+        assert_eq!(cache.entries().unwrap().len(), pkgs.len() - 1);
+        cache.sync().unwrap();
+        assert!(cache.get(pkg).is_none());
+        assert_eq!(cache.entries().unwrap().len(), pkgs.len() - 1);
+
+        // clean removes all entries unrelated to the given collection
+        let cpvs: IndexSet<_> = pkgs[1..2].iter().map(|p| p.cpv().clone()).collect();
+        cache.clean(&cpvs).unwrap();
+        assert_eq!(cache.entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn invalid() {
+        let dir = tempdir().unwrap();
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("cache");
+
+        // nonexistent
+        assert!(Index::load(&path).unwrap().is_none());
+
+        // invalid header
+        fs::write(&path, "data").unwrap();
+        let r = Index::load(&path);
+        assert_err_re!(r, "invalid binary cache: .+: invalid header$");
+
+        // unsupported version
+        let mut data = pack(&Default::default()).unwrap();
+        data[8] = 3;
+        fs::write(&path, &data).unwrap();
+        let r = Index::load(&path);
+        assert_err_re!(r, "invalid binary cache: .+: unsupported version: 3$");
+
+        // truncated
+        let entries = [("cat/pkg-1".to_string(), b"EAPI=8\n".to_vec())].into();
+        let data = pack(&entries).unwrap();
+        fs::write(&path, &data[..data.len() - 1]).unwrap();
+        let r = Index::load(&path);
+        assert_err_re!(r, "invalid binary cache: .+: truncated data$");
+
+        // valid
+        fs::write(&path, &data).unwrap();
+        let index = Index::load(&path).unwrap().unwrap();
+        assert_eq!(index.get("cat/pkg-1").unwrap(), b"EAPI=8\n");
+        assert!(index.get("cat/pkg-2").is_none());
+    }
+
+    #[test]
+    fn encoding() {
+        // varints
+        for value in [0, 1, 127, 128, 300, 16384, u32::MAX as usize] {
+            let mut data = vec![];
+            write_varint(&mut data, value);
+            assert_eq!(read_varint(&data), Some((value, data.len())));
+        }
+        assert!(read_varint(&[0x80]).is_none());
+
+        // entries
+        let data = test_data();
+        let repo = data.ebuild_repo("metadata").unwrap();
+        for pkg in repo.iter_raw() {
+            let pkg = pkg.unwrap();
+            let meta = pkg.metadata(false).unwrap();
+            let entry = decode(&encode(&meta)).unwrap();
+            assert_eq!(entry, Md5DictEntry::from(&meta));
+            assert!(encode(&meta).len() < entry.to_bytes().len());
+        }
+
+        // invalid
+        let r = decode(&[255]);
+        assert_err_re!(r, "invalid cache entry: unknown key: 255$");
+        let r = decode(&[0, 5, b'a']);
+        assert_err_re!(r, "invalid cache entry: truncated value$");
+        let r = decode(&[0]);
+        assert_err_re!(r, "invalid cache entry: invalid length$");
+    }
+}
@@ -5,14 +5,12 @@ use std::{fmt, fs, io};
 use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::IndexMap;
 use itertools::Itertools;
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
-use walkdir::WalkDir;
 
 use crate::Error;
 use crate::dep::Cpv;
-use crate::files::{atomic_write_file, is_file};
+use crate::files::atomic_write_file;
 use crate::pkg::ebuild::EbuildRawPkg;
 use crate::pkg::ebuild::metadata::{Key, Metadata};
 use crate::pkg::{Package, RepoPackage};
@@ -20,11 +18,11 @@ use crate::repo::EbuildRepo;
 use crate::traits::Contains;
 use crate::utils::digest;
 
-use super::{Cache, CacheEntry, CacheFormat};
+use super::{Cache, CacheEntry, CacheFormat, clean_file_entries, remove_file_entry};
 
 /// Wrapper that converts metadata keys to md5-dict compatible keys.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Md5DictKey(Key);
+pub(super) struct Md5DictKey(pub(super) Key);
 
 impl Borrow<Key> for Md5DictKey {
     fn borrow(&self) -> &Key {
@@ -113,11 +111,18 @@ impl CacheEntry for Md5DictEntry {
 
         Ok(())
     }
+
+    fn inherited(&self) -> Vec<&str> {
+        self.0
+            .get(&Key::INHERITED)
+            .map(|val| val.split_whitespace().step_by(2).collect())
+            .unwrap_or_default()
+    }
 }
 
 impl Md5DictEntry {
     /// Serialize a cache entry to raw bytes for writing to a file.
-    fn to_bytes(&self) -> Vec<u8> {
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
         self.0
             .iter()
             .flat_map(|(k, v)| format!("{k}={v}\n").into_bytes())
@@ -148,7 +153,7 @@ impl FromStr for Md5DictEntry {
 }
 
 /// Serialize a metadata field to its md5-dict cache mapping, returning None for empty fields.
-fn serialize(meta: &Metadata, key: Key) -> Option<(Md5DictKey, String)> {
+pub(super) fn serialize(meta: &Metadata, key: Key) -> Option<(Md5DictKey, String)> {
     let value = match key {
         Key::CHKSUM => meta.chksum.to_string(),
         Key::DESCRIPTION => meta.description.to_string(),
@@ -263,66 +268,11 @@ impl Cache for Md5Dict {
     }
 
     fn remove_entry(&self, cpv: &Cpv) -> crate::Result<()> {
-        let path = self.path.join(cpv.category()).join(cpv.pf());
-        match fs::remove_file(&path) {
-            Err(e) if e.kind() != io::ErrorKind::NotFound => {
-                Err(Error::IO(format!("failed removing cache entry: {cpv}: {e}")))
-            }
-            _ => {
-                // remove empty parent directory
-                let _ = fs::remove_dir(path.parent().unwrap());
-                Ok(())
-            }
-        }
+        remove_file_entry(&self.path, cpv)
     }
 
     fn clean<C: for<'a> Contains<&'a Cpv> + Sync>(&self, collection: C) -> crate::Result<()> {
-        // TODO: replace with parallelized cache iterator
-        let entries: Vec<_> = WalkDir::new(self.path())
-            .min_depth(2)
-            .max_depth(2)
-            .into_iter()
-            .collect();
-
-        // remove invalid file and parent directory if empty
-        let remove_file = |path: &Utf8Path| -> crate::Result<()> {
-            fs::remove_file(path).map_err(|e| {
-                Error::IO(format!("failed removing old cache entry: {path}: {e}"))
-            })?;
-
-            let dir = path.parent().unwrap();
-            if let Err(e) = fs::remove_dir(dir)
-                && e.kind() != io::ErrorKind::DirectoryNotEmpty
-            {
-                return Err(Error::IO(format!("failed removing cache dir: {dir}: {e}")));
-            }
-
-            Ok(())
-        };
-
-        // Remove outdated, invalid, and unrelated files as well as their parent
-        // directories if empty.
-        entries
-            .into_par_iter()
-            .filter_map(Result::ok)
-            .filter(is_file)
-            .filter_map(|e| Utf8PathBuf::from_path_buf(e.into_path()).ok())
-            .try_for_each(|path| -> crate::Result<()> {
-                // convert to relative path
-                let relpath = path.strip_prefix(self.path()).expect("invalid cache path");
-
-                // determine if a cache file is valid, relating to an existing pkg
-                let valid = Cpv::try_new(relpath)
-                    .ok()
-                    .map(|cpv| collection.contains(&cpv))
-                    .unwrap_or_default();
-
-                if !valid {
-                    remove_file(&path)?;
-                }
-
-                Ok(())
-            })
+        clean_file_entries(&self.path, collection)
     }
 }
 
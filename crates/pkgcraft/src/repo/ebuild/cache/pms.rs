@@ -0,0 +1,324 @@
+use std::str::FromStr;
+use std::{fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use filetime::{FileTime, set_file_mtime};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+use crate::dep::Cpv;
+use crate::files::atomic_write_file;
+use crate::pkg::ebuild::EbuildRawPkg;
+use crate::pkg::ebuild::metadata::{Key, Metadata};
+use crate::pkg::{Package, RepoPackage};
+use crate::repo::EbuildRepo;
+use crate::traits::Contains;
+use crate::utils::digest;
+
+use super::md5_dict::serialize;
+use super::{Cache, CacheEntry, CacheFormat, clean_file_entries, remove_file_entry};
+
+/// Metadata keys in line order for the PMS flat list cache format.
+const KEYS: [Key; 18] = [
+    Key::DEPEND,
+    Key::RDEPEND,
+    Key::SLOT,
+    Key::SRC_URI,
+    Key::RESTRICT,
+    Key::HOMEPAGE,
+    Key::LICENSE,
+    Key::DESCRIPTION,
+    Key::KEYWORDS,
+    Key::INHERITED,
+    Key::IUSE,
+    Key::REQUIRED_USE,
+    Key::PDEPEND,
+    Key::BDEPEND,
+    Key::EAPI,
+    Key::PROPERTIES,
+    Key::DEFINED_PHASES,
+    Key::IDEPEND,
+];
+
+/// Total number of lines in a PMS cache entry, with unused lines left blank.
+const LINES: usize = 22;
+
+/// The format for PMS flat list metadata cache entries.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct PmsEntry {
+    values: IndexMap<Key, String>,
+    mtime: Option<FileTime>,
+}
+
+impl CacheEntry for PmsEntry {
+    fn to_metadata(&self, pkg: &EbuildRawPkg) -> crate::Result<Metadata> {
+        let mut meta = Metadata::default();
+        let eapi = pkg.eapi();
+        let repo = &pkg.repo();
+        let invalid = |e| Error::InvalidValue(format!("{pkg}: invalid metadata: {e}"));
+
+        for key in eapi.metadata_keys() {
+            match (key, self.values.get(key)) {
+                // the flat list format only stores inherited eclass names
+                (Key::INHERITED, Some(val)) => {
+                    meta.inherited =
+                        val.split_whitespace()
+                            .map(|name| {
+                                repo.eclasses().get(name).cloned().ok_or_else(|| {
+                                    invalid(format!("nonexistent eclass: {name}"))
+                                })
+                            })
+                            .try_collect()?;
+                }
+                // checksums aren't stored so use the one from the ebuild
+                (Key::CHKSUM, _) => meta.chksum = pkg.chksum().to_string(),
+                (key, Some(val)) => meta
+                    .deserialize(eapi, repo, key, val)
+                    .map_err(|e| invalid(e.to_string()))?,
+                (key, None) if eapi.mandatory_keys().contains(key) => {
+                    return Err(invalid(format!("missing required value: {key}")));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(meta)
+    }
+
+    fn verify(&self, pkg: &EbuildRawPkg) -> crate::Result<()> {
+        let invalid = |e| Error::InvalidValue(format!("{pkg}: invalid metadata: {e}"));
+
+        // verify the cache entry was generated from the current ebuild
+        let path = pkg.path();
+        let meta = fs::metadata(&path)
+            .map_err(|e| Error::IO(format!("failed reading ebuild: {path}: {e}")))?;
+        if self.mtime != Some(FileTime::from_last_modification_time(&meta)) {
+            return Err(invalid("mismatched ebuild mtime".to_string()));
+        }
+
+        // verify inherited eclasses exist
+        if let Some(val) = self.values.get(&Key::INHERITED) {
+            let repo = pkg.repo();
+            for name in val.split_whitespace() {
+                if !repo.eclasses().contains(name) {
+                    return Err(invalid(format!("nonexistent eclass: {name}")));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn inherited(&self) -> Vec<&str> {
+        self.values
+            .get(&Key::INHERITED)
+            .map(|val| val.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl PmsEntry {
+    /// Serialize a cache entry to raw bytes for writing to a file.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = String::new();
+        for key in KEYS {
+            if let Some(val) = self.values.get(&key) {
+                data.push_str(val);
+            }
+            data.push('\n');
+        }
+        for _ in KEYS.len()..LINES {
+            data.push('\n');
+        }
+        data.into_bytes()
+    }
+}
+
+impl FromStr for PmsEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let lines: Vec<_> = s.lines().collect();
+        if lines.len() > LINES {
+            return Err(Error::InvalidValue(format!(
+                "invalid pms cache entry: too many lines: {}",
+                lines.len()
+            )));
+        }
+
+        let values = KEYS
+            .into_iter()
+            .zip(lines)
+            .filter(|(_, val)| !val.is_empty())
+            .map(|(key, val)| (key, val.to_string()))
+            .collect();
+
+        Ok(Self { values, mtime: None })
+    }
+}
+
+impl From<&Metadata> for PmsEntry {
+    fn from(meta: &Metadata) -> Self {
+        let values = KEYS
+            .into_iter()
+            .filter(|key| meta.eapi.metadata_keys().contains(key))
+            .filter_map(|key| match key {
+                Key::INHERITED if !meta.inherited.is_empty() => {
+                    Some((key, meta.inherited.iter().map(|e| e.name()).join(" ")))
+                }
+                Key::INHERITED => None,
+                _ => serialize(meta, key).map(|(_, val)| (key, val)),
+            })
+            .collect();
+
+        Self { values, mtime: None }
+    }
+}
+
+/// The PMS flat list metadata cache.
+///
+/// Entries don't store directly inherited eclasses and are only validated against the
+/// ebuild's mtime so eclass changes aren't detected. Therefore, the format is only
+/// supported for exporting metadata and is never used as a repo's metadata cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pms {
+    path: Utf8PathBuf,
+}
+
+impl Pms {
+    /// Load a metadata cache from the default repo location.
+    pub(super) fn from_repo<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().join("metadata/cache"),
+        }
+    }
+
+    /// Load a metadata cache from a custom location.
+    pub(super) fn from_path<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl Cache for Pms {
+    type Entry = PmsEntry;
+
+    fn chksum<S: AsRef<[u8]>>(&self, data: S) -> String {
+        digest::<md5::Md5>(data.as_ref())
+    }
+
+    fn format(&self) -> CacheFormat {
+        CacheFormat::Pms
+    }
+
+    fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    fn get(&self, pkg: &EbuildRawPkg) -> Option<crate::Result<Self::Entry>> {
+        let path = self.path.join(pkg.cpv().to_string());
+        let load = || -> io::Result<(String, FileTime)> {
+            let data = fs::read_to_string(&path)?;
+            let mtime = FileTime::from_last_modification_time(&fs::metadata(&path)?);
+            Ok((data, mtime))
+        };
+
+        match load() {
+            Ok((data, mtime)) => Some(
+                data.parse::<Self::Entry>()
+                    .map(|entry| Self::Entry { mtime: Some(mtime), ..entry })
+                    .and_then(|entry| entry.verify(pkg).and(Ok(entry))),
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => Some(Err(Error::IO(format!("failed loading metadata: {path}: {e}")))),
+        }
+    }
+
+    fn update(&self, pkg: &EbuildRawPkg, meta: &Metadata) -> crate::Result<()> {
+        // convert metadata to the cache entry format
+        let entry = Self::Entry::from(meta);
+        // atomically create cache file
+        let path = self.path.join(pkg.cpv().category()).join(pkg.cpv().pf());
+        atomic_write_file(&path, entry.to_bytes())?;
+
+        // entries are validated by matching the ebuild's mtime
+        let ebuild = pkg.path();
+        let mtime = fs::metadata(&ebuild)
+            .map(|m| FileTime::from_last_modification_time(&m))
+            .map_err(|e| Error::IO(format!("failed reading ebuild: {ebuild}: {e}")))?;
+        set_file_mtime(&path, mtime)
+            .map_err(|e| Error::IO(format!("failed setting cache entry mtime: {path}: {e}")))
+    }
+
+    fn remove(&self, _repo: &EbuildRepo) -> crate::Result<()> {
+        let path = &self.path;
+        fs::remove_dir_all(path)
+            .map_err(|e| Error::IO(format!("failed removing metadata cache: {path}: {e}")))
+    }
+
+    fn remove_entry(&self, cpv: &Cpv) -> crate::Result<()> {
+        remove_file_entry(&self.path, cpv)
+    }
+
+    fn clean<C: for<'a> Contains<&'a Cpv> + Sync>(&self, collection: C) -> crate::Result<()> {
+        clean_file_entries(&self.path, collection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::config::Config;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::*;
+
+    use super::*;
+
+    #[test]
+    fn update_and_get() {
+        let data = test_data();
+        let repo = data.ebuild_repo("metadata").unwrap();
+        let dir = tempdir().unwrap();
+        let cache = Pms::from_path(Utf8Path::from_path(dir.path()).unwrap());
+        for pkg in repo.iter_raw() {
+            let pkg = pkg.unwrap();
+            let mut meta = pkg.metadata(false).unwrap();
+            cache.update(&pkg, &meta).unwrap();
+            let entry = cache.get(&pkg).unwrap().unwrap();
+            // directly inherited eclasses aren't stored in the format
+            meta.inherit = Default::default();
+            assert_eq!(entry.to_metadata(&pkg).unwrap(), meta);
+        }
+    }
+
+    #[test]
+    fn outdated() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+
+        temp.create_ebuild("cat/pkg-1", &[]).unwrap();
+        let pkg = repo.iter_raw().next().unwrap().unwrap();
+        let meta = pkg.metadata(false).unwrap();
+        let cache = CacheFormat::Pms.from_repo(&repo);
+        cache.update(&pkg, &meta).unwrap();
+        assert!(cache.get(&pkg).unwrap().is_ok());
+
+        // modifying the ebuild's mtime invalidates the entry
+        set_file_mtime(pkg.path(), FileTime::from_unix_time(0, 0)).unwrap();
+        let r = cache.get(&pkg).unwrap();
+        assert_err_re!(r, "^cat/pkg-1::test: invalid metadata: mismatched ebuild mtime$");
+    }
+
+    #[test]
+    fn invalid_cache_entry() {
+        let data = "\n".repeat(LINES + 1);
+        let r = PmsEntry::from_str(&data);
+        assert_err_re!(r, "^invalid pms cache entry: too many lines: 23$");
+    }
+}
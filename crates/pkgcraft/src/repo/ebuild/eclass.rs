@@ -5,14 +5,20 @@ use std::sync::Arc;
 use std::{fmt, fs};
 
 use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexSet;
 use scallop::{ExecStatus, source};
 
 use crate::Error;
+use crate::bash;
 use crate::dep::parse;
 use crate::traits::SourceBash;
 
 use super::cache::{Cache, MetadataCache};
 
+pub mod doc;
+use doc::EclassDoc;
+pub mod graph;
+
 /// An eclass in an ebuild repository.
 #[derive(Debug)]
 struct InternalEclass {
@@ -51,6 +57,52 @@ impl Eclass {
         &self.0.path
     }
 
+    /// Return the eclasses directly inherited by the eclass.
+    ///
+    /// This statically parses global scope inherit calls, including conditional calls.
+    pub fn inherit(&self) -> crate::Result<IndexSet<String>> {
+        let path = self.path();
+        let data = fs::read_to_string(path)
+            .map_err(|e| Error::IO(format!("failed reading eclass: {path}: {e}")))?;
+        let tree = bash::Tree::new(data.into());
+        let mut cursor = tree.walk();
+        let mut eclasses = IndexSet::new();
+
+        for node in tree
+            .iter_global()
+            .filter(|x| x.kind() == "command_name" && x.as_str() == "inherit")
+        {
+            if let Some(cmd) = node.parent() {
+                for arg in cmd.children(&mut cursor).filter(|x| x.kind() == "word") {
+                    if let Ok(name) = parse::eclass_name(arg.as_str()) {
+                        eclasses.insert(name.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(eclasses)
+    }
+
+    /// Parse the eclass documentation.
+    pub fn doc(&self) -> crate::Result<EclassDoc> {
+        let path = self.path();
+        let data = fs::read_to_string(path)
+            .map_err(|e| Error::IO(format!("failed reading eclass: {path}: {e}")))?;
+        let doc: EclassDoc = data
+            .parse()
+            .map_err(|e| Error::InvalidValue(format!("{self}: invalid doc: {e}")))?;
+
+        if doc.name() != self.name() {
+            return Err(Error::InvalidValue(format!(
+                "{self}: invalid doc: mismatched @ECLASS name: {}",
+                doc.name()
+            )));
+        }
+
+        Ok(doc)
+    }
+
     /// Return the MD5 checksum of the eclass.
     pub(crate) fn chksum(&self) -> &str {
         &self.0.chksum
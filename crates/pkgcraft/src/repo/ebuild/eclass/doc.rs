@@ -0,0 +1,695 @@
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+use strum::{AsRefStr, Display, EnumString};
+
+use crate::Error;
+
+/// Eclass documentation tags.
+#[derive(AsRefStr, Display, EnumString, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+enum Tag {
+    Eclass,
+    Maintainer,
+    Author,
+    SupportedEapis,
+    Provides,
+    Blurb,
+    Deprecated,
+    Dead,
+    Description,
+    Example,
+    Vcsurl,
+    Function,
+    Usage,
+    Return,
+    EclassVariable,
+    Variable,
+    PreInherit,
+    UserVariable,
+    OutputVariable,
+    Required,
+    DefaultUnset,
+    Internal,
+}
+
+impl Tag {
+    /// Return true if the tag starts a new documentation block.
+    fn is_block(&self) -> bool {
+        matches!(self, Self::Eclass | Self::Function | Self::EclassVariable | Self::Variable)
+    }
+
+    /// Return true if the tag's value spans the following lines.
+    fn is_multiline(&self) -> bool {
+        matches!(self, Self::Maintainer | Self::Author | Self::Description | Self::Example)
+    }
+
+    /// Return true if the tag is a flag without a value.
+    fn is_flag(&self) -> bool {
+        matches!(
+            self,
+            Self::Dead
+                | Self::PreInherit
+                | Self::UserVariable
+                | Self::OutputVariable
+                | Self::Required
+                | Self::DefaultUnset
+                | Self::Internal
+        )
+    }
+
+    /// Return true if the tag is valid within a given block type.
+    fn is_valid(&self, block: Tag) -> bool {
+        match block {
+            Self::Eclass => matches!(
+                self,
+                Self::Maintainer
+                    | Self::Author
+                    | Self::SupportedEapis
+                    | Self::Provides
+                    | Self::Blurb
+                    | Self::Deprecated
+                    | Self::Dead
+                    | Self::Description
+                    | Self::Example
+                    | Self::Vcsurl
+            ),
+            Self::Function => matches!(
+                self,
+                Self::Maintainer
+                    | Self::Usage
+                    | Self::Return
+                    | Self::Internal
+                    | Self::Deprecated
+                    | Self::Description
+                    | Self::Example
+            ),
+            Self::EclassVariable | Self::Variable => matches!(
+                self,
+                Self::PreInherit
+                    | Self::UserVariable
+                    | Self::OutputVariable
+                    | Self::Required
+                    | Self::DefaultUnset
+                    | Self::Internal
+                    | Self::Deprecated
+                    | Self::Description
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Documentation block tag value.
+#[derive(Debug)]
+struct TagValue {
+    tag: Tag,
+    lines: Vec<String>,
+}
+
+impl TagValue {
+    /// Return the value for single line tags.
+    fn line(&self) -> String {
+        self.lines.first().cloned().unwrap_or_default()
+    }
+
+    /// Return the value for multiline tags with surrounding blank lines removed.
+    fn text(&self) -> String {
+        self.lines.join("\n").trim_matches('\n').to_string()
+    }
+
+    /// Return the non-empty lines of a multiline tag value.
+    fn items(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect()
+    }
+}
+
+/// Raw documentation block consisting of an initial block tag and its related tags.
+#[derive(Debug)]
+struct Block {
+    tag: Tag,
+    lineno: usize,
+    name: String,
+    tags: Vec<TagValue>,
+}
+
+impl Block {
+    /// Return the first instance of a tag in the block.
+    fn get(&self, tag: Tag) -> Option<&TagValue> {
+        self.tags.iter().find(|x| x.tag == tag)
+    }
+
+    /// Return true if the block contains a tag.
+    fn contains(&self, tag: Tag) -> bool {
+        self.get(tag).is_some()
+    }
+}
+
+/// Create an error for an invalid documentation line.
+fn invalid(lineno: usize, msg: impl std::fmt::Display) -> Error {
+    Error::InvalidValue(format!("line {lineno}: {msg}"))
+}
+
+/// Split raw eclass data into documentation blocks.
+fn blocks(data: &str) -> crate::Result<Vec<Block>> {
+    let mut blocks: Vec<Block> = vec![];
+    let mut in_block = false;
+
+    for (i, line) in data.lines().enumerate() {
+        let lineno = i + 1;
+
+        // documentation blocks only consist of consecutive comment lines
+        let Some(comment) = line.strip_prefix('#') else {
+            in_block = false;
+            continue;
+        };
+        let comment = comment.strip_prefix(' ').unwrap_or(comment).trim_end();
+
+        // determine if the line is a tag
+        let tag = comment.strip_prefix('@').and_then(|s| {
+            let (name, value) = s.split_once(':').unwrap_or((s, ""));
+            name.parse::<Tag>().ok().map(|tag| (tag, value.trim()))
+        });
+
+        match tag {
+            Some((tag, value)) if tag.is_block() => {
+                if value.is_empty() {
+                    return Err(invalid(lineno, format!("@{tag}: missing name")));
+                }
+                blocks.push(Block {
+                    tag,
+                    lineno,
+                    name: value.to_string(),
+                    tags: vec![],
+                });
+                in_block = true;
+            }
+            Some((tag, value)) if in_block => {
+                let block = blocks.last_mut().expect("missing block");
+                if !tag.is_valid(block.tag) {
+                    return Err(invalid(
+                        lineno,
+                        format!("@{tag}: invalid in @{} block", block.tag),
+                    ));
+                } else if block.contains(tag) {
+                    return Err(invalid(lineno, format!("@{tag}: duplicate tag")));
+                } else if tag.is_flag() && !value.is_empty() {
+                    return Err(invalid(lineno, format!("@{tag}: unexpected value: {value}")));
+                } else if !tag.is_flag() && !tag.is_multiline() && value.is_empty() {
+                    return Err(invalid(lineno, format!("@{tag}: missing value")));
+                }
+
+                let lines = if value.is_empty() {
+                    vec![]
+                } else {
+                    vec![value.to_string()]
+                };
+                block.tags.push(TagValue { tag, lines });
+            }
+            // ignore regular comments and unknown tags outside documentation blocks
+            _ if !in_block => (),
+            _ => {
+                let block = blocks.last_mut().expect("missing block");
+                match block.tags.last_mut() {
+                    Some(value) if value.tag.is_multiline() => {
+                        value.lines.push(comment.to_string());
+                    }
+                    // a regular comment following a block terminates the block
+                    _ if comment.is_empty() => in_block = false,
+                    _ => return Err(invalid(lineno, format!("unexpected text: {comment}"))),
+                }
+            }
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Documentation for an eclass function.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct FunctionDoc {
+    name: String,
+    lineno: usize,
+    usage: Option<String>,
+    returns: Option<String>,
+    maintainers: Vec<String>,
+    internal: bool,
+    deprecated: Option<String>,
+    description: Option<String>,
+    example: Option<String>,
+}
+
+impl FunctionDoc {
+    /// Return the function name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the line number of the documentation block.
+    pub fn lineno(&self) -> usize {
+        self.lineno
+    }
+
+    /// Return the function's usage arguments.
+    pub fn usage(&self) -> Option<&str> {
+        self.usage.as_deref()
+    }
+
+    /// Return the function's return value description.
+    pub fn returns(&self) -> Option<&str> {
+        self.returns.as_deref()
+    }
+
+    /// Return the function-specific maintainers.
+    pub fn maintainers(&self) -> &[String] {
+        &self.maintainers
+    }
+
+    /// Return true if the function is internal to the eclass.
+    pub fn internal(&self) -> bool {
+        self.internal
+    }
+
+    /// Return the deprecation replacement if the function is deprecated.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// Return the function's description.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the function's usage example.
+    pub fn example(&self) -> Option<&str> {
+        self.example.as_deref()
+    }
+}
+
+impl From<&Block> for FunctionDoc {
+    fn from(block: &Block) -> Self {
+        Self {
+            name: block.name.clone(),
+            lineno: block.lineno,
+            usage: block.get(Tag::Usage).map(|x| x.line()),
+            returns: block.get(Tag::Return).map(|x| x.line()),
+            maintainers: block
+                .get(Tag::Maintainer)
+                .map(|x| x.items())
+                .unwrap_or_default(),
+            internal: block.contains(Tag::Internal),
+            deprecated: block.get(Tag::Deprecated).map(|x| x.line()),
+            description: block.get(Tag::Description).map(|x| x.text()),
+            example: block.get(Tag::Example).map(|x| x.text()),
+        }
+    }
+}
+
+/// Documentation for an eclass variable.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct VariableDoc {
+    name: String,
+    lineno: usize,
+    eclass: bool,
+    pre_inherit: bool,
+    user: bool,
+    output: bool,
+    required: bool,
+    default_unset: bool,
+    internal: bool,
+    deprecated: Option<String>,
+    description: Option<String>,
+}
+
+impl VariableDoc {
+    /// Return the variable name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the line number of the documentation block.
+    pub fn lineno(&self) -> usize {
+        self.lineno
+    }
+
+    /// Return true for eclass variables as opposed to function-specific variables.
+    pub fn eclass(&self) -> bool {
+        self.eclass
+    }
+
+    /// Return true if the variable must be set before inheriting the eclass.
+    pub fn pre_inherit(&self) -> bool {
+        self.pre_inherit
+    }
+
+    /// Return true if the variable is meant to be set by users.
+    pub fn user(&self) -> bool {
+        self.user
+    }
+
+    /// Return true if the variable is set by the eclass for ebuild usage.
+    pub fn output(&self) -> bool {
+        self.output
+    }
+
+    /// Return true if the variable is required to be set.
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    /// Return true if the variable is unset by default.
+    pub fn default_unset(&self) -> bool {
+        self.default_unset
+    }
+
+    /// Return true if the variable is internal to the eclass.
+    pub fn internal(&self) -> bool {
+        self.internal
+    }
+
+    /// Return the deprecation replacement if the variable is deprecated.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// Return the variable's description.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+impl From<&Block> for VariableDoc {
+    fn from(block: &Block) -> Self {
+        Self {
+            name: block.name.clone(),
+            lineno: block.lineno,
+            eclass: block.tag == Tag::EclassVariable,
+            pre_inherit: block.contains(Tag::PreInherit),
+            user: block.contains(Tag::UserVariable),
+            output: block.contains(Tag::OutputVariable),
+            required: block.contains(Tag::Required),
+            default_unset: block.contains(Tag::DefaultUnset),
+            internal: block.contains(Tag::Internal),
+            deprecated: block.get(Tag::Deprecated).map(|x| x.line()),
+            description: block.get(Tag::Description).map(|x| x.text()),
+        }
+    }
+}
+
+/// Eclass documentation.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct EclassDoc {
+    name: String,
+    maintainers: Vec<String>,
+    authors: Vec<String>,
+    supported_eapis: Vec<String>,
+    provides: Vec<String>,
+    blurb: String,
+    deprecated: Option<String>,
+    dead: bool,
+    description: Option<String>,
+    example: Option<String>,
+    vcsurl: Option<String>,
+    functions: IndexMap<String, FunctionDoc>,
+    variables: IndexMap<String, VariableDoc>,
+}
+
+impl EclassDoc {
+    /// Return the eclass name without its file extension.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the eclass maintainers.
+    pub fn maintainers(&self) -> &[String] {
+        &self.maintainers
+    }
+
+    /// Return the eclass authors.
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    /// Return the EAPIs the eclass supports.
+    pub fn supported_eapis(&self) -> &[String] {
+        &self.supported_eapis
+    }
+
+    /// Return the eclasses indirectly provided via inherit.
+    pub fn provides(&self) -> &[String] {
+        &self.provides
+    }
+
+    /// Return the short eclass summary.
+    pub fn blurb(&self) -> &str {
+        &self.blurb
+    }
+
+    /// Return the deprecation replacement if the eclass is deprecated.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// Return true if the eclass is marked dead and pending removal.
+    pub fn dead(&self) -> bool {
+        self.dead
+    }
+
+    /// Return the eclass description.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the eclass usage example.
+    pub fn example(&self) -> Option<&str> {
+        self.example.as_deref()
+    }
+
+    /// Return the eclass VCS URL.
+    pub fn vcsurl(&self) -> Option<&str> {
+        self.vcsurl.as_deref()
+    }
+
+    /// Return the documented functions.
+    pub fn functions(&self) -> &IndexMap<String, FunctionDoc> {
+        &self.functions
+    }
+
+    /// Return the documented variables.
+    pub fn variables(&self) -> &IndexMap<String, VariableDoc> {
+        &self.variables
+    }
+}
+
+impl FromStr for EclassDoc {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let mut blocks = blocks(s)?.into_iter();
+
+        // the eclass block must come first
+        let block = match blocks.next() {
+            Some(block) if block.tag == Tag::Eclass => block,
+            Some(block) => return Err(invalid(block.lineno, "missing initial @ECLASS block")),
+            None => return Err(Error::InvalidValue("missing @ECLASS block".to_string())),
+        };
+
+        let name = block.name.strip_suffix(".eclass").ok_or_else(|| {
+            invalid(block.lineno, format!("invalid eclass name: {}", block.name))
+        })?;
+        let maintainers = block
+            .get(Tag::Maintainer)
+            .map(|x| x.items())
+            .unwrap_or_default();
+        if maintainers.is_empty() {
+            return Err(invalid(block.lineno, "missing @MAINTAINER"));
+        }
+        let blurb = block
+            .get(Tag::Blurb)
+            .map(|x| x.line())
+            .ok_or_else(|| invalid(block.lineno, "missing @BLURB"))?;
+
+        let words = |tag| -> Vec<String> {
+            block
+                .get(tag)
+                .map(|x| x.line().split_whitespace().map(|s| s.to_string()).collect())
+                .unwrap_or_default()
+        };
+
+        let mut doc = Self {
+            name: name.to_string(),
+            maintainers,
+            authors: block
+                .get(Tag::Author)
+                .map(|x| x.items())
+                .unwrap_or_default(),
+            supported_eapis: words(Tag::SupportedEapis),
+            provides: words(Tag::Provides),
+            blurb,
+            deprecated: block.get(Tag::Deprecated).map(|x| x.line()),
+            dead: block.contains(Tag::Dead),
+            description: block.get(Tag::Description).map(|x| x.text()),
+            example: block.get(Tag::Example).map(|x| x.text()),
+            vcsurl: block.get(Tag::Vcsurl).map(|x| x.line()),
+            ..Default::default()
+        };
+
+        for block in blocks {
+            let name = block.name.clone();
+            let existing = match block.tag {
+                Tag::Eclass => return Err(invalid(block.lineno, "duplicate @ECLASS block")),
+                Tag::Function => doc.functions.insert(name, (&block).into()).is_some(),
+                _ => doc.variables.insert(name, (&block).into()).is_some(),
+            };
+
+            if existing {
+                return Err(invalid(
+                    block.lineno,
+                    format!("duplicate @{}: {}", block.tag, block.name),
+                ));
+            }
+        }
+
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let data = indoc::indoc! {r#"
+            # Copyright 2024 Gentoo Authors
+            # Distributed under the terms of the GNU General Public License v2
+
+            # @ECLASS: foo.eclass
+            # @MAINTAINER:
+            # A Developer <dev@gentoo.org>
+            # Another Developer <other@gentoo.org>
+            # @AUTHOR:
+            # Original Author <author@gentoo.org>
+            # @SUPPORTED_EAPIS: 7 8
+            # @PROVIDES: bar
+            # @BLURB: example eclass
+            # @DEPRECATED: baz
+            # @DESCRIPTION:
+            # First paragraph.
+            #
+            # Second paragraph.
+            # @EXAMPLE:
+            # inherit foo
+
+            # @ECLASS_VARIABLE: FOO_VAR
+            # @PRE_INHERIT
+            # @DEFAULT_UNSET
+            # @DESCRIPTION:
+            # Variable description.
+            : "${FOO_VAR:=}"
+
+            # @VARIABLE: FOO_ARGS
+            # @INTERNAL
+            # @DESCRIPTION:
+            # Function-specific variable.
+
+            # @FUNCTION: foo_src_compile
+            # @USAGE: [args]
+            # @RETURN: nonzero on failure
+            # @DEPRECATED: none
+            # @DESCRIPTION:
+            # Compile things.
+            foo_src_compile() {
+                # regular comment
+                emake "$@"
+            }
+        "#};
+
+        let doc: EclassDoc = data.parse().unwrap();
+        assert_eq!(doc.name(), "foo");
+        assert_eq!(
+            doc.maintainers(),
+            ["A Developer <dev@gentoo.org>", "Another Developer <other@gentoo.org>"]
+        );
+        assert_eq!(doc.authors(), ["Original Author <author@gentoo.org>"]);
+        assert_eq!(doc.supported_eapis(), ["7", "8"]);
+        assert_eq!(doc.provides(), ["bar"]);
+        assert_eq!(doc.blurb(), "example eclass");
+        assert_eq!(doc.deprecated(), Some("baz"));
+        assert!(!doc.dead());
+        assert_eq!(doc.description(), Some("First paragraph.\n\nSecond paragraph."));
+        assert_eq!(doc.example(), Some("inherit foo"));
+        assert!(doc.vcsurl().is_none());
+
+        let var = doc.variables().get("FOO_VAR").unwrap();
+        assert_eq!(var.lineno(), 21);
+        assert!(var.eclass());
+        assert!(var.pre_inherit());
+        assert!(var.default_unset());
+        assert!(!var.internal());
+        assert_eq!(var.description(), Some("Variable description."));
+
+        let var = doc.variables().get("FOO_ARGS").unwrap();
+        assert!(!var.eclass());
+        assert!(var.internal());
+
+        let func = doc.functions().get("foo_src_compile").unwrap();
+        assert_eq!(func.lineno(), 33);
+        assert_eq!(func.usage(), Some("[args]"));
+        assert_eq!(func.returns(), Some("nonzero on failure"));
+        assert_eq!(func.deprecated(), Some("none"));
+        assert!(!func.internal());
+        assert_eq!(func.description(), Some("Compile things."));
+    }
+
+    #[test]
+    fn invalid() {
+        for (data, err) in [
+            ("", "^missing @ECLASS block$"),
+            ("# @FUNCTION: foo", "^line 1: missing initial @ECLASS block$"),
+            ("# @ECLASS:", "^line 1: @ECLASS: missing name$"),
+            (
+                "# @ECLASS: foo\n# @MAINTAINER:\n# dev\n# @BLURB: b",
+                "^line 1: invalid eclass name: foo$",
+            ),
+            ("# @ECLASS: foo.eclass\n# @BLURB: b", "^line 1: missing @MAINTAINER$"),
+            ("# @ECLASS: foo.eclass\n# @MAINTAINER:\n# dev", "^line 1: missing @BLURB$"),
+            (
+                "# @ECLASS: foo.eclass\n# @USAGE: args",
+                "^line 2: @USAGE: invalid in @ECLASS block$",
+            ),
+            ("# @ECLASS: foo.eclass\n# @DEAD\n# @DEAD", "^line 3: @DEAD: duplicate tag$"),
+            ("# @ECLASS: foo.eclass\n# @DEAD: yes", "^line 2: @DEAD: unexpected value: yes$"),
+            ("# @ECLASS: foo.eclass\n# @BLURB:", "^line 2: @BLURB: missing value$"),
+            ("# @ECLASS: foo.eclass\n# @DEAD\n# text", "^line 3: unexpected text: text$"),
+        ] {
+            let r = EclassDoc::from_str(data);
+            assert_err_re!(r, err, format!("{data:?}"));
+        }
+
+        // duplicate blocks
+        let data = indoc::indoc! {"
+            # @ECLASS: foo.eclass
+            # @MAINTAINER:
+            # dev
+            # @BLURB: blurb
+
+            # @FUNCTION: foo
+            # @DESCRIPTION:
+            # desc
+
+            # @FUNCTION: foo
+            # @DESCRIPTION:
+            # desc
+        "};
+        let r = EclassDoc::from_str(data);
+        assert_err_re!(r, "^line 10: duplicate @FUNCTION: foo$");
+    }
+}
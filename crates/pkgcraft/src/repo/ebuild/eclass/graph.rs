@@ -0,0 +1,244 @@
+use std::fmt::Write;
+
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::Error;
+use crate::dep::Cpv;
+use crate::pkg::Package;
+use crate::repo::ebuild::EbuildRepo;
+use crate::traits::LogErrors;
+
+/// Eclass node in the inheritance graph.
+#[derive(Debug, Default, Serialize, PartialEq, Eq, Clone)]
+pub struct EclassNode {
+    inherit: IndexSet<String>,
+    pkgs: IndexSet<Cpv>,
+}
+
+impl EclassNode {
+    /// Return the eclasses directly inherited by the eclass.
+    pub fn inherit(&self) -> &IndexSet<String> {
+        &self.inherit
+    }
+
+    /// Return the packages directly inheriting the eclass.
+    pub fn pkgs(&self) -> &IndexSet<Cpv> {
+        &self.pkgs
+    }
+}
+
+/// Eclass inheritance graph for an ebuild repo.
+#[derive(Debug, Default, Serialize, PartialEq, Eq, Clone)]
+pub struct EclassGraph {
+    eclasses: IndexMap<String, EclassNode>,
+    #[serde(skip)]
+    inherited: IndexMap<Cpv, IndexSet<String>>,
+}
+
+impl EclassGraph {
+    /// Build the eclass graph for a repo.
+    pub fn from_repo(repo: &EbuildRepo, ignore: bool) -> crate::Result<Self> {
+        let mut graph = Self::default();
+
+        // statically determine the eclasses each eclass inherits
+        let eclasses: Vec<_> = repo
+            .eclasses()
+            .par_iter()
+            .map(|e| e.inherit().map(|inherit| (e.name().to_string(), inherit)))
+            .collect::<crate::Result<_>>()?;
+        for (name, inherit) in eclasses {
+            graph
+                .eclasses
+                .insert(name, EclassNode { inherit, ..Default::default() });
+        }
+
+        let mut iter = repo.iter_unordered().log_errors(ignore);
+        for pkg in &mut iter {
+            let cpv = pkg.cpv();
+            for eclass in pkg.inherit().iter() {
+                graph
+                    .eclasses
+                    .entry(eclass.name().to_string())
+                    .or_default()
+                    .pkgs
+                    .insert(cpv.clone());
+            }
+            let inherited = pkg.inherited().iter().map(|e| e.to_string()).collect();
+            graph.inherited.insert(cpv.clone(), inherited);
+        }
+
+        if iter.failed() {
+            return Err(Error::InvalidValue("metadata failures occurred".to_string()));
+        }
+
+        graph.eclasses.par_sort_keys();
+        graph.eclasses.par_values_mut().for_each(|x| x.pkgs.sort());
+        graph.inherited.par_sort_keys();
+        Ok(graph)
+    }
+
+    /// Return the eclass nodes of the graph.
+    pub fn eclasses(&self) -> &IndexMap<String, EclassNode> {
+        &self.eclasses
+    }
+
+    /// Return the node for an eclass if it exists.
+    pub fn get(&self, eclass: &str) -> Option<&EclassNode> {
+        self.eclasses.get(eclass)
+    }
+
+    /// Return the eclasses directly inheriting an eclass.
+    pub fn inheritors(&self, eclass: &str) -> IndexSet<&str> {
+        self.eclasses
+            .iter()
+            .filter(|(_, node)| node.inherit.contains(eclass))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Return the eclasses directly or transitively inheriting an eclass.
+    pub fn affected_eclasses(&self, eclass: &str) -> IndexSet<&str> {
+        let mut affected = IndexSet::new();
+        let mut pending = vec![eclass];
+        while let Some(name) = pending.pop() {
+            for x in self.inheritors(name) {
+                if x != eclass && affected.insert(x) {
+                    pending.push(x);
+                }
+            }
+        }
+        affected.sort_unstable();
+        affected
+    }
+
+    /// Return the packages directly or transitively inheriting an eclass.
+    ///
+    /// This combines the inherited eclasses from package metadata with the static
+    /// inheritance graph, catching conditional inherits that only apply to a subset of
+    /// packages as well as eclass changes that haven't been regenerated yet.
+    pub fn affected_pkgs(&self, eclass: &str) -> IndexSet<&Cpv> {
+        let mut eclasses = self.affected_eclasses(eclass);
+        eclasses.insert(eclass);
+
+        let mut pkgs: IndexSet<_> = self
+            .inherited
+            .iter()
+            .filter(|(_, inherited)| inherited.contains(eclass))
+            .map(|(cpv, _)| cpv)
+            .chain(
+                eclasses
+                    .iter()
+                    .filter_map(|x| self.eclasses.get(*x))
+                    .flat_map(|x| &x.pkgs),
+            )
+            .collect();
+        pkgs.sort_unstable();
+        pkgs
+    }
+
+    /// Render the eclass inheritance graph in DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut s = String::new();
+        writeln!(s, "digraph eclasses {{").unwrap();
+        for (name, node) in &self.eclasses {
+            let count = node.pkgs.len();
+            let label = if count == 1 { "pkg" } else { "pkgs" };
+            writeln!(s, "  \"{name}\" [label=\"{name}\\n{count} {label}\"];").unwrap();
+        }
+        for (name, node) in &self.eclasses {
+            for x in node.inherit.iter().sorted() {
+                writeln!(s, "  \"{name}\" -> \"{x}\";").unwrap();
+            }
+        }
+        writeln!(s, "}}").unwrap();
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::assert_ordered_eq;
+
+    use super::*;
+
+    #[test]
+    fn graph() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+
+        // create eclasses
+        temp.create_eclass("a", "inherit b").unwrap();
+        let eclass = indoc::indoc! {r#"
+            case ${EAPI} in
+                8) inherit c ;;
+            esac
+
+            b_func() {
+                inherit nonexistent
+            }
+        "#};
+        temp.create_eclass("b", eclass).unwrap();
+        temp.create_eclass("c", "# stub eclass").unwrap();
+        temp.create_eclass("d", "# stub eclass").unwrap();
+
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+
+        let ebuild = |eclasses: &str| {
+            indoc::formatdoc! {r#"
+                EAPI=8
+                inherit {eclasses}
+                DESCRIPTION="testing eclass graph"
+                SLOT=0
+            "#}
+        };
+        temp.create_ebuild_from_str("cat/a-1", &ebuild("a"))
+            .unwrap();
+        temp.create_ebuild_from_str("cat/b-1", &ebuild("b d"))
+            .unwrap();
+        temp.create_ebuild_from_str("cat/c-1", &ebuild("c"))
+            .unwrap();
+        temp.create_ebuild_from_str("cat/d-1", &ebuild("d"))
+            .unwrap();
+
+        let graph = EclassGraph::from_repo(&repo, false).unwrap();
+        assert_ordered_eq!(graph.eclasses().keys(), ["a", "b", "c", "d"]);
+
+        // eclass inherits are determined statically from global scope
+        let node = graph.get("b").unwrap();
+        assert_ordered_eq!(node.inherit(), ["c"]);
+        assert_ordered_eq!(node.pkgs().iter().map(|x| x.to_string()), ["cat/b-1"]);
+
+        // eclass inheritors
+        assert_ordered_eq!(graph.inheritors("c"), ["b"]);
+        assert!(graph.inheritors("a").is_empty());
+        assert_ordered_eq!(graph.affected_eclasses("c"), ["a", "b"]);
+        assert!(graph.affected_eclasses("nonexistent").is_empty());
+
+        // affected packages
+        let pkgs = |eclass| {
+            graph
+                .affected_pkgs(eclass)
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pkgs("a"), ["cat/a-1"]);
+        assert_eq!(pkgs("b"), ["cat/a-1", "cat/b-1"]);
+        assert_eq!(pkgs("c"), ["cat/a-1", "cat/b-1", "cat/c-1"]);
+        assert_eq!(pkgs("d"), ["cat/b-1", "cat/d-1"]);
+        assert!(pkgs("nonexistent").is_empty());
+
+        // DOT output
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph eclasses {\n"));
+        assert!(dot.contains("  \"c\" [label=\"c\\n1 pkg\"];\n"));
+        assert!(dot.contains("  \"a\" -> \"b\";\n"));
+        assert!(dot.contains("  \"b\" -> \"c\";\n"));
+    }
+}
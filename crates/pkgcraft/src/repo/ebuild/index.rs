@@ -0,0 +1,190 @@
+use std::hash::Hash;
+use std::{fmt, fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use tracing::error;
+
+use crate::Error;
+use crate::dep::Cpv;
+use crate::files::atomic_write_file;
+use crate::macros::build_path;
+use crate::pkg::ebuild::{EbuildPkg, EbuildRawPkg};
+use crate::repo::Repository;
+use crate::utils::digest;
+
+use super::EbuildRepo;
+use super::cache::{Cache, CacheEntry};
+
+/// Persistent index file shared by incrementally updated repo indexes.
+pub(super) struct IndexFile {
+    /// Index name used for its cache directory and error messages.
+    name: &'static str,
+    /// Header line identifying the file format.
+    header: &'static str,
+}
+
+impl IndexFile {
+    pub(super) const fn new(name: &'static str, header: &'static str) -> Self {
+        Self { name, header }
+    }
+
+    /// Return the default index path for a repo within a cache directory.
+    pub(super) fn default_path(&self, dir: &Utf8Path, repo: &EbuildRepo) -> Utf8PathBuf {
+        // repo IDs can be absolute paths
+        let id = repo.id().trim_start_matches('/');
+        build_path!(dir, self.name, id, "index")
+    }
+
+    /// Parse the lines of an index file, skipping nonexistent files.
+    pub(super) fn load<F>(&self, path: &Utf8Path, mut parse: F) -> crate::Result<()>
+    where
+        F: FnMut(&str) -> crate::Result<()>,
+    {
+        let name = self.name;
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(Error::IO(format!("failed reading {name} index: {path}: {e}")));
+            }
+        };
+
+        let invalid = |i: usize, e: &dyn fmt::Display| {
+            Error::InvalidValue(format!("invalid {name} index: {path}, line {i}: {e}"))
+        };
+
+        let mut lines = data.lines().enumerate().map(|(i, s)| (i + 1, s));
+        match lines.next() {
+            Some((_, s)) if s == self.header => (),
+            Some((i, s)) => return Err(invalid(i, &format!("unsupported format: {s}"))),
+            None => return Ok(()),
+        }
+
+        for (i, line) in lines {
+            parse(line).map_err(|e| invalid(i, &e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Atomically write index lines to a file.
+    pub(super) fn save<I>(&self, path: &Utf8Path, lines: I) -> crate::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: fmt::Display,
+    {
+        let mut data = format!("{}\n", self.header);
+        for line in lines {
+            data.push_str(&format!("{line}\n"));
+        }
+        atomic_write_file(path, data)
+    }
+}
+
+/// Return true if an index file is missing or older than the repo's metadata cache.
+///
+/// Only file modification times are compared so this is cheap enough to run for
+/// queries, but package changes without regenerated metadata aren't detected.
+pub(super) fn is_stale(path: &Utf8Path, repo: &EbuildRepo) -> bool {
+    let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) else {
+        return true;
+    };
+
+    // metadata cache entries are atomically written, altering their directory mtimes
+    let cache = repo.metadata().cache().path();
+    let entries = fs::read_dir(cache)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok);
+    [cache.to_path_buf().into_std_path_buf()]
+        .into_iter()
+        .chain(entries.map(|e| e.path()))
+        .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .any(|time| time > modified)
+}
+
+/// Return the index checksum for an ebuild and its inherited eclasses.
+pub(super) fn chksum<'a, I>(ebuild: &str, eclasses: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let data = [ebuild].into_iter().chain(eclasses).join(" ");
+    digest::<md5::Md5>(data.as_bytes())
+}
+
+/// Return the index checksum for a package if its metadata cache entry is valid.
+pub(super) fn cached_chksum(repo: &EbuildRepo, raw: &EbuildRawPkg) -> Option<String> {
+    match repo.metadata().cache().get(raw) {
+        Some(Ok(entry)) => Some(chksum(
+            raw.chksum(),
+            entry
+                .inherited()
+                .into_iter()
+                .filter_map(|name| repo.eclasses().get(name))
+                .map(|e| e.chksum()),
+        )),
+        _ => None,
+    }
+}
+
+/// Return the index checksum for a package, loading it if its metadata cache entry is invalid.
+pub(super) fn pkg_chksum(repo: &EbuildRepo, cpv: &Cpv) -> crate::Result<String> {
+    let raw = repo.get_pkg_raw(cpv.clone())?;
+    if let Some(value) = cached_chksum(repo, &raw) {
+        return Ok(value);
+    }
+
+    let ebuild = raw.chksum().to_string();
+    let pkg = EbuildPkg::try_from(raw)?;
+    Ok(chksum(&ebuild, pkg.inherited().iter().map(|e| e.chksum())))
+}
+
+/// Merge updated entries into an index, returning the number of altered entries.
+///
+/// Entries missing from the given keys are dropped, as are invalid entries.
+pub(super) fn merge<K, V>(
+    entries: &mut IndexMap<K, V>,
+    keys: &IndexSet<K>,
+    results: Vec<(&K, crate::Result<V>)>,
+    ignore: bool,
+) -> crate::Result<usize>
+where
+    K: Hash + Eq + Ord + Clone,
+{
+    // drop entries for removed packages
+    let len = entries.len();
+    entries.retain(|key, _| keys.contains(key));
+    let mut count = len - entries.len();
+
+    // invalid packages are dropped from the index
+    let mut failed = false;
+    for (key, result) in results {
+        match result {
+            Ok(entry) => {
+                entries.insert(key.clone(), entry);
+                count += 1;
+            }
+            Err(e) => {
+                if entries.shift_remove(key).is_some() {
+                    count += 1;
+                }
+                if !ignore {
+                    error!("{e}");
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    if count > 0 {
+        entries.sort_unstable_keys();
+    }
+
+    if failed {
+        Err(Error::InvalidValue("metadata failures occurred".to_string()))
+    } else {
+        Ok(count)
+    }
+}
@@ -0,0 +1,530 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::process::Command;
+use std::time::SystemTime;
+
+use bzip2::read::MultiBzDecoder;
+use camino::{Utf8Path, Utf8PathBuf};
+use flate2::read::MultiGzDecoder;
+use indexmap::IndexMap;
+use liblzma::read::XzDecoder;
+use rayon::prelude::*;
+use walkdir::WalkDir;
+
+use crate::Error;
+use crate::files::{atomic_write_file, is_hidden, sorted_dir_list_utf8};
+use crate::pkg::ebuild::manifest::{HashType, ManifestEntry, ManifestType};
+
+/// Paths ignored by default in generated top-level Manifests.
+const IGNORED: &[&str] = &["distfiles", "local", "lost+found", "packages"];
+
+/// Directory depth limit for generating sub-Manifests.
+const SUBMANIFEST_DEPTH: usize = 2;
+
+/// Return true if a file name relates to a Manifest file.
+fn is_manifest(name: &str) -> bool {
+    name == "Manifest" || name.starts_with("Manifest.")
+}
+
+/// Read a potentially compressed Manifest file.
+fn read_manifest(path: &Utf8Path) -> crate::Result<String> {
+    let file =
+        File::open(path).map_err(|e| Error::IO(format!("failed reading: {path}: {e}")))?;
+    let mut reader: Box<dyn Read> = match path.extension() {
+        Some("gz") => Box::new(MultiGzDecoder::new(file)),
+        Some("bz2") => Box::new(MultiBzDecoder::new(file)),
+        Some("xz") => Box::new(XzDecoder::new_multi_decoder(file)),
+        _ => Box::new(file),
+    };
+
+    let mut data = String::new();
+    reader
+        .read_to_string(&mut data)
+        .map_err(|e| Error::IO(format!("failed reading: {path}: {e}")))?;
+    Ok(data)
+}
+
+/// Strip the OpenPGP cleartext signature wrapping from Manifest data, if it exists.
+fn strip_signature(data: &str) -> (bool, String) {
+    if !data.starts_with("-----BEGIN PGP SIGNED MESSAGE-----") {
+        return (false, data.to_string());
+    }
+
+    let content = data
+        .lines()
+        // skip armor headers
+        .skip_while(|line| !line.is_empty())
+        .skip(1)
+        .take_while(|line| *line != "-----BEGIN PGP SIGNATURE-----")
+        // undo dash escaping
+        .map(|line| line.strip_prefix("- ").unwrap_or(line))
+        .fold(String::new(), |mut s, line| {
+            s.push_str(line);
+            s.push('\n');
+            s
+        });
+
+    (true, content)
+}
+
+/// Return the current UTC time formatted as required for TIMESTAMP entries.
+fn timestamp() -> String {
+    humantime::format_rfc3339_seconds(SystemTime::now()).to_string()
+}
+
+/// Manifest file contained in a GLEP 74 Manifest tree.
+#[derive(Debug, Default)]
+struct TreeManifest {
+    entries: Vec<ManifestEntry>,
+    ignore: Vec<String>,
+    timestamp: Option<String>,
+    signed: bool,
+}
+
+impl TreeManifest {
+    /// Parse a Manifest file.
+    fn from_path(path: &Utf8Path) -> crate::Result<Self> {
+        let data = read_manifest(path)?;
+        Self::parse(&data).map_err(|e| Error::InvalidValue(format!("{path}: {e}")))
+    }
+
+    /// Parse a string into a [`TreeManifest`].
+    fn parse(data: &str) -> crate::Result<Self> {
+        let (signed, data) = strip_signature(data);
+        let mut manifest = Self { signed, ..Default::default() };
+
+        for (i, line) in data.lines().enumerate() {
+            let err = |e: &str| Error::InvalidValue(format!("line {}: {e}", i + 1));
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [] => (),
+                ["IGNORE", path] => manifest.ignore.push(path.to_string()),
+                ["TIMESTAMP", value] => manifest.timestamp = Some(value.to_string()),
+                ["IGNORE" | "TIMESTAMP", ..] => {
+                    return Err(err("invalid number of manifest tokens"));
+                }
+                _ => manifest
+                    .entries
+                    .push(line.parse().map_err(|e: Error| err(&e.to_string()))?),
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+impl fmt::Display for TreeManifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(value) = &self.timestamp {
+            writeln!(f, "TIMESTAMP {value}")?;
+        }
+        for path in &self.ignore {
+            writeln!(f, "IGNORE {path}")?;
+        }
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Manifest tree verification failure.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TreeError {
+    /// File listed in a Manifest doesn't exist.
+    Missing(Utf8PathBuf),
+    /// File exists without being listed in a Manifest.
+    Unlisted(Utf8PathBuf),
+    /// File failed size or hash verification.
+    Invalid { path: Utf8PathBuf, err: String },
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "{path}: missing file"),
+            Self::Unlisted(path) => write!(f, "{path}: unlisted file"),
+            Self::Invalid { path, err } => write!(f, "{path}: {err}"),
+        }
+    }
+}
+
+/// Repository-wide Manifest tree as defined by GLEP 74.
+#[derive(Debug, Clone)]
+pub struct ManifestTree {
+    path: Utf8PathBuf,
+    signed: bool,
+    keyring: Option<Utf8PathBuf>,
+}
+
+impl ManifestTree {
+    /// Create a Manifest tree for a given repo path.
+    pub fn new<P: AsRef<Utf8Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            signed: false,
+            keyring: None,
+        }
+    }
+
+    /// Require a valid OpenPGP signature on the top-level Manifest.
+    pub fn signed(mut self, value: bool) -> Self {
+        self.signed = value;
+        self
+    }
+
+    /// Use a custom keyring for OpenPGP signature verification.
+    pub fn keyring<P: AsRef<Utf8Path>>(mut self, path: P) -> Self {
+        self.keyring = Some(path.as_ref().to_path_buf());
+        self.signed = true;
+        self
+    }
+
+    /// Verify the OpenPGP signature for the top-level Manifest.
+    fn verify_signature(&self, path: &Utf8Path) -> crate::Result<()> {
+        let mut cmd = if let Some(keyring) = &self.keyring {
+            let mut cmd = Command::new("gpgv");
+            cmd.args(["--keyring", keyring.as_str()]);
+            cmd
+        } else {
+            let mut cmd = Command::new("gpg");
+            cmd.arg("--verify");
+            cmd
+        };
+
+        let output = cmd
+            .arg(path)
+            .output()
+            .map_err(|e| Error::IO(format!("failed running {:?}: {e}", cmd.get_program())))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let msg = String::from_utf8_lossy(&output.stderr);
+            Err(Error::InvalidValue(format!(
+                "{path}: invalid OpenPGP signature: {}",
+                msg.trim()
+            )))
+        }
+    }
+
+    /// Verify the Manifest tree, returning all verification failures.
+    ///
+    /// An error is returned if the top-level Manifest can't be loaded or its signature
+    /// verification fails.
+    pub fn verify(&self) -> crate::Result<Vec<TreeError>> {
+        let top = self.path.join("Manifest");
+        let manifest = TreeManifest::from_path(&top)?;
+
+        if self.signed {
+            if !manifest.signed {
+                return Err(Error::InvalidValue(format!("{top}: unsigned Manifest")));
+            }
+            self.verify_signature(&top)?;
+        }
+
+        let mut errors = vec![];
+        let mut files = IndexMap::new();
+        let mut ignored = vec![];
+        let mut manifests = vec![(Utf8PathBuf::new(), manifest)];
+
+        // recursively load sub-Manifests, collecting all covered files
+        while let Some((dir, manifest)) = manifests.pop() {
+            ignored.extend(manifest.ignore.iter().map(|x| dir.join(x)));
+
+            for entry in manifest.entries {
+                let path = match entry.kind() {
+                    ManifestType::Dist => continue,
+                    ManifestType::Aux => dir.join("files").join(entry.name()),
+                    _ => dir.join(entry.name()),
+                };
+
+                if entry.kind() == ManifestType::Manifest {
+                    let abspath = self.path.join(&path);
+                    let subdir = path.parent().unwrap_or(&dir).to_path_buf();
+                    if !abspath.exists() {
+                        errors.push(TreeError::Missing(path.clone()));
+                    } else if let Err(e) = verify_entry(&entry, &abspath)
+                        .and_then(|_| TreeManifest::from_path(&abspath))
+                        .map(|m| manifests.push((subdir, m)))
+                    {
+                        let err = e.to_string();
+                        errors.push(TreeError::Invalid { path: path.clone(), err });
+                    }
+                }
+
+                files.insert(path, entry);
+            }
+        }
+
+        // verify all listed files in parallel
+        errors.par_extend(files.par_iter().filter_map(|(path, entry)| {
+            let abspath = self.path.join(path);
+            match entry.kind() {
+                ManifestType::Manifest => None,
+                // MISC files are optional
+                ManifestType::Misc if !abspath.exists() => None,
+                _ if !abspath.exists() => Some(TreeError::Missing(path.clone())),
+                _ => verify_entry(entry, &abspath)
+                    .err()
+                    .map(|e| TreeError::Invalid {
+                        path: path.clone(),
+                        err: e.to_string(),
+                    }),
+            }
+        }));
+
+        // flag files that aren't listed in any Manifest
+        let walker = WalkDir::new(&self.path)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                !is_hidden(e)
+                    && e.path()
+                        .strip_prefix(&self.path)
+                        .map(|p| !ignored.iter().any(|x| p.starts_with(x)))
+                        .unwrap_or(true)
+            });
+
+        for entry in walker.filter_map(Result::ok) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            if let Some(path) = entry
+                .path()
+                .strip_prefix(&self.path)
+                .ok()
+                .and_then(|p| Utf8Path::from_path(p))
+                && path != "Manifest"
+                && !files.contains_key(path)
+            {
+                errors.push(TreeError::Unlisted(path.to_path_buf()));
+            }
+        }
+
+        errors.sort();
+        Ok(errors)
+    }
+
+    /// Regenerate the Manifest tree using the given hashes.
+    ///
+    /// Sub-Manifests are created for directories up to two levels deep, e.g. for
+    /// categories and packages, with existing DIST entries being preserved.
+    pub fn generate<'a, I>(&self, hashes: I) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = &'a HashType> + Send + Sync + Copy,
+    {
+        self.generate_dir(&self.path, 0, hashes)
+    }
+
+    /// Generate the Manifest for a directory, recursing into subdirectories.
+    fn generate_dir<'a, I>(&self, dir: &Utf8Path, depth: usize, hashes: I) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = &'a HashType> + Send + Sync + Copy,
+    {
+        let path = dir.join("Manifest");
+        let mut manifest = TreeManifest::default();
+
+        // preserve existing distfile entries
+        if path.exists() {
+            manifest.entries.extend(
+                TreeManifest::from_path(&path)?
+                    .entries
+                    .into_iter()
+                    .filter(|x| x.kind() == ManifestType::Dist),
+            );
+        }
+
+        let mut files = vec![];
+        for entry in sorted_dir_list_utf8(dir)? {
+            let name = entry.file_name();
+            if name.starts_with('.')
+                || is_manifest(name)
+                || (depth == 0 && IGNORED.contains(&name))
+            {
+                continue;
+            }
+
+            let abspath = entry.path();
+            if abspath.is_dir() {
+                if depth < SUBMANIFEST_DEPTH {
+                    self.generate_dir(abspath, depth + 1, hashes)?;
+                    files.push((ManifestType::Manifest, format!("{name}/Manifest")));
+                } else {
+                    for e in WalkDir::new(abspath)
+                        .sort_by_file_name()
+                        .into_iter()
+                        .filter_entry(|e| !is_hidden(e))
+                        .filter_map(Result::ok)
+                        .filter(|e| e.file_type().is_file())
+                    {
+                        if let Some(p) =
+                            e.path().strip_prefix(dir).ok().and_then(|p| p.to_str())
+                        {
+                            files.push((ManifestType::Data, p.to_string()));
+                        }
+                    }
+                }
+            } else if abspath.is_file() {
+                files.push((ManifestType::Data, name.to_string()));
+            }
+        }
+
+        // generate file hashes in parallel
+        let entries: Vec<_> = files
+            .into_par_iter()
+            .map(|(kind, name)| {
+                let abspath = dir.join(&name);
+                ManifestEntry::from_path(kind, name, abspath, hashes)
+            })
+            .collect::<crate::Result<_>>()?;
+        manifest.entries.extend(entries);
+        manifest.entries.sort_by(|a, b| a.name().cmp(b.name()));
+
+        if depth == 0 {
+            manifest.ignore = IGNORED.iter().map(|s| s.to_string()).collect();
+            manifest.timestamp = Some(timestamp());
+        }
+
+        atomic_write_file(&path, manifest.to_string())
+    }
+}
+
+/// Verify a file matches the size and hashes of its Manifest entry.
+fn verify_entry(entry: &ManifestEntry, path: &Utf8Path) -> crate::Result<()> {
+    // errors are reported using full paths so drop the entry name prefix
+    entry.verify_path(path).map_err(|e| {
+        let msg = e.to_string();
+        let prefix = format!("{}: ", entry.name());
+        Error::InvalidValue(msg.strip_prefix(&prefix).unwrap_or(&msg).to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        // invalid
+        let r = TreeManifest::parse("IGNORE");
+        assert_err_re!(r, "line 1: invalid number of manifest tokens");
+        let r = TreeManifest::parse("UNKNOWN a 1 BLAKE2B a");
+        assert_err_re!(r, "line 1: invalid manifest type: UNKNOWN");
+
+        // signed
+        let data = indoc::indoc! {"
+            -----BEGIN PGP SIGNED MESSAGE-----
+            Hash: SHA256
+
+            TIMESTAMP 2024-01-01T00:00:00Z
+            IGNORE distfiles
+            DATA a 1 BLAKE2B a
+            -----BEGIN PGP SIGNATURE-----
+
+            abcdef
+            -----END PGP SIGNATURE-----
+        "};
+        let manifest = TreeManifest::parse(data).unwrap();
+        assert!(manifest.signed);
+        assert_eq!(manifest.timestamp.as_deref(), Some("2024-01-01T00:00:00Z"));
+        assert_eq!(manifest.ignore, ["distfiles"]);
+        assert_eq!(manifest.entries.len(), 1);
+    }
+
+    #[test]
+    fn timestamp_format() {
+        let value = timestamp();
+        assert_eq!(value.len(), 20);
+        assert!(value.ends_with('Z'));
+        assert!(humantime::parse_rfc3339(&value).is_ok());
+    }
+
+    #[test]
+    fn compressed() {
+        use std::io::Write;
+
+        let tmpdir = tempdir().unwrap();
+        let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+        let data = "TIMESTAMP 2024-01-01T00:00:00Z\nDATA a 1 BLAKE2B a\n";
+
+        let mut gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gz.write_all(data.as_bytes()).unwrap();
+        let mut bz2 = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+        bz2.write_all(data.as_bytes()).unwrap();
+        let mut xz = liblzma::write::XzEncoder::new(vec![], 6);
+        xz.write_all(data.as_bytes()).unwrap();
+
+        for (ext, value) in [
+            ("gz", gz.finish().unwrap()),
+            ("bz2", bz2.finish().unwrap()),
+            ("xz", xz.finish().unwrap()),
+        ] {
+            let path = dir.join(format!("Manifest.{ext}"));
+            fs::write(&path, value).unwrap();
+            let manifest = TreeManifest::from_path(&path).unwrap();
+            assert_eq!(manifest.to_string(), data, "failed decompressing: {ext}");
+        }
+
+        // invalid compressed data
+        let path = dir.join("Manifest.gz");
+        fs::write(&path, data).unwrap();
+        let r = TreeManifest::from_path(&path);
+        assert_err_re!(r, "failed reading: ");
+    }
+
+    #[test]
+    fn generate_and_verify() {
+        let tmpdir = tempdir().unwrap();
+        let path = Utf8Path::from_path(tmpdir.path()).unwrap();
+        let hashes = &[HashType::Blake2b, HashType::Sha512];
+
+        // create repo files
+        fs::create_dir_all(path.join("cat/pkg/files")).unwrap();
+        fs::create_dir_all(path.join("profiles")).unwrap();
+        fs::create_dir_all(path.join("distfiles")).unwrap();
+        fs::write(path.join("cat/pkg/pkg-1.ebuild"), "EAPI=8").unwrap();
+        fs::write(path.join("cat/pkg/files/a.patch"), "patch").unwrap();
+        fs::write(path.join("cat/pkg/Manifest"), "DIST a.tar.gz 1 BLAKE2B a\n").unwrap();
+        fs::write(path.join("profiles/repo_name"), "test").unwrap();
+        fs::write(path.join("distfiles/a.tar.gz"), "a").unwrap();
+        fs::write(path.join("header.txt"), "header").unwrap();
+
+        // missing top-level Manifest
+        let tree = ManifestTree::new(path);
+        assert!(tree.verify().is_err());
+
+        // generated tree verifies
+        tree.generate(hashes).unwrap();
+        assert!(tree.verify().unwrap().is_empty());
+        let pkg_manifest = fs::read_to_string(path.join("cat/pkg/Manifest")).unwrap();
+        assert!(pkg_manifest.contains("DIST a.tar.gz"));
+        assert!(pkg_manifest.contains("DATA files/a.patch"));
+
+        // unsigned Manifest fails when requiring signatures
+        let r = tree.clone().signed(true).verify();
+        assert_err_re!(r, "unsigned Manifest");
+
+        // modified, unlisted, and missing files
+        fs::write(path.join("cat/pkg/pkg-1.ebuild"), "EAPI=7").unwrap();
+        fs::write(path.join("cat/pkg/pkg-2.ebuild"), "EAPI=8").unwrap();
+        fs::remove_file(path.join("profiles/repo_name")).unwrap();
+        let errors = tree.verify().unwrap();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].to_string(), "profiles/repo_name: missing file");
+        assert_eq!(errors[1].to_string(), "cat/pkg/pkg-2.ebuild: unlisted file");
+        let err = errors[2].to_string();
+        assert!(err.starts_with("cat/pkg/pkg-1.ebuild: failed verifying: size: expected: 6"));
+        assert!(err.contains("BLAKE2B: expected: "));
+
+        // regenerating fixes all issues
+        tree.generate(hashes).unwrap();
+        assert!(tree.verify().unwrap().is_empty());
+    }
+}
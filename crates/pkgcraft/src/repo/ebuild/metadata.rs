@@ -31,7 +31,8 @@ use crate::traits::FilterLines;
 use crate::types::{OrderedMap, OrderedSet};
 
 use super::Eclass;
-use super::cache::{CacheFormat, MetadataCache};
+use super::cache::{Cache, CacheFormat, MetadataCache};
+use super::profile::ProfileDesc;
 
 /// Wrapper for ini format config files.
 #[derive(Debug, Default)]
@@ -290,6 +291,11 @@ impl Mirror {
         &self.name
     }
 
+    /// Return the mirror's base url.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
     /// Return a mirrored url for a path.
     pub(crate) fn get_url(&self, path: &str) -> crate::Result<Url> {
         self.url
@@ -357,6 +363,7 @@ pub struct Metadata {
     pkg_mask: OnceLock<IndexSet<Dep>>,
     pkg_metadata_cache: DashMap<Cpn, Arc<xml::Metadata>>,
     pkg_manifest_cache: DashMap<Cpn, Arc<Manifest>>,
+    profiles_desc: OnceLock<IndexSet<ProfileDesc>>,
     updates: OnceLock<IndexSet<PkgUpdate>>,
     use_global: OnceLock<IndexMap<String, String>>,
     use_expand: OnceLock<IndexMap<String, IndexMap<String, String>>>,
@@ -464,15 +471,23 @@ impl Metadata {
 
     pub fn cache(&self) -> &MetadataCache {
         self.cache.get_or_init(|| {
-            // TODO: support multiple cache formats?
-            let format = self
+            // use the first configured format with an existing cache, falling back to the
+            // first configured format while skipping PMS caches that lack inherit data
+            let mut caches: Vec<_> = self
                 .config
                 .cache_formats
-                .first()
-                .copied()
-                .unwrap_or_default();
-
-            format.from_repo(&self.path)
+                .iter()
+                .filter(|format| **format != CacheFormat::Pms)
+                .map(|format| format.from_repo(&self.path))
+                .collect();
+
+            if let Some(idx) = caches.iter().position(|cache| cache.path().exists()) {
+                caches.swap_remove(idx)
+            } else if !caches.is_empty() {
+                caches.swap_remove(0)
+            } else {
+                CacheFormat::default().from_repo(&self.path)
+            }
         })
     }
 
@@ -715,6 +730,22 @@ impl Metadata {
         }
     }
 
+    /// Return a repo's profiles from `profiles/profiles.desc`.
+    pub fn profiles_desc(&self) -> &IndexSet<ProfileDesc> {
+        self.profiles_desc.get_or_init(|| {
+            self.read_path("profiles/profiles.desc")
+                .filter_lines()
+                .filter_map(|(i, s)| match ProfileDesc::from_line(s, self.arches()) {
+                    Ok(desc) => Some(desc),
+                    Err(e) => {
+                        warn!("{}::profiles/profiles.desc, line {i}: {e}", self.id);
+                        None
+                    }
+                })
+                .collect()
+        })
+    }
+
     /// Return the ordered set of package updates.
     pub fn updates(&self) -> &IndexSet<PkgUpdate> {
         self.updates.get_or_init(|| {
@@ -920,6 +951,28 @@ mod tests {
         assert!(!metadata.config.thin_manifests);
     }
 
+    #[test]
+    fn cache() {
+        let repo = EbuildRepoBuilder::new().build().unwrap();
+
+        // default
+        let metadata = Metadata::try_new("test", repo.path()).unwrap();
+        assert_eq!(metadata.cache().format(), CacheFormat::Md5Dict);
+
+        // existing cache
+        fs::write(repo.path().join("metadata/layout.conf"), "cache-formats = md5-dict binary")
+            .unwrap();
+        fs::write(repo.path().join("metadata/pkgcraft-cache"), "").unwrap();
+        let metadata = Metadata::try_new("test", repo.path()).unwrap();
+        assert_eq!(metadata.cache().format(), CacheFormat::Binary);
+
+        // PMS caches are skipped
+        fs::write(repo.path().join("metadata/layout.conf"), "cache-formats = pms").unwrap();
+        fs::create_dir_all(repo.path().join("metadata/cache")).unwrap();
+        let metadata = Metadata::try_new("test", repo.path()).unwrap();
+        assert_eq!(metadata.cache().format(), CacheFormat::Md5Dict);
+    }
+
     #[test]
     fn arches() {
         let repo = EbuildRepoBuilder::new().build().unwrap();
@@ -1151,7 +1204,7 @@ mod tests {
                 .get("mirror1")
                 .unwrap()
                 .iter()
-                .map(|x| x.url.as_str()),
+                .map(|x| x.url().as_str()),
             ["https://a/mirror/", "https://another/mirror/"],
         );
         assert_ordered_eq!(
@@ -1160,7 +1213,7 @@ mod tests {
                 .get("mirror2")
                 .unwrap()
                 .iter()
-                .map(|x| x.url.as_str()),
+                .map(|x| x.url().as_str()),
             ["http://yet/another/mirror/"]
         );
     }
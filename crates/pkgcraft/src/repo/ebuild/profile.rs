@@ -0,0 +1,694 @@
+use std::{fs, io};
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use indexmap::{IndexMap, IndexSet};
+use strum::{AsRefStr, Display, EnumString};
+use tracing::warn;
+
+use crate::Error;
+use crate::dep::Dep;
+use crate::eapi::Eapi;
+use crate::pkg::ebuild::EbuildPkg;
+use crate::pkg::ebuild::keyword::{Arch, KeywordStatus};
+use crate::repo::Repository;
+use crate::traits::{FilterLines, Intersects};
+use crate::utils::relpath_utf8;
+
+use super::EbuildRepo;
+
+/// Profile stability status from `profiles/profiles.desc`.
+#[derive(
+    AsRefStr, Display, EnumString, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum ProfileStatus {
+    Stable,
+    Dev,
+    Exp,
+}
+
+/// Profile entry from `profiles/profiles.desc`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ProfileDesc {
+    arch: Arch,
+    path: String,
+    status: ProfileStatus,
+}
+
+impl ProfileDesc {
+    /// Parse a line from profiles/profiles.desc into a profile entry.
+    pub(super) fn from_line(line: &str, arches: &IndexSet<Arch>) -> crate::Result<Self> {
+        let vals: Vec<_> = line.split_whitespace().collect();
+        let [arch, path, status] = &vals[..] else {
+            return Err(Error::InvalidValue(
+                "invalid line format: should be '<arch> <path> <status>'".to_string(),
+            ));
+        };
+
+        if !arches.contains(*arch) {
+            return Err(Error::InvalidValue(format!("unknown arch: {arch}")));
+        }
+
+        let status = status
+            .parse()
+            .map_err(|_| Error::InvalidValue(format!("unknown status: {status}")))?;
+
+        Ok(Self {
+            arch: (*arch).into(),
+            path: path.to_string(),
+            status,
+        })
+    }
+
+    /// Return the profile's architecture.
+    pub fn arch(&self) -> &Arch {
+        &self.arch
+    }
+
+    /// Return the profile's path relative to the profiles directory.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return the profile's stability status.
+    pub fn status(&self) -> ProfileStatus {
+        self.status
+    }
+}
+
+/// Apply incremental values to a set where "-*" clears it and "-value" removes a value.
+fn incremental<'a, I>(set: &mut IndexSet<String>, values: I)
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    for value in values {
+        if value == "-*" {
+            set.clear();
+        } else if let Some(x) = value.strip_prefix('-') {
+            set.shift_remove(x);
+        } else {
+            set.insert(value.to_string());
+        }
+    }
+}
+
+/// Lexically normalize a path, resolving any parent directory components.
+fn normalize(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalized = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => (),
+            Utf8Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Expand variable references in a value using a lookup function.
+fn expand<F>(value: &str, lookup: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let name: String = if chars.next_if_eq(&'{').is_some() {
+            chars.by_ref().take_while(|c| *c != '}').collect()
+        } else {
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                name.push(c);
+            }
+            name
+        };
+
+        if name.is_empty() {
+            expanded.push('$');
+        } else if let Some(value) = lookup(&name) {
+            expanded.push_str(&value);
+        }
+    }
+    expanded
+}
+
+/// Parse make.defaults data into variable assignments.
+///
+/// Only the simple assignment subset of bash used by profiles is supported, with
+/// variable references expanded using prior assignments.
+fn parse_make_defaults(
+    data: &str,
+    vars: &IndexMap<String, String>,
+) -> crate::Result<IndexMap<String, String>> {
+    let mut values = IndexMap::<String, String>::new();
+    let mut lines = data.lines().enumerate().map(|(i, s)| (i + 1, s.trim()));
+    while let Some((i, line)) = lines.next() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(Error::InvalidValue(format!("line {i}: invalid assignment: {line}")));
+        };
+
+        let mut value = value.to_string();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'');
+        if let Some(quote) = quote {
+            // join multi-line quoted values
+            while value.len() < 2 || !value.ends_with(quote) {
+                let Some((_, line)) = lines.next() else {
+                    return Err(Error::InvalidValue(format!("line {i}: unterminated quote")));
+                };
+                value.push(' ');
+                value.push_str(line);
+            }
+            value = value[1..value.len() - 1].to_string();
+        }
+
+        if quote != Some('\'') {
+            value = expand(&value, |k| values.get(k).or_else(|| vars.get(k)).cloned());
+        }
+
+        values.insert(key.to_string(), value);
+    }
+
+    Ok(values)
+}
+
+/// Package specific flag entries, e.g. from package.use.mask.
+type PkgFlags = Vec<(Dep, Vec<String>)>;
+
+/// Stacked profile state.
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    path: String,
+    nodes: Vec<Utf8PathBuf>,
+    vars: IndexMap<String, String>,
+    arch: Option<Arch>,
+    use_flags: IndexSet<String>,
+    use_expand: IndexMap<String, IndexSet<String>>,
+    accept_keywords: IndexSet<String>,
+    use_mask: IndexSet<String>,
+    use_force: IndexSet<String>,
+    use_stable_mask: IndexSet<String>,
+    use_stable_force: IndexSet<String>,
+    pkg_mask: IndexSet<Dep>,
+    pkg_unmask: IndexSet<Dep>,
+    pkg_use: PkgFlags,
+    pkg_use_mask: PkgFlags,
+    pkg_use_force: PkgFlags,
+    pkg_use_stable_mask: PkgFlags,
+    pkg_use_stable_force: PkgFlags,
+}
+
+impl Profile {
+    /// Load a profile from its path relative to a repo's profiles directory.
+    pub fn load(repo: &EbuildRepo, path: &str) -> crate::Result<Self> {
+        let dir = normalize(&repo.path().join("profiles").join(path));
+        if !dir.is_dir() {
+            return Err(Error::InvalidValue(format!("nonexistent profile: {path}")));
+        }
+
+        let mut nodes = vec![];
+        Self::resolve(repo, &dir, &mut nodes, &mut Default::default())?;
+
+        let mut profile = Self {
+            path: path.to_string(),
+            ..Default::default()
+        };
+
+        // packages masked at the repo level apply to all profiles
+        let trees: Vec<_> = repo.trees().collect();
+        for r in trees.into_iter().rev() {
+            profile
+                .pkg_mask
+                .extend(r.metadata().pkg_mask().iter().cloned());
+        }
+
+        for node in &nodes {
+            profile.load_node(repo, node)?;
+        }
+
+        // expand USE_EXPAND variables into USE flags
+        for (key, values) in &profile.use_expand {
+            let prefix = key.to_lowercase();
+            profile
+                .use_flags
+                .extend(values.iter().map(|x| format!("{prefix}_{x}")));
+        }
+
+        profile.arch = profile.vars.get("ARCH").map(|s| s.as_str().into());
+        profile.nodes = nodes;
+        Ok(profile)
+    }
+
+    /// Return the relative path to a profile directory for error messages.
+    fn relpath(repo: &EbuildRepo, dir: &Utf8Path) -> Utf8PathBuf {
+        relpath_utf8(dir, repo.path().join("profiles")).unwrap_or_else(|| dir.to_path_buf())
+    }
+
+    /// Resolve the profile directory stack depth-first, parents before children.
+    fn resolve(
+        repo: &EbuildRepo,
+        dir: &Utf8Path,
+        nodes: &mut Vec<Utf8PathBuf>,
+        stack: &mut IndexSet<Utf8PathBuf>,
+    ) -> crate::Result<()> {
+        if !stack.insert(dir.to_path_buf()) {
+            let path = Self::relpath(repo, dir);
+            return Err(Error::InvalidValue(format!("{path}: cyclic parent")));
+        }
+
+        let data = Self::read(repo, dir, "parent")?;
+        for (i, value) in data.filter_lines() {
+            let parent = Self::parent(repo, dir, value).map_err(|e| {
+                let path = Self::relpath(repo, dir);
+                Error::InvalidValue(format!("{path}/parent, line {i}: {e}"))
+            })?;
+            Self::resolve(repo, &parent, nodes, stack)?;
+        }
+
+        stack.pop();
+        nodes.push(dir.to_path_buf());
+        Ok(())
+    }
+
+    /// Resolve a parent file entry to its profile directory.
+    ///
+    /// Entries prefixed with a repo name and colon are relative to the profiles
+    /// directory of the matching inherited repo, otherwise they're relative to the
+    /// profile directory.
+    pub fn parent(
+        repo: &EbuildRepo,
+        dir: &Utf8Path,
+        value: &str,
+    ) -> crate::Result<Utf8PathBuf> {
+        let path = if let Some((name, path)) = value.split_once(':') {
+            let Some(repo) = repo.trees().find(|r| r.name() == name) else {
+                return Err(Error::InvalidValue(format!("unknown repo: {name}")));
+            };
+            repo.path().join("profiles").join(path)
+        } else {
+            dir.join(value)
+        };
+
+        let path = normalize(&path);
+        if path.is_dir() {
+            Ok(path)
+        } else {
+            Err(Error::InvalidValue(format!("nonexistent parent: {value}")))
+        }
+    }
+
+    /// Read a profile file, returning an empty string if it doesn't exist.
+    fn read(repo: &EbuildRepo, dir: &Utf8Path, name: &str) -> crate::Result<String> {
+        match fs::read_to_string(dir.join(name)) {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => {
+                let path = Self::relpath(repo, dir);
+                Err(Error::IO(format!("{path}/{name}: {e}")))
+            }
+        }
+    }
+
+    /// Stack the settings from a profile directory onto the profile.
+    fn load_node(&mut self, repo: &EbuildRepo, dir: &Utf8Path) -> crate::Result<()> {
+        let path = Self::relpath(repo, dir);
+        let eapi_path = dir.join("eapi");
+        let eapi: &'static Eapi = if eapi_path.exists() {
+            eapi_path
+                .as_path()
+                .try_into()
+                .map_err(|e| Error::InvalidValue(format!("{path}/eapi: {e}")))?
+        } else {
+            repo.eapi()
+        };
+
+        let data = Self::read(repo, dir, "make.defaults")?;
+        let vars = parse_make_defaults(&data, &self.vars)
+            .map_err(|e| Error::InvalidValue(format!("{path}/make.defaults, {e}")))?;
+        if let Some(value) = vars.get("USE_EXPAND") {
+            for key in value.split_whitespace() {
+                self.use_expand.entry(key.to_string()).or_default();
+            }
+        }
+        for (key, value) in &vars {
+            let values = value.split_whitespace();
+            match key.as_str() {
+                "USE" => incremental(&mut self.use_flags, values),
+                "ACCEPT_KEYWORDS" => incremental(&mut self.accept_keywords, values),
+                _ => {
+                    if let Some(set) = self.use_expand.get_mut(key) {
+                        incremental(set, values);
+                    }
+                }
+            }
+        }
+        self.vars.extend(vars);
+
+        // flag files
+        for (name, set) in [
+            ("use.mask", &mut self.use_mask),
+            ("use.force", &mut self.use_force),
+            ("use.stable.mask", &mut self.use_stable_mask),
+            ("use.stable.force", &mut self.use_stable_force),
+        ] {
+            let data = Self::read(repo, dir, name)?;
+            incremental(set, data.filter_lines().map(|(_, s)| s));
+        }
+
+        // package files
+        for (name, set) in
+            [("package.mask", &mut self.pkg_mask), ("package.unmask", &mut self.pkg_unmask)]
+        {
+            for (i, s) in Self::read(repo, dir, name)?.filter_lines() {
+                let (remove, s) = match s.strip_prefix('-') {
+                    Some(s) => (true, s),
+                    None => (false, s),
+                };
+                match eapi.dep(s) {
+                    Ok(dep) if remove => {
+                        set.shift_remove(&dep);
+                    }
+                    Ok(dep) => {
+                        set.insert(dep);
+                    }
+                    Err(e) => warn!("{repo}::profiles/{path}/{name}, line {i}: {e}"),
+                }
+            }
+        }
+
+        // package flag files
+        for (name, entries) in [
+            ("package.use", &mut self.pkg_use),
+            ("package.use.mask", &mut self.pkg_use_mask),
+            ("package.use.force", &mut self.pkg_use_force),
+            ("package.use.stable.mask", &mut self.pkg_use_stable_mask),
+            ("package.use.stable.force", &mut self.pkg_use_stable_force),
+        ] {
+            for (i, s) in Self::read(repo, dir, name)?.filter_lines() {
+                let mut vals = s.split_whitespace();
+                let dep = vals.next().unwrap_or_default();
+                match eapi.dep(dep) {
+                    Ok(dep) => entries.push((dep, vals.map(|s| s.to_string()).collect())),
+                    Err(e) => warn!("{repo}::profiles/{path}/{name}, line {i}: {e}"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the profile's path relative to the profiles directory.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return the stacked profile directories in inheritance order.
+    pub fn nodes(&self) -> &[Utf8PathBuf] {
+        &self.nodes
+    }
+
+    /// Return the profile's architecture if defined.
+    pub fn arch(&self) -> Option<&Arch> {
+        self.arch.as_ref()
+    }
+
+    /// Return the value of a make.defaults variable if defined.
+    pub fn var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|s| s.as_str())
+    }
+
+    /// Return the USE flags enabled by the profile, including USE_EXPAND flags.
+    pub fn use_flags(&self) -> &IndexSet<String> {
+        &self.use_flags
+    }
+
+    /// Return the keywords accepted by the profile.
+    pub fn accept_keywords(&self) -> &IndexSet<String> {
+        &self.accept_keywords
+    }
+
+    /// Determine if a package is masked by the profile.
+    pub fn masked(&self, pkg: &EbuildPkg) -> bool {
+        self.pkg_mask.iter().any(|x| pkg.intersects(x))
+            && !self.pkg_unmask.iter().any(|x| pkg.intersects(x))
+    }
+
+    /// Determine if a package's keywords are accepted by the profile.
+    pub fn accepted(&self, pkg: &EbuildPkg) -> bool {
+        let keywords = pkg.keywords();
+        self.accept_keywords
+            .iter()
+            .any(|accept| match accept.as_str() {
+                "**" => true,
+                "*" => keywords.iter().any(|k| k.status() == KeywordStatus::Stable),
+                "~*" => keywords
+                    .iter()
+                    .any(|k| k.status() == KeywordStatus::Unstable),
+                s => {
+                    let (arch, status) = match s.strip_prefix('~') {
+                        Some(arch) => (arch, KeywordStatus::Unstable),
+                        None => (s, KeywordStatus::Stable),
+                    };
+                    keywords
+                        .iter()
+                        .any(|k| k.arch() == arch && k.status() >= status)
+                }
+            })
+    }
+
+    /// Determine if a package is unmasked with keywords accepted by the profile.
+    pub fn visible(&self, pkg: &EbuildPkg) -> bool {
+        !self.masked(pkg) && self.accepted(pkg)
+    }
+
+    /// Determine if a package is stable for the profile's architecture.
+    fn stable(&self, pkg: &EbuildPkg) -> bool {
+        self.arch.as_ref().is_some_and(|arch| {
+            pkg.keywords()
+                .iter()
+                .any(|k| k.arch() == arch && k.status() == KeywordStatus::Stable)
+        })
+    }
+
+    /// Apply matching package specific flag entries to a set of flags.
+    fn pkg_flags(flags: &mut IndexSet<String>, entries: &PkgFlags, pkg: &EbuildPkg) {
+        for (_, values) in entries.iter().filter(|(dep, _)| pkg.intersects(dep)) {
+            incremental(flags, values.iter().map(|s| s.as_str()));
+        }
+    }
+
+    /// Return the USE flags masked for a package.
+    pub fn use_masked(&self, pkg: &EbuildPkg) -> IndexSet<String> {
+        let mut flags = self.use_mask.clone();
+        Self::pkg_flags(&mut flags, &self.pkg_use_mask, pkg);
+        if self.stable(pkg) {
+            incremental(&mut flags, self.use_stable_mask.iter().map(|s| s.as_str()));
+            Self::pkg_flags(&mut flags, &self.pkg_use_stable_mask, pkg);
+        }
+        flags
+    }
+
+    /// Return the USE flags forced for a package.
+    pub fn use_forced(&self, pkg: &EbuildPkg) -> IndexSet<String> {
+        let mut flags = self.use_force.clone();
+        Self::pkg_flags(&mut flags, &self.pkg_use_force, pkg);
+        if self.stable(pkg) {
+            incremental(&mut flags, self.use_stable_force.iter().map(|s| s.as_str()));
+            Self::pkg_flags(&mut flags, &self.pkg_use_stable_force, pkg);
+        }
+        flags
+    }
+
+    /// Return the USE flags enabled for a package.
+    ///
+    /// Profile and package.use flags are applied on top of IUSE defaults, then masked
+    /// flags are disabled and forced flags are enabled.
+    pub fn use_enabled(&self, pkg: &EbuildPkg) -> IndexSet<String> {
+        let mut flags: IndexSet<_> = pkg
+            .iuse()
+            .iter()
+            .filter(|x| x.is_enabled())
+            .map(|x| x.flag().to_string())
+            .collect();
+        flags.extend(self.use_flags.iter().cloned());
+        Self::pkg_flags(&mut flags, &self.pkg_use, pkg);
+        let masked = self.use_masked(pkg);
+        flags.retain(|x| !masked.contains(x));
+        flags.extend(self.use_forced(pkg));
+        flags.retain(|x| pkg.iuse_effective().contains(x));
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::{assert_err_re, assert_ordered_eq};
+
+    use super::*;
+
+    #[test]
+    fn profiles_desc() {
+        let mut config = Config::default();
+        let temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("arch.list"), "amd64\narm64\n").unwrap();
+        let data = indoc::indoc! {"
+            amd64 default/amd64 stable
+            arm64 default/arm64 dev
+            arm64 default/arm64/exp exp
+            ppc default/ppc stable
+            amd64 default/amd64/test unknown
+            amd64 default/amd64/invalid
+        "};
+        fs::write(path.join("profiles.desc"), data).unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        let profiles = repo.metadata().profiles_desc();
+        assert_ordered_eq!(
+            profiles.iter().map(|x| x.path()),
+            ["default/amd64", "default/arm64", "default/arm64/exp"]
+        );
+        let statuses: Vec<_> = profiles.iter().map(|x| x.status()).collect();
+        assert_eq!(statuses, [ProfileStatus::Stable, ProfileStatus::Dev, ProfileStatus::Exp]);
+        assert_eq!(profiles[0].arch(), "amd64");
+    }
+
+    #[test]
+    fn make_defaults() {
+        let vars = [("USE".to_string(), "a".to_string())].into_iter().collect();
+        let data = indoc::indoc! {r#"
+            # comment
+            ARCH="amd64"
+            USE="${USE} b $ARCH"
+            export LITERAL='$USE'
+            MULTI="1
+                2"
+        "#};
+        let values = parse_make_defaults(data, &vars).unwrap();
+        assert_eq!(values["ARCH"], "amd64");
+        assert_eq!(values["USE"], "a b amd64");
+        assert_eq!(values["LITERAL"], "$USE");
+        assert_eq!(values["MULTI"], "1 2");
+
+        let r = parse_make_defaults("USE", &vars);
+        assert_err_re!(r, "^line 1: invalid assignment: USE$");
+        let r = parse_make_defaults("USE=\"a\nb", &vars);
+        assert_err_re!(r, "^line 1: unterminated quote$");
+    }
+
+    #[test]
+    fn load() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("package.mask"), "cat/masked\n").unwrap();
+        for dir in ["base", "amd64", "cycle", "invalid"] {
+            fs::create_dir_all(path.join(dir)).unwrap();
+        }
+        let files = [
+            ("base/make.defaults", "USE=\"a b\"\nUSE_EXPAND=\"TARGETS\"\nTARGETS=\"x\""),
+            ("base/use.mask", "c\nd"),
+            ("base/package.use.mask", "cat/pkg e"),
+            ("amd64/parent", "../base"),
+            ("amd64/make.defaults", "ARCH=amd64\nACCEPT_KEYWORDS=\"${ARCH}\"\nUSE=\"-a\""),
+            ("amd64/use.mask", "-d"),
+            ("amd64/use.stable.force", "f"),
+            ("amd64/package.use.mask", "cat/pkg -e\n=cat/pkg-2 e"),
+            ("amd64/package.unmask", "=cat/masked-2"),
+            ("cycle/parent", "."),
+            ("invalid/parent", "../nonexistent"),
+        ];
+        for (file, data) in files {
+            fs::write(path.join(file), data).unwrap();
+        }
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+
+        // nonexistent
+        let r = Profile::load(&repo, "nonexistent");
+        assert_err_re!(r, "^nonexistent profile: nonexistent$");
+
+        // cyclic parent
+        let r = Profile::load(&repo, "cycle");
+        assert_err_re!(r, "^cycle: cyclic parent$");
+
+        // invalid parent
+        let r = Profile::load(&repo, "invalid");
+        assert_err_re!(r, "^invalid/parent, line 1: nonexistent parent: ../nonexistent$");
+
+        let profile = Profile::load(&repo, "amd64").unwrap();
+        assert_eq!(profile.path(), "amd64");
+        assert_eq!(profile.arch().unwrap(), "amd64");
+        assert_eq!(profile.var("TARGETS"), Some("x"));
+        assert_ordered_eq!(profile.accept_keywords(), ["amd64"]);
+        assert_ordered_eq!(profile.use_flags(), ["b", "targets_x"]);
+        assert_eq!(profile.nodes().len(), 2);
+
+        temp.create_ebuild("cat/pkg-1", &["KEYWORDS=~amd64", "IUSE=+a c d e f"])
+            .unwrap();
+        temp.create_ebuild("cat/pkg-2", &["KEYWORDS=amd64", "IUSE=+a c d e f"])
+            .unwrap();
+        temp.create_ebuild("cat/masked-1", &["KEYWORDS=amd64"])
+            .unwrap();
+        temp.create_ebuild("cat/masked-2", &["KEYWORDS=amd64"])
+            .unwrap();
+
+        // unstable package
+        let pkg = repo.get_pkg("cat/pkg-1").unwrap();
+        assert!(!profile.masked(&pkg));
+        assert!(!profile.accepted(&pkg));
+        assert!(!profile.visible(&pkg));
+        assert_ordered_eq!(profile.use_masked(&pkg), ["c"]);
+        assert!(profile.use_forced(&pkg).is_empty());
+        assert_ordered_eq!(profile.use_enabled(&pkg), ["a"]);
+
+        // stable package
+        let pkg = repo.get_pkg("cat/pkg-2").unwrap();
+        assert!(profile.visible(&pkg));
+        assert_ordered_eq!(profile.use_masked(&pkg), ["c", "e"]);
+        assert_ordered_eq!(profile.use_forced(&pkg), ["f"]);
+        assert_ordered_eq!(profile.use_enabled(&pkg), ["a", "f"]);
+
+        // masked packages
+        let pkg = repo.get_pkg("cat/masked-1").unwrap();
+        assert!(profile.masked(&pkg));
+        assert!(profile.accepted(&pkg));
+        assert!(!profile.visible(&pkg));
+        let pkg = repo.get_pkg("cat/masked-2").unwrap();
+        assert!(!profile.masked(&pkg));
+
+        // unstable keywords accept stable and unstable packages
+        fs::write(path.join("amd64/make.defaults"), "ARCH=amd64\nACCEPT_KEYWORDS=\"~amd64\"")
+            .unwrap();
+        let profile = Profile::load(&repo, "amd64").unwrap();
+        for cpv in ["cat/pkg-1", "cat/pkg-2"] {
+            let pkg = repo.get_pkg(cpv).unwrap();
+            assert!(profile.accepted(&pkg));
+        }
+
+        // wildcard keywords
+        for (accept, expected) in
+            [("*", [false, true]), ("~*", [true, false]), ("**", [true, true])]
+        {
+            let data = format!("ARCH=amd64\nACCEPT_KEYWORDS=\"{accept}\"");
+            fs::write(path.join("amd64/make.defaults"), data).unwrap();
+            let profile = Profile::load(&repo, "amd64").unwrap();
+            for (cpv, expected) in ["cat/pkg-1", "cat/pkg-2"].into_iter().zip(expected) {
+                let pkg = repo.get_pkg(cpv).unwrap();
+                assert_eq!(profile.accepted(&pkg), expected, "{accept}: {cpv}");
+            }
+        }
+    }
+}
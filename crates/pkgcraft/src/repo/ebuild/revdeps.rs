@@ -2,21 +2,25 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs::{self, File};
 use std::io::Write;
+use std::str::FromStr;
 
-use camino::Utf8Path;
-use indexmap::IndexSet;
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use ordermap::OrderSet;
 use rayon::prelude::*;
 
 use crate::Error;
-use crate::dep::{ConditionalFlatten, Cpn, Cpv, Dep, UseDep};
+use crate::dep::{ConditionalFlatten, Cpn, Cpv, CpvOrDep, Dep, UseDep};
 use crate::macros::build_path;
 use crate::pkg::Package;
+use crate::pkg::ebuild::EbuildPkg;
 use crate::pkg::ebuild::metadata::Key;
-use crate::traits::LogErrors;
+use crate::repo::PkgRepository;
+use crate::traits::{Intersects, LogErrors};
 
 use super::EbuildRepo;
+use super::index::{self, IndexFile};
 
 /// Reverse dependency entry for the RevDepCache.
 #[derive(Debug, Eq, PartialEq, Hash)]
@@ -94,23 +98,8 @@ impl RevDepCache {
         // TODO: build cache in parallel
         let mut iter = repo.iter_unordered().log_errors(ignore);
         for pkg in &mut iter {
-            for key in pkg.eapi().dep_keys().iter().copied() {
-                for (mut use_deps, dep) in
-                    pkg.dependencies([key]).into_iter_conditional_flatten()
-                {
-                    use_deps.sort();
-                    cache
-                        .0
-                        .entry(dep.cpn.clone())
-                        .or_default()
-                        .entry(RevDep {
-                            cpv: pkg.cpv().clone(),
-                            use_deps: use_deps.into_iter().collect(),
-                            dep: dep.clone(),
-                        })
-                        .or_default()
-                        .insert(key);
-                }
+            for dep in pkg_deps(&pkg) {
+                cache.insert(pkg.cpv(), dep);
             }
         }
 
@@ -121,11 +110,52 @@ impl RevDepCache {
         }
     }
 
+    /// Add a package dependency to the cache.
+    fn insert(&mut self, cpv: &Cpv, value: IndexDep) {
+        let IndexDep { key, use_deps, dep } = value;
+        self.0
+            .entry(dep.cpn.clone())
+            .or_default()
+            .entry(RevDep {
+                cpv: cpv.clone(),
+                use_deps,
+                dep,
+            })
+            .or_default()
+            .insert(key);
+    }
+
     /// Get the reverse dependencies for a Cpn.
     pub fn get(&self, cpn: &Cpn) -> Option<&HashMap<RevDep, HashSet<Key>>> {
         self.0.get(cpn)
     }
 
+    /// Query the reverse dependencies matching a target.
+    ///
+    /// Package targets match dependencies that intersect them while dependency targets
+    /// match by package name, version, and slot with any USE dependencies required to
+    /// be present in matching dependencies.
+    pub fn query<'a>(
+        &'a self,
+        target: &'a CpvOrDep,
+    ) -> impl Iterator<Item = (&'a RevDep, &'a HashSet<Key>)> {
+        self.get(target.cpn())
+            .into_iter()
+            .flatten()
+            .filter(move |(revdep, _)| match target {
+                CpvOrDep::Cpv(cpv) => cpv.intersects(&revdep.dep),
+                CpvOrDep::Dep(dep) => {
+                    dep.use_deps().into_iter().flatten().all(|x| {
+                        revdep
+                            .dep
+                            .use_deps()
+                            .map(|use_deps| use_deps.contains(x))
+                            .unwrap_or_default()
+                    }) && revdep.dep.intersects(&dep.no_use_deps())
+                }
+            })
+    }
+
     /// Serialize the cache to a directory using qa reports format.
     pub fn serialize_to_qa<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<()> {
         let dir = path.as_ref().join("revdeps");
@@ -165,3 +195,295 @@ impl RevDepCache {
         Ok(())
     }
 }
+
+/// Package dependency entry for the RevDepIndex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexDep {
+    key: Key,
+    use_deps: OrderSet<UseDep>,
+    dep: Dep,
+}
+
+impl fmt::Display for IndexDep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\t{}\t{}", self.key, self.use_deps.iter().join(" "), self.dep)
+    }
+}
+
+impl FromStr for IndexDep {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let Some((key, use_deps, dep)) = s.split('\t').collect_tuple() else {
+            return Err(Error::InvalidValue(format!("invalid dependency entry: {s}")));
+        };
+
+        Ok(Self {
+            key: key
+                .parse()
+                .map_err(|_| Error::InvalidValue(format!("invalid key: {key}")))?,
+            use_deps: use_deps
+                .split_whitespace()
+                .map(UseDep::try_new)
+                .try_collect()?,
+            dep: Dep::try_new(dep)?,
+        })
+    }
+}
+
+/// Return all flattened dependencies for a package.
+fn pkg_deps(pkg: &EbuildPkg) -> Vec<IndexDep> {
+    let mut deps = vec![];
+    for key in pkg.eapi().dep_keys().iter().copied() {
+        for (mut use_deps, dep) in pkg.dependencies([key]).into_iter_conditional_flatten() {
+            use_deps.sort();
+            deps.push(IndexDep {
+                key,
+                use_deps: use_deps.into_iter().collect(),
+                dep: dep.clone(),
+            });
+        }
+    }
+    deps
+}
+
+/// Package entry for the RevDepIndex.
+#[derive(Debug, PartialEq, Eq)]
+struct IndexEntry {
+    chksum: String,
+    deps: Vec<IndexDep>,
+}
+
+/// Revdeps index file format.
+const INDEX_FILE: IndexFile = IndexFile::new("revdeps", "pkgcraft-revdeps 1");
+
+/// Persistent, incrementally updated reverse dependencies index for an ebuild repo.
+///
+/// Package entries are invalidated via checksums pulled from the repo's metadata cache,
+/// so updates only reprocess changed packages.
+#[derive(Debug)]
+pub struct RevDepIndex {
+    path: Utf8PathBuf,
+    entries: IndexMap<Cpv, IndexEntry>,
+    cache: RevDepCache,
+}
+
+impl RevDepIndex {
+    /// Return the default index path for a repo within a cache directory.
+    pub fn default_path<P: AsRef<Utf8Path>>(dir: P, repo: &EbuildRepo) -> Utf8PathBuf {
+        INDEX_FILE.default_path(dir.as_ref(), repo)
+    }
+
+    /// Load an index from a file, returning an empty index if it doesn't exist.
+    pub fn load<P: AsRef<Utf8Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut index = Self {
+            path: path.to_path_buf(),
+            entries: Default::default(),
+            cache: Default::default(),
+        };
+
+        let mut entry: Option<(Cpv, IndexEntry)> = None;
+        INDEX_FILE.load(path, |line| {
+            if let Some(s) = line.strip_prefix('\t') {
+                let Some((_, e)) = entry.as_mut() else {
+                    return Err(Error::InvalidValue("dependency without package".to_string()));
+                };
+                e.deps.push(s.parse()?);
+            } else {
+                let Some((cpv, chksum)) = line.split_once(' ') else {
+                    return Err(Error::InvalidValue(format!("invalid package entry: {line}")));
+                };
+                let cpv = Cpv::try_new(cpv)?;
+                let chksum = chksum.to_string();
+                if let Some((cpv, e)) =
+                    entry.replace((cpv, IndexEntry { chksum, deps: vec![] }))
+                {
+                    index.entries.insert(cpv, e);
+                }
+            }
+            Ok(())
+        })?;
+
+        if let Some((cpv, e)) = entry {
+            index.entries.insert(cpv, e);
+        }
+
+        index.rebuild();
+        Ok(index)
+    }
+
+    /// Rebuild the reverse dependencies mapping from the package entries.
+    fn rebuild(&mut self) {
+        let mut cache = RevDepCache::default();
+        for (cpv, entry) in &self.entries {
+            for dep in &entry.deps {
+                cache.insert(cpv, dep.clone());
+            }
+        }
+        self.cache = cache;
+    }
+
+    /// Update the index for an ebuild repo, returning the number of altered entries.
+    ///
+    /// Packages with metadata cache checksums matching their existing entries are skipped
+    /// while all others are loaded and reprocessed.
+    pub fn update(&mut self, repo: &EbuildRepo, ignore: bool) -> crate::Result<usize> {
+        let cpvs: IndexSet<_> = repo.iter_cpv().collect();
+
+        // process new and changed packages in parallel
+        let results: Vec<_> = cpvs
+            .par_iter()
+            .filter_map(|cpv| -> Option<(&Cpv, crate::Result<IndexEntry>)> {
+                let raw = match repo.get_pkg_raw(cpv.clone()) {
+                    Ok(pkg) => pkg,
+                    Err(e) => return Some((cpv, Err(e))),
+                };
+
+                // skip packages with valid cache entries matching the index
+                let cached = index::cached_chksum(repo, &raw);
+                if cached.is_some()
+                    && cached.as_ref() == self.entries.get(cpv).map(|e| &e.chksum)
+                {
+                    return None;
+                }
+
+                let ebuild = raw.chksum().to_string();
+                let pkg = match EbuildPkg::try_from(raw) {
+                    Ok(pkg) => pkg,
+                    Err(e) => return Some((cpv, Err(e))),
+                };
+                let chksum =
+                    index::chksum(&ebuild, pkg.inherited().iter().map(|e| e.chksum()));
+                let deps = pkg_deps(&pkg);
+                Some((cpv, Ok(IndexEntry { chksum, deps })))
+            })
+            .collect();
+
+        let result = index::merge(&mut self.entries, &cpvs, results, ignore);
+        if !matches!(result, Ok(0)) {
+            self.rebuild();
+        }
+        result
+    }
+
+    /// Return true if the index file is missing or older than the repo's metadata cache.
+    pub fn is_stale(&self, repo: &EbuildRepo) -> bool {
+        index::is_stale(&self.path, repo)
+    }
+
+    /// Write the index to its file.
+    pub fn save(&self) -> crate::Result<()> {
+        let lines = self.entries.iter().map(|(cpv, entry)| {
+            let deps = entry.deps.iter().map(|dep| format!("\n\t{dep}")).join("");
+            format!("{cpv} {}{deps}", entry.chksum)
+        });
+        INDEX_FILE.save(&self.path, lines)
+    }
+
+    /// Return the index's file path.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Return the reverse dependencies cache for the index.
+    pub fn cache(&self) -> &RevDepCache {
+        &self.cache
+    }
+
+    /// Return the number of indexed packages.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if the index has no packages, otherwise false.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::config::Config;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    /// Return the sorted dependency strings matching a query target.
+    fn query(index: &RevDepIndex, s: &str) -> Vec<String> {
+        let target = CpvOrDep::try_new(s).unwrap();
+        index
+            .cache()
+            .query(&target)
+            .map(|(revdep, _)| revdep.dep().to_string())
+            .sorted()
+            .collect()
+    }
+
+    #[test]
+    fn index() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+        let dir = tempdir().unwrap();
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("index");
+
+        temp.create_ebuild("a/b-1", &["SLOT=1"]).unwrap();
+        temp.create_ebuild("a/b-2", &["SLOT=2"]).unwrap();
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            DESCRIPTION="ebuild with revdeps"
+            SLOT=0
+            IUSE="u"
+            DEPEND="a/b:1[x]"
+            RDEPEND="u? ( a/b:2 )"
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+
+        // nonexistent index files create empty indexes
+        let mut index = RevDepIndex::load(&path).unwrap();
+        assert!(index.is_empty());
+        assert!(index.is_stale(&repo));
+
+        // initial update processes all packages
+        assert_eq!(index.update(&repo, false).unwrap(), 3);
+        assert_eq!(index.len(), 3);
+        index.save().unwrap();
+        assert!(!index.is_stale(&repo));
+
+        // unchanged packages are skipped
+        let mut index = RevDepIndex::load(&path).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.update(&repo, false).unwrap(), 0);
+
+        // queries
+        assert_eq!(query(&index, "a/b"), ["a/b:1[x]", "a/b:2"]);
+        assert_eq!(query(&index, "a/b:2"), ["a/b:2"]);
+        assert_eq!(query(&index, "a/b[x]"), ["a/b:1[x]"]);
+        assert!(query(&index, "a/b:2[x]").is_empty());
+        assert!(query(&index, "c/d").is_empty());
+
+        // changed and removed packages are reprocessed
+        let data = data.replace(r#"DEPEND="a/b:1[x]""#, r#"DEPEND="c/d""#);
+        temp.create_ebuild_from_str("cat/pkg-1", &data).unwrap();
+        fs::remove_file(repo.path().join("a/b/b-2.ebuild")).unwrap();
+        assert_eq!(index.update(&repo, false).unwrap(), 2);
+        assert_eq!(index.len(), 2);
+        assert_eq!(query(&index, "a/b"), ["a/b:2"]);
+        assert_eq!(query(&index, "c/d"), ["c/d"]);
+
+        // invalid
+        fs::write(&path, "unknown\n").unwrap();
+        let r = RevDepIndex::load(&path);
+        assert_err_re!(r, "line 1: unsupported format: unknown$");
+        fs::write(&path, "pkgcraft-revdeps 1\n\tDEPEND\t\tc/d\n").unwrap();
+        let r = RevDepIndex::load(&path);
+        assert_err_re!(r, "line 2: dependency without package$");
+    }
+}
@@ -0,0 +1,532 @@
+use std::cmp::Reverse;
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use rayon::prelude::*;
+use strum::{AsRefStr, Display, EnumIter, EnumString, IntoEnumIterator};
+
+use crate::Error;
+use crate::dep::Cpn;
+use crate::macros::build_path;
+use crate::pkg::ebuild::EbuildPkg;
+use crate::repo::PkgRepository;
+use crate::utils::digest;
+
+use super::EbuildRepo;
+use super::index::{self, IndexFile};
+
+/// Search index file format.
+const INDEX_FILE: IndexFile = IndexFile::new("search", "pkgcraft-search 1");
+
+/// Separator for list values within index fields.
+const LIST_SEPARATOR: char = '\x1f';
+
+/// Package fields supported by the search index.
+#[derive(AsRefStr, Display, EnumIter, EnumString, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SearchField {
+    /// Package name.
+    Name,
+    /// Package description from the DESCRIPTION variable.
+    Description,
+    /// Long description from metadata.xml.
+    LongDescription,
+    /// Maintainer names and emails from metadata.xml.
+    Maintainer,
+    /// Upstream remote-ids from metadata.xml.
+    RemoteId,
+    /// Local USE flag names and descriptions from metadata.xml.
+    Use,
+}
+
+impl SearchField {
+    /// Return the ranking weight for matches in the field.
+    fn weight(&self) -> u32 {
+        match self {
+            Self::Name => 20,
+            Self::Description => 8,
+            Self::Maintainer => 6,
+            Self::RemoteId => 6,
+            Self::LongDescription => 3,
+            Self::Use => 1,
+        }
+    }
+}
+
+/// Package entry in the search index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchEntry {
+    cpn: Cpn,
+    chksum: String,
+    description: String,
+    long_description: String,
+    maintainers: Vec<String>,
+    remote_ids: Vec<String>,
+    use_flags: Vec<String>,
+}
+
+/// Normalize a value for storage, collapsing all whitespace.
+fn normalize(value: &str) -> String {
+    value.split_whitespace().join(" ")
+}
+
+impl SearchEntry {
+    /// Create a search entry for a package.
+    fn new(pkg: &EbuildPkg, chksum: String) -> Self {
+        let meta = pkg.metadata();
+        let maintainers = meta
+            .maintainers()
+            .iter()
+            .map(|m| match m.name() {
+                Some(name) => normalize(&format!("{name} <{}>", m.email())),
+                None => normalize(m.email()),
+            })
+            .collect();
+        let remote_ids = meta
+            .upstream()
+            .into_iter()
+            .flat_map(|u| u.remote_ids())
+            .map(|r| normalize(&format!("{}:{}", r.site(), r.name())))
+            .collect();
+        let use_flags = meta
+            .local_use()
+            .iter()
+            .map(|(flag, desc)| normalize(&format!("{flag}: {desc}")))
+            .collect();
+
+        Self {
+            cpn: pkg.cpn().clone(),
+            chksum,
+            description: normalize(pkg.description()),
+            long_description: normalize(meta.description().unwrap_or_default()),
+            maintainers,
+            remote_ids,
+            use_flags,
+        }
+    }
+
+    /// Return the package name.
+    pub fn cpn(&self) -> &Cpn {
+        &self.cpn
+    }
+
+    /// Return the package description.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Return the package long description.
+    pub fn long_description(&self) -> &str {
+        &self.long_description
+    }
+
+    /// Return the package maintainers.
+    pub fn maintainers(&self) -> &[String] {
+        &self.maintainers
+    }
+
+    /// Return the package upstream remote-ids.
+    pub fn remote_ids(&self) -> &[String] {
+        &self.remote_ids
+    }
+
+    /// Return the package local USE flag descriptions.
+    pub fn use_flags(&self) -> &[String] {
+        &self.use_flags
+    }
+
+    /// Return the lowercased values for a field.
+    fn values(&self, field: SearchField) -> Vec<String> {
+        match field {
+            SearchField::Name => vec![self.cpn.to_string()],
+            SearchField::Description => vec![self.description.to_lowercase()],
+            SearchField::LongDescription => vec![self.long_description.to_lowercase()],
+            SearchField::Maintainer => {
+                self.maintainers.iter().map(|s| s.to_lowercase()).collect()
+            }
+            SearchField::RemoteId => {
+                self.remote_ids.iter().map(|s| s.to_lowercase()).collect()
+            }
+            SearchField::Use => self.use_flags.iter().map(|s| s.to_lowercase()).collect(),
+        }
+    }
+
+    /// Return the match score for a lowercased term in a field.
+    fn score(&self, field: SearchField, term: &str) -> u32 {
+        let weight = field.weight();
+        if field == SearchField::Name {
+            let name = self.cpn.package().to_lowercase();
+            return if name == term || self.cpn.to_string() == term {
+                weight * 5
+            } else if name.starts_with(term) {
+                weight * 2
+            } else if name.contains(term) {
+                weight
+            } else {
+                0
+            };
+        }
+
+        self.values(field)
+            .iter()
+            .map(|value| {
+                if value
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|w| w == term)
+                {
+                    weight * 2
+                } else if value.contains(term) {
+                    weight
+                } else {
+                    0
+                }
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for SearchEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sep = LIST_SEPARATOR.to_string();
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.cpn,
+            self.chksum,
+            self.description,
+            self.long_description,
+            self.maintainers.join(&sep),
+            self.remote_ids.join(&sep),
+            self.use_flags.join(&sep),
+        )
+    }
+}
+
+impl FromStr for SearchEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let fields: Vec<_> = s.split('\t').collect();
+        let [cpn, chksum, desc, long_desc, maintainers, remote_ids, use_flags] = fields[..]
+        else {
+            return Err(Error::InvalidValue(format!(
+                "invalid number of fields: {}",
+                fields.len()
+            )));
+        };
+
+        let list = |s: &str| -> Vec<String> {
+            s.split(LIST_SEPARATOR)
+                .filter(|x| !x.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        Ok(Self {
+            cpn: Cpn::try_new(cpn)?,
+            chksum: chksum.to_string(),
+            description: desc.to_string(),
+            long_description: long_desc.to_string(),
+            maintainers: list(maintainers),
+            remote_ids: list(remote_ids),
+            use_flags: list(use_flags),
+        })
+    }
+}
+
+/// Ranked search result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch<'a> {
+    entry: &'a SearchEntry,
+    score: u32,
+}
+
+impl<'a> SearchMatch<'a> {
+    /// Return the matching index entry.
+    pub fn entry(&self) -> &'a SearchEntry {
+        self.entry
+    }
+
+    /// Return the ranking score where higher values are more relevant.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+}
+
+/// Persistent, incrementally updated full-text package search index for an ebuild repo.
+#[derive(Debug)]
+pub struct SearchIndex {
+    path: Utf8PathBuf,
+    entries: IndexMap<Cpn, SearchEntry>,
+}
+
+impl SearchIndex {
+    /// Return the default index path for a repo within a cache directory.
+    pub fn default_path<P: AsRef<Utf8Path>>(dir: P, repo: &EbuildRepo) -> Utf8PathBuf {
+        INDEX_FILE.default_path(dir.as_ref(), repo)
+    }
+
+    /// Load an index from a file, returning an empty index if it doesn't exist.
+    pub fn load<P: AsRef<Utf8Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut index = Self {
+            path: path.to_path_buf(),
+            entries: Default::default(),
+        };
+
+        INDEX_FILE.load(path, |line| {
+            let entry: SearchEntry = line.parse()?;
+            index.entries.insert(entry.cpn.clone(), entry);
+            Ok(())
+        })?;
+
+        Ok(index)
+    }
+
+    /// Update the index for an ebuild repo, returning the number of altered entries.
+    ///
+    /// Packages are only reprocessed when their ebuilds, inherited eclasses, or
+    /// metadata.xml files change.
+    pub fn update(&mut self, repo: &EbuildRepo, ignore: bool) -> crate::Result<usize> {
+        let cpns: IndexSet<_> = repo.iter_cpn().collect();
+
+        // determine the checksum for a package from its versions and metadata.xml file
+        let chksum = |cpn: &Cpn| -> crate::Result<String> {
+            let mut data: Vec<_> = repo
+                .iter_cpv_restrict(cpn)
+                .map(|cpv| index::pkg_chksum(repo, &cpv))
+                .try_collect()?;
+            let path = build_path!(repo.path(), cpn.category(), cpn.package(), "metadata.xml");
+            match fs::read(&path) {
+                Ok(xml) => data.push(digest::<md5::Md5>(&xml)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(Error::IO(format!("failed reading: {path}: {e}"))),
+            }
+            Ok(digest::<md5::Md5>(data.join(" ").as_bytes()))
+        };
+
+        // process new and changed packages in parallel using their latest versions
+        let results: Vec<_> = cpns
+            .par_iter()
+            .filter_map(|cpn| -> Option<(&Cpn, crate::Result<SearchEntry>)> {
+                let chksum = match chksum(cpn) {
+                    Ok(value) => value,
+                    Err(e) => return Some((cpn, Err(e))),
+                };
+
+                if self.entries.get(cpn).map(|e| &e.chksum) == Some(&chksum) {
+                    return None;
+                }
+
+                let cpv = repo.iter_cpv_restrict(cpn).last()?;
+                let result = repo.get_pkg(cpv).map(|pkg| SearchEntry::new(&pkg, chksum));
+                Some((cpn, result))
+            })
+            .collect();
+
+        index::merge(&mut self.entries, &cpns, results, ignore)
+    }
+
+    /// Return true if the index file is missing or older than the repo's metadata cache.
+    ///
+    /// Only file modification times are compared so metadata.xml changes and package
+    /// changes without regenerated metadata aren't detected.
+    pub fn is_stale(&self, repo: &EbuildRepo) -> bool {
+        index::is_stale(&self.path, repo)
+    }
+
+    /// Write the index to its file.
+    pub fn save(&self) -> crate::Result<()> {
+        INDEX_FILE.save(&self.path, self.entries.values())
+    }
+
+    /// Return the index's file path.
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Return the index entry for a package if it exists.
+    pub fn get(&self, cpn: &Cpn) -> Option<&SearchEntry> {
+        self.entries.get(cpn)
+    }
+
+    /// Return the number of indexed packages.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if the index has no packages, otherwise false.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Search all fields of the index for a query.
+    pub fn search(&self, query: &str) -> Vec<SearchMatch<'_>> {
+        self.search_fields(query, SearchField::iter())
+    }
+
+    /// Search the given fields of the index for a query.
+    ///
+    /// All whitespace-separated, case-insensitive terms must match for a package to be
+    /// returned and results are sorted by descending relevance.
+    pub fn search_fields<I>(&self, query: &str, fields: I) -> Vec<SearchMatch<'_>>
+    where
+        I: IntoIterator<Item = SearchField>,
+    {
+        let fields: IndexSet<_> = fields.into_iter().collect();
+        let terms: Vec<_> = query.split_whitespace().map(|s| s.to_lowercase()).collect();
+        if terms.is_empty() {
+            return vec![];
+        }
+
+        self.entries
+            .par_values()
+            .filter_map(|entry| {
+                let mut score = 0;
+                for term in &terms {
+                    let value = fields.iter().map(|f| entry.score(*f, term)).sum::<u32>();
+                    if value == 0 {
+                        return None;
+                    }
+                    score += value;
+                }
+                Some(SearchMatch { entry, score })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .sorted_by_key(|m| (Reverse(m.score), &m.entry.cpn))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::repo::ebuild::EbuildRepoBuilder;
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    /// Return the matching package names for a query.
+    fn search(index: &SearchIndex, query: &str) -> Vec<String> {
+        index
+            .search(query)
+            .into_iter()
+            .map(|m| m.entry().cpn().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn index() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("index");
+
+        temp.create_ebuild("dev-util/foo-1", &["DESCRIPTION=a build tool"])
+            .unwrap();
+        temp.create_ebuild("dev-util/bar-1", &["DESCRIPTION=foo helper library"])
+            .unwrap();
+        temp.create_ebuild("app-misc/baz-1", &["DESCRIPTION=misc tool"])
+            .unwrap();
+        temp.create_eclass("e1", "E1_VAR=1\n").unwrap();
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            inherit e1
+            DESCRIPTION="eclass inheriting package"
+            SLOT=0
+        "#};
+        temp.create_ebuild_from_str("app-misc/qux-1", data).unwrap();
+        let data = indoc::indoc! {r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <!DOCTYPE pkgmetadata SYSTEM "https://www.gentoo.org/dtd/metadata.dtd">
+            <pkgmetadata>
+                <maintainer type="person">
+                    <email>dev@gentoo.org</email>
+                    <name>A Developer</name>
+                </maintainer>
+                <longdescription>
+                    Detailed information about
+                    the baz package.
+                </longdescription>
+                <use>
+                    <flag name="gui">Enable graphical interface</flag>
+                </use>
+                <upstream>
+                    <remote-id type="github">baz/baz</remote-id>
+                </upstream>
+            </pkgmetadata>
+        "#};
+        fs::write(repo.path().join("app-misc/baz/metadata.xml"), data).unwrap();
+
+        // initial update processes all packages
+        let mut index = SearchIndex::load(&path).unwrap();
+        assert!(index.is_empty());
+        assert!(index.is_stale(&repo));
+        assert_eq!(index.update(&repo, false).unwrap(), 4);
+        index.save().unwrap();
+        assert!(!index.is_stale(&repo));
+
+        // stale indexes are saved even when no packages changed
+        filetime::set_file_mtime(&path, filetime::FileTime::zero()).unwrap();
+        assert!(index.is_stale(&repo));
+        let index = repo.search_index(&path, false).unwrap();
+        assert!(!index.is_stale(&repo));
+
+        // unchanged packages are skipped
+        let mut index = SearchIndex::load(&path).unwrap();
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.update(&repo, false).unwrap(), 0);
+        let entry = index.get(&Cpn::try_new("app-misc/baz").unwrap()).unwrap();
+        assert_eq!(entry.long_description(), "Detailed information about the baz package.");
+        assert_eq!(entry.maintainers(), ["A Developer <dev@gentoo.org>"]);
+        assert_eq!(entry.remote_ids(), ["github:baz/baz"]);
+        assert_eq!(entry.use_flags(), ["gui: Enable graphical interface"]);
+
+        // name matches rank above description matches
+        assert_eq!(search(&index, "foo"), ["dev-util/foo", "dev-util/bar"]);
+        assert_eq!(search(&index, "TOOL"), ["app-misc/baz", "dev-util/foo"]);
+        // all terms must match
+        assert_eq!(search(&index, "tool misc"), ["app-misc/baz"]);
+        assert!(search(&index, "tool nonexistent").is_empty());
+        // metadata.xml fields
+        assert_eq!(search(&index, "dev@gentoo.org"), ["app-misc/baz"]);
+        assert_eq!(search(&index, "github:baz"), ["app-misc/baz"]);
+        assert_eq!(search(&index, "graphical"), ["app-misc/baz"]);
+        assert_eq!(search(&index, "detailed"), ["app-misc/baz"]);
+        // specific fields
+        let matches = index.search_fields("foo", [SearchField::Description]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry().cpn().to_string(), "dev-util/bar");
+
+        // changed and removed packages are reprocessed
+        temp.create_ebuild("dev-util/foo-2", &["DESCRIPTION=a new build tool"])
+            .unwrap();
+        fs::remove_dir_all(repo.path().join("dev-util/bar")).unwrap();
+        assert_eq!(index.update(&repo, false).unwrap(), 2);
+        assert_eq!(search(&index, "new"), ["dev-util/foo"]);
+        assert_eq!(search(&index, "foo"), ["dev-util/foo"]);
+
+        // eclass changes reprocess inheriting packages
+        temp.create_eclass("e1", "E1_VAR=2\n").unwrap();
+        let mut config = Config::default();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+        assert_eq!(index.update(&repo, false).unwrap(), 1);
+        assert_eq!(index.update(&repo, false).unwrap(), 0);
+
+        // invalid
+        fs::write(&path, "unknown\n").unwrap();
+        let r = SearchIndex::load(&path);
+        assert_err_re!(r, "line 1: unsupported format: unknown$");
+        fs::write(&path, "pkgcraft-search 1\ncat/pkg\n").unwrap();
+        let r = SearchIndex::load(&path);
+        assert_err_re!(r, "line 2: invalid number of fields: 1$");
+    }
+}
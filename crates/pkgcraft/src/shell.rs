@@ -84,6 +84,8 @@ pub(crate) struct BuildData {
     // TODO: proxy these fields via borrowed package reference
     distfiles: IndexSet<String>,
     user_patches: IndexSet<String>,
+    /// fetch-restricted URIs requiring manual download
+    nofetch: IndexSet<String>,
     use_: HashSet<String>,
 
     scope: Scope,
@@ -5,7 +5,7 @@ use scallop::{Error, ExecStatus, functions};
 use tempfile::NamedTempFile;
 
 use crate::pkg::ebuild::{EbuildPkg, EbuildRawPkg};
-use crate::pkg::{Build, Package, PkgPretend, Source};
+use crate::pkg::{Build, Package, PkgNofetch, PkgPretend, Source};
 use crate::shell::phase::PhaseKind;
 use crate::shell::scope::Scope;
 use crate::shell::{BuildData, get_build_mut};
@@ -77,6 +77,38 @@ impl PkgPretend for EbuildPkg {
     }
 }
 
+impl PkgNofetch for EbuildPkg {
+    fn pkg_nofetch(&self, uris: &[String]) -> scallop::Result<Option<String>> {
+        let Some(phase) = self.eapi().phases().get(&PhaseKind::PkgNofetch) else {
+            // ignore packages with EAPIs lacking pkg_nofetch() support
+            return Ok(None);
+        };
+
+        self.source()?;
+
+        // set the URIs output by the default phase
+        let build = get_build_mut();
+        build.nofetch = uris.iter().cloned().collect();
+
+        // redirect pkg_nofetch() output to a temporary file
+        let file = NamedTempFile::new()?;
+        redirect_output(&file)?;
+
+        // execute phase capturing output
+        let result = phase.run();
+        let output = fs::read_to_string(file.path()).unwrap_or_default();
+        let output = output.trim();
+
+        if let Err(e) = result {
+            Err(Error::Base(format!("{self}: {e}")))
+        } else if !output.is_empty() {
+            Ok(Some(output.to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl Source for EbuildRawPkg {
     fn source(&self) -> scallop::Result<ExecStatus> {
         BuildData::from_raw_pkg(self);
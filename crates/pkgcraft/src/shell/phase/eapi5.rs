@@ -11,13 +11,12 @@ use crate::shell::utils::{configure, makefile_exists};
 use super::emake_install;
 
 pub(crate) fn pkg_nofetch(build: &mut BuildData) -> scallop::Result<ExecStatus> {
-    // TODO: only output URLs for missing distfiles
-    if !build.distfiles.is_empty() {
+    if !build.nofetch.is_empty() {
         let pkg = build.pkg();
         let mut stderr = stderr();
         writeln!(stderr, "The following files must be manually downloaded for {pkg}:")?;
-        for url in &build.distfiles {
-            writeln!(stderr, "{url}")?;
+        for uri in &build.nofetch {
+            writeln!(stderr, "{uri}")?;
         }
     }
 
@@ -70,10 +69,44 @@ mod tests {
 
     use crate::config::Config;
     use crate::eapi;
+    use crate::io::stderr;
     use crate::pkg::Build;
     use crate::repo::ebuild::EbuildRepoBuilder;
-    use crate::shell::BuildData;
     use crate::shell::test::FileTree;
+    use crate::shell::{BuildData, get_build_mut};
+
+    #[test]
+    fn pkg_nofetch() {
+        let mut config = Config::default();
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let repo = config.add_repo(&temp).unwrap().into_ebuild().unwrap();
+        config.finalize().unwrap();
+
+        let data = indoc::indoc! {r#"
+            EAPI=8
+            DESCRIPTION="pkg_nofetch outputting missing URIs"
+            SRC_URI="https://a.com/a.tar.gz https://a.com/b.tar.gz"
+            SLOT=0
+            RESTRICT="fetch"
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+        let pkg = repo.get_pkg("cat/pkg-1").unwrap();
+        BuildData::from_pkg(&pkg);
+        let build = get_build_mut();
+
+        // no missing files
+        super::pkg_nofetch(build).unwrap();
+        assert_eq!(stderr().get(), "");
+
+        // only missing files are output
+        build.nofetch = ["https://a.com/b.tar.gz".to_string()].into_iter().collect();
+        super::pkg_nofetch(build).unwrap();
+        let expected = indoc::formatdoc! {"
+            The following files must be manually downloaded for {pkg}:
+            https://a.com/b.tar.gz
+        "};
+        assert_eq!(stderr().get(), expected);
+    }
 
     #[test]
     fn src_install() {
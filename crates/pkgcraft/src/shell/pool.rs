@@ -19,7 +19,7 @@ use crate::config::ConfigRepos;
 use crate::dep::Cpv;
 use crate::error::Error;
 use crate::pkg::ebuild::metadata::Metadata;
-use crate::pkg::{Package, PkgPretend, Source};
+use crate::pkg::{Package, PkgNofetch, PkgPretend, Source};
 use crate::repo::EbuildRepo;
 use crate::repo::Repository;
 use crate::repo::ebuild::cache::{Cache, CacheEntry, MetadataCache};
@@ -200,6 +200,29 @@ impl PretendTask {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct NofetchTask {
+    repo: String,
+    cpv: Cpv,
+    uris: Vec<String>,
+}
+
+impl NofetchTask {
+    fn new<T: Into<Cpv>>(repo: &EbuildRepo, cpv: T, uris: Vec<String>) -> Self {
+        Self {
+            repo: repo.id().to_string(),
+            cpv: cpv.into(),
+            uris,
+        }
+    }
+
+    fn run(self, config: &ConfigRepos) -> crate::Result<Option<String>> {
+        let repo = get_ebuild_repo(config, &self.repo)?;
+        let pkg = repo.get_pkg(self.cpv)?;
+        Ok(pkg.pkg_nofetch(&self.uris)?)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct EnvTask {
     repo: String,
@@ -266,6 +289,7 @@ enum Task {
     Env(EnvTask, Sender<crate::Result<IndexMap<String, String>>>),
     Metadata(MetadataTask, Sender<crate::Result<Option<String>>>),
     Pretend(PretendTask, Sender<crate::Result<Option<String>>>),
+    Nofetch(NofetchTask, Sender<crate::Result<Option<String>>>),
     Duration(DurationTask, Sender<crate::Result<Duration>>),
 }
 
@@ -317,6 +341,12 @@ impl IntoTask for PretendTask {
         Task::Pretend(self, Self::sender(name))
     }
 }
+impl IntoTask for NofetchTask {
+    type R = Option<String>;
+    fn into_task(self, name: String) -> Task {
+        Task::Nofetch(self, Self::sender(name))
+    }
+}
 impl IntoTask for DurationTask {
     type R = Duration;
     fn into_task(self, name: String) -> Task {
@@ -331,6 +361,7 @@ impl Task {
             Self::Env(task, tx) => tx.send(task.run(config)),
             Self::Metadata(task, tx) => tx.send(task.run(config)),
             Self::Pretend(task, tx) => tx.send(task.run(config)),
+            Self::Nofetch(task, tx) => tx.send(task.run(config)),
             Self::Duration(task, tx) => tx.send(task.run(config)),
         }
     }
@@ -458,6 +489,17 @@ impl BuildPool {
         Command::run_task(&self.tx, task)
     }
 
+    /// Run the pkg_nofetch phase for an ebuild package's missing fetch-restricted URIs.
+    pub fn nofetch<T: Into<Cpv>>(
+        &self,
+        repo: &EbuildRepo,
+        cpv: T,
+        uris: Vec<String>,
+    ) -> crate::Result<Option<String>> {
+        let task = NofetchTask::new(repo, cpv, uris);
+        Command::run_task(&self.tx, task)
+    }
+
     /// Return the mapping of global environment variables exported by a package.
     pub fn env<T: Into<Cpv>>(
         &self,
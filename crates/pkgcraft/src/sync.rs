@@ -3,6 +3,7 @@ use std::fs;
 use std::str::FromStr;
 
 use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use tracing::debug;
 
@@ -29,10 +30,34 @@ impl fmt::Display for Syncer {
     }
 }
 
+/// Resolved revision of a synced repo.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum Revision {
+    Git { commit: String },
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Git { commit } => write!(f, "{commit}"),
+        }
+    }
+}
+
 trait Syncable: fmt::Display + fmt::Debug + Sized {
     fn uri_to_syncer(uri: &str) -> crate::Result<Self>;
     fn fallback_name(&self) -> Option<String>;
-    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()>;
+    /// Sync the repo to a path, returning true if its content was altered.
+    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<bool>;
+    /// Sync the repo to a locked revision, returning true if its content was altered.
+    async fn sync_revision<P: AsRef<Utf8Path> + Send>(
+        &self,
+        path: P,
+        revision: &Revision,
+    ) -> crate::Result<bool>;
+    /// Return the resolved revision for the repo at a path, if supported.
+    fn revision<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<Option<Revision>>;
     // TODO decide if we want it async as well.
     fn remove<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()>;
 }
@@ -46,7 +71,8 @@ impl Syncer {
         }
     }
 
-    pub(crate) async fn sync<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<()> {
+    /// Sync the repo to a path, returning true if its content was altered.
+    pub(crate) async fn sync<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<bool> {
         let path = path.as_ref();
 
         // make sure repos dir exists
@@ -60,6 +86,46 @@ impl Syncer {
             Syncer::Local(repo) => repo.sync(path).await,
         }
     }
+
+    /// Sync the repo to a locked revision, returning true if its content was altered.
+    pub(crate) async fn sync_revision<P: AsRef<Utf8Path>>(
+        &self,
+        path: P,
+        revision: &Revision,
+    ) -> crate::Result<bool> {
+        let path = path.as_ref();
+
+        // make sure repos dir exists
+        let dir = path.parent().expect("invalid repos dir");
+        fs::create_dir_all(dir)
+            .map_err(|e| Error::RepoSync(format!("failed creating repos dir: {dir}: {e}")))?;
+
+        match self {
+            Syncer::Git(repo) => repo.sync_revision(path, revision).await,
+            Syncer::TarHttps(repo) => repo.sync_revision(path, revision).await,
+            Syncer::Local(repo) => repo.sync_revision(path, revision).await,
+        }
+    }
+
+    /// Return the resolved revision for the repo at a path, if supported.
+    pub(crate) fn revision<P: AsRef<Utf8Path>>(
+        &self,
+        path: P,
+    ) -> crate::Result<Option<Revision>> {
+        match self {
+            Syncer::Git(repo) => repo.revision(path),
+            Syncer::TarHttps(repo) => repo.revision(path),
+            Syncer::Local(repo) => repo.revision(path),
+        }
+    }
+
+    /// Return true if the syncer supports locked revisions.
+    ///
+    /// Tarball repos are unsupported since their URLs only provide the latest snapshot.
+    pub(crate) fn lockable(&self) -> bool {
+        matches!(self, Syncer::Git(_))
+    }
+
     pub(crate) fn remove<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
         match self {
             Syncer::Git(repo) => repo.remove(path),
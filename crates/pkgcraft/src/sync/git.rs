@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::Error;
 use crate::repo::RepoFormat;
-use crate::sync::Syncable;
+use crate::sync::{Revision, Syncable};
 
 static HANDLED_URI_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^(https?|git)://(?P<path>.+)(\.git|)$").unwrap());
@@ -45,13 +45,13 @@ impl Syncable for Repo {
         })
     }
 
-    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
+    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<bool> {
         let path = path.as_ref();
         let uri = self.uri.as_str();
         let url = gix::url::parse(uri.into())
             .map_err(|e| Error::RepoSync(format!("invalid repo URL: {uri}: {e}")))?;
 
-        match gix::open(path) {
+        let changed = match gix::open(path) {
             Ok(repo) => {
                 let mut remote = repo
                     .find_default_remote(gix::remote::Direction::Fetch)
@@ -76,11 +76,13 @@ impl Syncable for Repo {
                     })?;
 
                 // TODO: support shallow repos
-                prepare_fetch
+                let outcome = prepare_fetch
                     .receive(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
                     .map_err(|e| {
                         Error::RepoSync(format!("failed fetching git repo: {uri}: {e}"))
                     })?;
+
+                matches!(outcome.status, gix::remote::fetch::Status::Change { .. })
             }
             Err(_) => {
                 let mut prepare_fetch = gix::prepare_clone(url, path).map_err(|e| {
@@ -99,10 +101,45 @@ impl Syncable for Repo {
                     .map_err(|e| {
                         Error::RepoSync(format!("failed checking out git repo: {uri}: {e}"))
                     })?;
+                true
             }
+        };
+
+        Ok(changed)
+    }
+
+    async fn sync_revision<P: AsRef<Utf8Path> + Send>(
+        &self,
+        path: P,
+        revision: &Revision,
+    ) -> crate::Result<bool> {
+        let path = path.as_ref();
+        let Revision::Git { commit } = revision;
+
+        let id = gix::ObjectId::from_hex(commit.as_bytes())
+            .map_err(|e| Error::RepoSync(format!("invalid git commit: {commit}: {e}")))?;
+
+        // fetch the latest remote changes so the locked commit is available
+        self.sync(path).await?;
+
+        let repo = gix::open(path)
+            .map_err(|e| Error::RepoSync(format!("invalid git repo: {path}: {e}")))?;
+
+        // skip checkout if the locked commit is already used
+        match repo.head_id() {
+            Ok(head) if head.detach() == id => Ok(false),
+            _ => checkout(&repo, id).map(|_| true),
         }
+    }
 
-        Ok(())
+    fn revision<P: AsRef<Utf8Path>>(&self, path: P) -> crate::Result<Option<Revision>> {
+        let path = path.as_ref();
+        let repo = gix::open(path)
+            .map_err(|e| Error::RepoSync(format!("invalid git repo: {path}: {e}")))?;
+        let id = repo
+            .head_id()
+            .map_err(|e| Error::RepoSync(format!("invalid git HEAD: {path}: {e}")))?;
+        Ok(Some(Revision::Git { commit: id.to_string() }))
     }
 
     fn remove<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
@@ -110,3 +147,162 @@ impl Syncable for Repo {
         Ok(())
     }
 }
+
+/// Check out a commit into a git repo's worktree, detaching HEAD.
+fn checkout(repo: &gix::Repository, id: gix::ObjectId) -> crate::Result<()> {
+    use gix::refs::transaction::{Change, LogChange, PreviousValue, RefEdit};
+
+    let path = repo.path();
+    let err = |e: &dyn std::fmt::Display| {
+        Error::RepoSync(format!("failed checking out commit: {path:?}: {id}: {e}"))
+    };
+
+    let workdir = repo.workdir().ok_or_else(|| err(&"bare repo"))?;
+    let commit = repo.find_commit(id).map_err(|e| err(&e))?;
+    let tree = commit.tree_id().map_err(|e| err(&e))?;
+    let old_index = repo.index_or_empty().map_err(|e| err(&e))?;
+    let mut index = repo.index_from_tree(&tree).map_err(|e| err(&e))?;
+    let mut opts = repo
+        .checkout_options(gix::worktree::stack::state::attributes::Source::IdMapping)
+        .map_err(|e| err(&e))?;
+    opts.overwrite_existing = true;
+
+    let objects = repo.objects.clone().into_arc().map_err(|e| err(&e))?;
+    gix::worktree::state::checkout(
+        &mut index,
+        workdir,
+        objects,
+        &gix::progress::Discard,
+        &gix::progress::Discard,
+        &gix::interrupt::IS_INTERRUPTED,
+        opts,
+    )
+    .map_err(|e| err(&e))?;
+
+    // remove files tracked by the previous index that are missing from the target tree
+    for entry in old_index.entries() {
+        let rel_path = entry.path(&old_index);
+        if index.entry_by_path(rel_path).is_none() {
+            let path = workdir.join(gix::path::from_bstr(rel_path));
+            match fs::remove_file(&path) {
+                Ok(_) => {
+                    // prune emptied parent directories
+                    for dir in path.ancestors().skip(1) {
+                        if dir == workdir || fs::remove_dir(dir).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+                Err(e) => return Err(err(&e)),
+            }
+        }
+    }
+
+    index.write(Default::default()).map_err(|e| err(&e))?;
+
+    // point HEAD at the commit
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                message: format!("pkgcraft: checkout {id}").into(),
+                ..Default::default()
+            },
+            expected: PreviousValue::Any,
+            new: gix::refs::Target::Object(id),
+        },
+        name: "HEAD".try_into().expect("invalid ref name"),
+        deref: false,
+    })
+    .map_err(|e| err(&e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use futures::executor::block_on;
+    use tempfile::tempdir;
+
+    use crate::test::assert_err_re;
+
+    use super::*;
+
+    /// Run a git command in a repo, returning its trimmed output.
+    fn git(path: &Utf8Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-C", path.as_str()])
+            .args(["-c", "user.name=pkgcraft", "-c", "user.email=pkgcraft@pkgcraft.test"])
+            .args(args)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "git {args:?} failed: {stderr}");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn sync_revision() {
+        let tmpdir = tempdir().unwrap();
+        let dir = Utf8Path::from_path(tmpdir.path()).unwrap();
+        let upstream = dir.join("upstream");
+        let path = dir.join("repos/repo");
+        fs::create_dir_all(&upstream).unwrap();
+        fs::create_dir_all(dir.join("repos")).unwrap();
+        git(&upstream, &["init", "-q"]);
+        let commit = |value: &str| {
+            fs::write(upstream.join("file"), value).unwrap();
+            git(&upstream, &["add", "file"]);
+            git(&upstream, &["commit", "-q", "-m", value]);
+            Revision::Git {
+                commit: git(&upstream, &["rev-parse", "HEAD"]),
+            }
+        };
+        let first = commit("1");
+        let repo = Repo {
+            uri: format!("file://{upstream}"),
+        };
+
+        // lock the initial clone
+        assert!(block_on(repo.sync(&path)).unwrap());
+        let locked = repo.revision(&path).unwrap().unwrap();
+        assert_eq!(locked, first);
+
+        // sync to a newer commit
+        let second = commit("2");
+        assert!(block_on(repo.sync_revision(&path, &second)).unwrap());
+        assert_eq!(repo.revision(&path).unwrap().unwrap(), second);
+        assert_eq!(fs::read_to_string(path.join("file")).unwrap(), "2");
+
+        // resync to the locked commit
+        assert!(block_on(repo.sync_revision(&path, &locked)).unwrap());
+        assert_eq!(repo.revision(&path).unwrap().unwrap(), locked);
+        assert_eq!(fs::read_to_string(path.join("file")).unwrap(), "1");
+
+        // already using the locked commit
+        assert!(!block_on(repo.sync_revision(&path, &locked)).unwrap());
+
+        // files added after the locked commit are removed
+        fs::create_dir_all(upstream.join("dir")).unwrap();
+        fs::write(upstream.join("dir/added"), "added").unwrap();
+        git(&upstream, &["add", "dir/added"]);
+        git(&upstream, &["commit", "-q", "-m", "added"]);
+        let added = Revision::Git {
+            commit: git(&upstream, &["rev-parse", "HEAD"]),
+        };
+        assert!(block_on(repo.sync_revision(&path, &added)).unwrap());
+        assert!(path.join("dir/added").exists());
+        assert!(block_on(repo.sync_revision(&path, &locked)).unwrap());
+        assert_eq!(repo.revision(&path).unwrap().unwrap(), locked);
+        assert!(!path.join("dir/added").exists());
+        assert!(!path.join("dir").exists());
+        assert_eq!(fs::read_to_string(path.join("file")).unwrap(), "1");
+
+        // invalid commit
+        let revision = Revision::Git { commit: "abcdef".to_string() };
+        let r = block_on(repo.sync_revision(&path, &revision));
+        assert_err_re!(r, "invalid git commit: abcdef");
+    }
+}
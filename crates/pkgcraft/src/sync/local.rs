@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::Error;
 use crate::repo::RepoFormat;
-use crate::sync::Syncable;
+use crate::sync::{Revision, Syncable};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub(crate) struct Repo {
@@ -44,15 +44,30 @@ impl Syncable for Repo {
         self.path.file_stem().map(|n| n.to_string())
     }
 
-    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
+    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<bool> {
         let path = path.as_ref();
 
+        // local repos are only altered when initially linked
         if !path.exists() {
             symlink(&self.path, path)
                 .map_err(|e| Error::IO(format!("failed creating symlink: {path}: {e}")))?;
+            Ok(true)
+        } else {
+            Ok(false)
         }
+    }
 
-        Ok(())
+    async fn sync_revision<P: AsRef<Utf8Path> + Send>(
+        &self,
+        path: P,
+        revision: &Revision,
+    ) -> crate::Result<bool> {
+        let path = path.as_ref();
+        Err(Error::RepoSync(format!("unsupported locked revision: {path}: {revision}")))
+    }
+
+    fn revision<P: AsRef<Utf8Path>>(&self, _path: P) -> crate::Result<Option<Revision>> {
+        Ok(None)
     }
 
     fn remove<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
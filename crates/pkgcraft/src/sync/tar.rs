@@ -14,7 +14,7 @@ use tempfile::Builder;
 
 use crate::Error;
 use crate::repo::RepoFormat;
-use crate::sync::Syncable;
+use crate::sync::{Revision, Syncable};
 
 static HANDLED_URI_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^tar\+(?P<url>https://(?P<path>.+))$").unwrap());
@@ -52,7 +52,7 @@ impl Syncable for Repo {
         self.path.file_stem().map(|n| n.to_string())
     }
 
-    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
+    async fn sync<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<bool> {
         let path = path.as_ref();
         let repos_dir = path.parent().unwrap();
         let repo_name = path.file_name().unwrap();
@@ -78,7 +78,7 @@ impl Syncable for Repo {
 
         // content is unchanged
         if resp.status().as_u16() == 304 {
-            return Ok(());
+            return Ok(false);
         }
 
         // Clone headers used to later extract ETAG data since streaming the response body consumes
@@ -148,7 +148,21 @@ impl Syncable for Repo {
             })?;
         }
 
-        Ok(())
+        Ok(true)
+    }
+
+    // Tarball URLs are unversioned so previous snapshots can't be restored.
+    async fn sync_revision<P: AsRef<Utf8Path> + Send>(
+        &self,
+        path: P,
+        revision: &Revision,
+    ) -> crate::Result<bool> {
+        let path = path.as_ref();
+        Err(Error::RepoSync(format!("unsupported locked revision: {path}: {revision}")))
+    }
+
+    fn revision<P: AsRef<Utf8Path>>(&self, _path: P) -> crate::Result<Option<Revision>> {
+        Ok(None)
     }
 
     fn remove<P: AsRef<Utf8Path> + Send>(&self, path: P) -> crate::Result<()> {
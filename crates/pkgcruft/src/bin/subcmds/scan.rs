@@ -7,7 +7,7 @@ use pkgcraft::cli::{MaybeStdinVec, Targets};
 use pkgcraft::config::Config;
 use pkgcraft::repo::RepoFormat;
 use pkgcruft::report::ReportSet;
-use pkgcruft::scan::Scanner;
+use pkgcruft::scan::{STABLE_TIME, Scanner};
 use pkgcruft::source::PkgFilter;
 
 use crate::options;
@@ -31,6 +31,10 @@ pub(crate) struct Command {
     #[arg(short, long)]
     sort: bool,
 
+    /// Days before unstable versions are considered for stabilization
+    #[arg(long, value_name = "DAYS", default_value_t = STABLE_TIME)]
+    stable_time: u64,
+
     /// Exit status triggers
     #[arg(
         long,
@@ -81,6 +85,8 @@ impl Command {
             .filters(self.filters.iter().cloned())
             .force(self.force)
             .sort(self.sort)
+            .stable_time(self.stable_time)
+            .cache_dir(&config.path().cache)
             .exit(self.exit.iter().copied());
 
         // determine reporter
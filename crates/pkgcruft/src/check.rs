@@ -6,6 +6,7 @@ use camino::Utf8Path;
 use indexmap::IndexSet;
 use pkgcraft::dep::{Cpn, Cpv};
 use pkgcraft::pkg::ebuild::{EbuildPkg, EbuildRawPkg};
+use pkgcraft::repo::ebuild::Eclass;
 use pkgcraft::repo::{EbuildRepo, Repository};
 use pkgcraft::restrict::Scope;
 use pkgcraft::types::{OrderedMap, OrderedSet};
@@ -13,20 +14,24 @@ use strum::{AsRefStr, Display, EnumIter, EnumString, IntoEnumIterator};
 
 use crate::report::ReportKind;
 use crate::scan::ScannerRun;
-use crate::source::SourceKind;
+use crate::source::{ProfileNode, SourceKind};
 
+mod bash;
 mod commands;
 mod dependency;
 mod dependency_slot_missing;
+mod dependency_use;
 mod duplicates;
 mod eapi_stale;
 mod eapi_status;
 mod ebuild_name;
 mod eclass;
+mod eclasses;
 mod filesdir;
 mod header;
 mod homepage;
 mod ignore;
+mod inherit;
 mod iuse;
 mod keywords;
 mod keywords_dropped;
@@ -34,6 +39,8 @@ mod license;
 mod live;
 mod manifest;
 mod metadata;
+mod metadata_xml;
+mod profiles;
 mod properties;
 mod python_update;
 mod repo_layout;
@@ -41,10 +48,14 @@ mod restrict;
 mod restrict_test_missing;
 mod ruby_update;
 mod src_uri;
+mod stable_request;
 mod unstable_only;
+mod unstable_stale;
 mod use_local;
 mod variable_order;
 mod variables;
+mod version_redundant;
+mod visibility;
 mod whitespace;
 
 /// Check variants.
@@ -63,18 +74,22 @@ mod whitespace;
     Clone,
 )]
 pub enum Check {
+    Bash,
     Commands,
     Dependency,
     DependencySlotMissing,
+    DependencyUse,
     Duplicates,
     EapiStale,
     EapiStatus,
     EbuildName,
     Eclass,
+    Eclasses,
     Filesdir,
     Header,
     Homepage,
     Ignore,
+    Inherit,
     Iuse,
     Keywords,
     KeywordsDropped,
@@ -82,6 +97,8 @@ pub enum Check {
     Live,
     Manifest,
     Metadata,
+    MetadataXml,
+    Profiles,
     Properties,
     PythonUpdate,
     RepoLayout,
@@ -89,10 +106,14 @@ pub enum Check {
     RestrictTestMissing,
     RubyUpdate,
     SrcUri,
+    StableRequest,
     UnstableOnly,
+    UnstableStale,
     UseLocal,
     VariableOrder,
     Variables,
+    VersionRedundant,
+    Visibility,
     Whitespace,
 }
 
@@ -106,6 +127,9 @@ impl Check {
     pub fn reports(&self) -> &'static [ReportKind] {
         use ReportKind::*;
         match self {
+            Self::Bash => {
+                &[CdUnchecked, CommandSubstitutionGlobal, PathUnquoted, PipelineUnchecked]
+            }
             Self::Commands => {
                 &[Builtin, CommandDieUnneeded, CommandScopeInvalid, Optfeature, PhaseCall]
             }
@@ -116,15 +140,28 @@ impl Check {
                 PackageDeprecatedUnused,
             ],
             Self::DependencySlotMissing => &[DependencySlotMissing],
+            Self::DependencyUse => &[DependencyUseMissing],
             Self::Duplicates => &[PackageOverride],
             Self::EapiStale => &[EapiStale],
             Self::EapiStatus => &[EapiBanned, EapiDeprecated, EapiUnused],
             Self::Filesdir => &[FileUnknown, FilesUnused],
             Self::EbuildName => &[EbuildNameInvalid, EbuildVersionsEqual],
             Self::Eclass => &[EclassUnused],
+            Self::Eclasses => &[
+                EclassDocInvalid,
+                EclassDocMissing,
+                EclassEapisMismatch,
+                EclassFunctionUndocumented,
+                EclassNameMismatch,
+                EclassSyntaxError,
+                EclassVariableUndocumented,
+            ],
             Self::Header => &[HeaderInvalid],
             Self::Homepage => &[HomepageInvalid],
             Self::Ignore => &[IgnoreInvalid, IgnoreUnused],
+            Self::Inherit => {
+                &[FunctionDeprecated, FunctionInternal, InheritIndirect, InheritUnused]
+            }
             Self::Iuse => &[IuseInvalid, UseGlobalUnused],
             Self::Keywords => &[
                 EapiUnstable,
@@ -138,19 +175,51 @@ impl Check {
             Self::Live => &[LiveOnly],
             Self::Manifest => &[ManifestInvalid, ManifestConflict, ManifestCollide],
             Self::Metadata => &[MetadataError],
+            Self::MetadataXml => &[
+                MaintainerEmailInvalid,
+                MaintainerMissing,
+                MaintainerTypeUnknown,
+                MetadataXmlInvalid,
+                MetadataXmlMissing,
+                MetadataXmlSlotUnknown,
+                PackageOrphaned,
+                RemoteIdInvalid,
+                RemoteIdUnknown,
+                StabilizeAllarchesInvalid,
+            ],
+            Self::Profiles => &[
+                ProfileDescMissing,
+                ProfileEntryInvalid,
+                ProfileFlagUnknown,
+                ProfilePackageObsolete,
+                ProfilePackageUnknown,
+                ProfileParentInvalid,
+                ProfileUnused,
+            ],
             Self::Properties => &[PropertiesInvalid],
             Self::PythonUpdate => &[PythonUpdate],
             Self::RepoLayout => &[RepoCategoryEmpty, RepoCategoriesUnused, RepoPackageEmpty],
             Self::Restrict => &[RestrictInvalid],
             Self::RestrictTestMissing => &[RestrictMissing],
             Self::RubyUpdate => &[RubyUpdate],
-            Self::SrcUri => &[MirrorsUnused, UriInvalid],
+            Self::SrcUri => &[
+                DistfileGeneric,
+                MirrorMissing,
+                MirrorUnknown,
+                MirrorsUnused,
+                RemoteIdMissing,
+                UriInvalid,
+            ],
+            Self::StableRequest => &[StableRequest],
             Self::UnstableOnly => &[UnstableOnly],
+            Self::UnstableStale => &[UnstableStale],
             Self::UseLocal => {
                 &[UseLocalDescMissing, UseLocalGlobal, UseLocalUnused, UseLocalUnsorted]
             }
             Self::VariableOrder => &[VariableOrder],
             Self::Variables => &[VariableScopeInvalid],
+            Self::VersionRedundant => &[VersionRedundant],
+            Self::Visibility => &[DependencyUnresolvable, DependencyUnresolvableDev],
             Self::Whitespace => &[EapiFormat, WhitespaceInvalid, WhitespaceUnneeded],
         }
     }
@@ -158,18 +227,22 @@ impl Check {
     /// The minimum scope the check can run in.
     pub(crate) fn scope(&self) -> Scope {
         match self {
+            Self::Bash => Scope::Version,
             Self::Commands => Scope::Version,
             Self::Dependency => Scope::Version,
             Self::DependencySlotMissing => Scope::Version,
+            Self::DependencyUse => Scope::Version,
             Self::Duplicates => Scope::Package,
             Self::EapiStale => Scope::Package,
             Self::EapiStatus => Scope::Version,
             Self::Filesdir => Scope::Package,
             Self::EbuildName => Scope::Package,
             Self::Eclass => Scope::Version,
+            Self::Eclasses => Scope::Repo,
             Self::Header => Scope::Version,
             Self::Homepage => Scope::Version,
             Self::Ignore => Scope::Version,
+            Self::Inherit => Scope::Version,
             Self::Iuse => Scope::Version,
             Self::Keywords => Scope::Version,
             Self::KeywordsDropped => Scope::Package,
@@ -177,6 +250,8 @@ impl Check {
             Self::Live => Scope::Package,
             Self::Manifest => Scope::Package,
             Self::Metadata => Scope::Version,
+            Self::MetadataXml => Scope::Package,
+            Self::Profiles => Scope::Repo,
             Self::Properties => Scope::Version,
             Self::PythonUpdate => Scope::Version,
             Self::RepoLayout => Scope::Package,
@@ -184,10 +259,14 @@ impl Check {
             Self::RestrictTestMissing => Scope::Version,
             Self::RubyUpdate => Scope::Version,
             Self::SrcUri => Scope::Version,
+            Self::StableRequest => Scope::Package,
             Self::UnstableOnly => Scope::Package,
+            Self::UnstableStale => Scope::Package,
             Self::UseLocal => Scope::Package,
             Self::VariableOrder => Scope::Version,
             Self::Variables => Scope::Version,
+            Self::VersionRedundant => Scope::Package,
+            Self::Visibility => Scope::Version,
             Self::Whitespace => Scope::Version,
         }
     }
@@ -195,18 +274,22 @@ impl Check {
     /// The sources of values a check can run against.
     pub(crate) fn sources(&self) -> &[SourceKind] {
         match self {
+            Self::Bash => &[SourceKind::EbuildPkg],
             Self::Commands => &[SourceKind::EbuildRawPkg],
             Self::Dependency => &[SourceKind::EbuildPkg],
             Self::DependencySlotMissing => &[SourceKind::EbuildPkg],
+            Self::DependencyUse => &[SourceKind::EbuildPkg],
             Self::Duplicates => &[SourceKind::Cpn],
             Self::EapiStale => &[SourceKind::EbuildPkg],
             Self::EapiStatus => &[SourceKind::EbuildRawPkg],
             Self::Filesdir => &[SourceKind::EbuildPkg],
             Self::EbuildName => &[SourceKind::Cpn],
             Self::Eclass => &[SourceKind::EbuildPkg],
+            Self::Eclasses => &[SourceKind::Eclass],
             Self::Header => &[SourceKind::EbuildRawPkg],
             Self::Homepage => &[SourceKind::EbuildPkg],
             Self::Ignore => &[SourceKind::Cpv, SourceKind::Cpn, SourceKind::Category],
+            Self::Inherit => &[SourceKind::EbuildPkg],
             Self::Iuse => &[SourceKind::EbuildPkg],
             Self::Keywords => &[SourceKind::EbuildPkg],
             Self::KeywordsDropped => &[SourceKind::EbuildPkg],
@@ -214,6 +297,8 @@ impl Check {
             Self::Live => &[SourceKind::EbuildPkg],
             Self::Manifest => &[SourceKind::EbuildPkg],
             Self::Metadata => &[SourceKind::Cpv],
+            Self::MetadataXml => &[SourceKind::EbuildPkg],
+            Self::Profiles => &[SourceKind::Profile],
             Self::Properties => &[SourceKind::EbuildPkg],
             Self::PythonUpdate => &[SourceKind::EbuildPkg],
             Self::RepoLayout => &[SourceKind::Cpn, SourceKind::Category],
@@ -221,10 +306,14 @@ impl Check {
             Self::RestrictTestMissing => &[SourceKind::EbuildPkg],
             Self::RubyUpdate => &[SourceKind::EbuildPkg],
             Self::SrcUri => &[SourceKind::EbuildPkg],
+            Self::StableRequest => &[SourceKind::EbuildPkg],
             Self::UnstableOnly => &[SourceKind::EbuildPkg],
+            Self::UnstableStale => &[SourceKind::EbuildPkg],
             Self::UseLocal => &[SourceKind::EbuildPkg],
             Self::VariableOrder => &[SourceKind::EbuildRawPkg],
             Self::Variables => &[SourceKind::EbuildRawPkg],
+            Self::VersionRedundant => &[SourceKind::EbuildPkg],
+            Self::Visibility => &[SourceKind::EbuildPkg],
             Self::Whitespace => &[SourceKind::EbuildRawPkg],
         }
     }
@@ -237,9 +326,13 @@ impl Check {
             Self::Header => &[Gentoo],
             Self::Ignore => &[Optional],
             Self::Live => &[Gentoo],
+            Self::MetadataXml => &[Gentoo],
             Self::PythonUpdate => &[GentooInherited],
             Self::RubyUpdate => &[GentooInherited],
+            Self::StableRequest => &[Optional],
             Self::UnstableOnly => &[Optional],
+            Self::UnstableStale => &[Optional],
+            Self::VersionRedundant => &[Optional],
             _ => &[],
         }
     }
@@ -368,6 +461,22 @@ pub(crate) trait RepoCheck: fmt::Display {
 }
 pub(crate) type RepoRunner = Box<dyn RepoCheck + Send + Sync>;
 
+/// Run a check against an eclass.
+#[allow(unused_variables)]
+pub(crate) trait EclassCheck: fmt::Display {
+    fn run(&self, eclass: &Eclass, run: &ScannerRun);
+    fn finish_check(&self, run: &ScannerRun) {}
+}
+pub(crate) type EclassRunner = Box<dyn EclassCheck + Send + Sync>;
+
+/// Run a check against a profile directory.
+#[allow(unused_variables)]
+pub(crate) trait ProfileCheck: fmt::Display {
+    fn run(&self, node: &ProfileNode, run: &ScannerRun);
+    fn finish_check(&self, run: &ScannerRun) {}
+}
+pub(crate) type ProfileRunner = Box<dyn ProfileCheck + Send + Sync>;
+
 /// Run a check against a category.
 #[allow(unused_variables)]
 pub(crate) trait CategoryCheck: fmt::Display {
@@ -435,10 +544,13 @@ pub(crate) trait ToRunner<T> {
 impl ToRunner<EbuildPkgRunner> for Check {
     fn to_runner(&self, run: &ScannerRun) -> EbuildPkgRunner {
         match self {
+            Self::Bash => Box::new(bash::create()),
             Self::Dependency => Box::new(dependency::create(run)),
             Self::DependencySlotMissing => Box::new(dependency_slot_missing::create()),
+            Self::DependencyUse => Box::new(dependency_use::create()),
             Self::Eclass => Box::new(eclass::create(run)),
             Self::Homepage => Box::new(homepage::create()),
+            Self::Inherit => Box::new(inherit::create()),
             Self::Iuse => Box::new(iuse::create(run)),
             Self::Keywords => Box::new(keywords::create(run)),
             Self::License => Box::new(license::create(run)),
@@ -448,6 +560,7 @@ impl ToRunner<EbuildPkgRunner> for Check {
             Self::RestrictTestMissing => Box::new(restrict_test_missing::create()),
             Self::RubyUpdate => Box::new(ruby_update::create(run)),
             Self::SrcUri => Box::new(src_uri::create(run)),
+            Self::Visibility => Box::new(visibility::create(run)),
             _ => unreachable!("unsupported check: {self}"),
         }
     }
@@ -461,8 +574,12 @@ impl ToRunner<EbuildPkgSetRunner> for Check {
             Self::KeywordsDropped => Box::new(keywords_dropped::create()),
             Self::Live => Box::new(live::create()),
             Self::Manifest => Box::new(manifest::create(run)),
+            Self::MetadataXml => Box::new(metadata_xml::create()),
+            Self::StableRequest => Box::new(stable_request::create(run)),
             Self::UnstableOnly => Box::new(unstable_only::create(run)),
+            Self::UnstableStale => Box::new(unstable_stale::create(run)),
             Self::UseLocal => Box::new(use_local::create()),
+            Self::VersionRedundant => Box::new(version_redundant::create()),
             _ => unreachable!("unsupported check: {self}"),
         }
     }
@@ -520,6 +637,24 @@ impl ToRunner<CategoryRunner> for Check {
     }
 }
 
+impl ToRunner<EclassRunner> for Check {
+    fn to_runner(&self, _run: &ScannerRun) -> EclassRunner {
+        match self {
+            Self::Eclasses => Box::new(eclasses::create()),
+            _ => unreachable!("unsupported check: {self}"),
+        }
+    }
+}
+
+impl ToRunner<ProfileRunner> for Check {
+    fn to_runner(&self, run: &ScannerRun) -> ProfileRunner {
+        match self {
+            Self::Profiles => Box::new(profiles::create(run)),
+            _ => unreachable!("unsupported check: {self}"),
+        }
+    }
+}
+
 impl ToRunner<RepoRunner> for Check {
     fn to_runner(&self, _run: &ScannerRun) -> RepoRunner {
         unreachable!("unsupported check: {self}");
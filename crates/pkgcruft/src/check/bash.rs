@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use itertools::Itertools;
+use pkgcraft::bash::{Node, Tree};
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::repo::ebuild::Eclass;
+
+use crate::report::Location;
+use crate::report::ReportKind::{
+    CdUnchecked, CommandSubstitutionGlobal, PathUnquoted, PipelineUnchecked,
+};
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgCheck;
+
+/// Path variables that break when subjected to word splitting.
+const PATHS: &[&str] = &["D", "ED", "S", "WORKDIR", "T", "FILESDIR"];
+
+/// Bash builtins that don't affect metadata generation.
+const BUILTINS: &[&str] = &["echo", "printf", "true", "false", "test", "["];
+
+/// Commands that can't meaningfully fail when used in pipelines.
+const INFALLIBLE: &[&str] = &["echo", "printf"];
+
+pub(super) fn create() -> impl EbuildPkgCheck {
+    Check { functions: Default::default() }
+}
+
+struct Check {
+    functions: DashMap<Eclass, Arc<HashSet<String>>>,
+}
+
+super::register!(Check, super::Check::Bash);
+
+/// Return true if a path variable expansion is subject to word splitting.
+fn unquoted(node: &Node) -> bool {
+    let mut node = *node;
+    while let Some(parent) = node.parent() {
+        match parent.kind() {
+            // quoted or non-splitting contexts
+            "string" | "heredoc_body" | "variable_assignment" | "case_statement" => {
+                return false;
+            }
+            "test_command" => return !parent.as_str().starts_with("[["),
+            // splitting contexts
+            "command" | "array" | "for_statement" => return true,
+            _ => node = parent,
+        }
+    }
+    false
+}
+
+/// Return true if a command's exit status is checked.
+fn checked(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    match parent.kind() {
+        // only `cmd || die` forms are handled
+        "list" => {
+            parent.child(0).is_some_and(|x| node == &x)
+                && parent.child(1).is_some_and(|x| x.kind() == "||")
+        }
+        // conditional usage
+        "if_statement" | "elif_clause" | "while_statement" => parent
+            .child_by_field_name("condition")
+            .is_some_and(|x| node == &x),
+        _ => false,
+    }
+}
+
+impl Check {
+    /// Get the global functions defined by an eclass.
+    fn functions(&self, eclass: &Eclass) -> Arc<HashSet<String>> {
+        if let Some(functions) = self.functions.get(eclass) {
+            return functions.clone();
+        }
+
+        let data = fs::read_to_string(eclass.path()).unwrap_or_default();
+        let tree = Tree::new(data.into());
+        let functions: Arc<HashSet<_>> = Arc::new(
+            tree.iter_func()
+                .filter_map(|x| x.name().map(|s| s.to_string()))
+                .collect(),
+        );
+        self.functions.insert(eclass.clone(), functions.clone());
+        functions
+    }
+
+    /// Flag path variables subject to word splitting.
+    fn paths(&self, pkg: &EbuildPkg, tree: &Tree, run: &ScannerRun) {
+        let mut cursor = tree.walk();
+        for node in tree
+            .into_iter()
+            .filter(|x| matches!(x.kind(), "expansion" | "simple_expansion"))
+        {
+            if node
+                .children(&mut cursor)
+                .any(|x| x.kind() == "variable_name" && PATHS.contains(&x.as_str()))
+                && unquoted(&node)
+            {
+                PathUnquoted
+                    .version(pkg)
+                    .message(node.as_str())
+                    .location(Location::span(&node))
+                    .report(run);
+            }
+        }
+    }
+
+    /// Flag `cd` usage without `|| die`.
+    ///
+    /// Note that `cd` isn't fatal in any supported EAPI.
+    fn cd(&self, pkg: &EbuildPkg, tree: &Tree, run: &ScannerRun) {
+        for node in tree
+            .into_iter()
+            .filter(|x| x.kind() == "command_name" && x.as_str() == "cd")
+            .filter_map(|x| x.parent())
+        {
+            if !checked(&node) {
+                CdUnchecked
+                    .version(pkg)
+                    .message(node.as_str())
+                    .location(Location::span(&node))
+                    .report(run);
+            }
+        }
+    }
+
+    /// Flag pipelines using `|| die` that only checks the final command.
+    fn pipelines(&self, pkg: &EbuildPkg, tree: &Tree, run: &ScannerRun) {
+        let mut cursor = tree.walk();
+        for node in tree
+            .into_iter()
+            .filter(|x| x.kind() == "pipeline" && checked(x))
+        {
+            let mut commands: Vec<_> = node
+                .children(&mut cursor)
+                .filter(|x| x.kind() == "command")
+                .collect();
+            commands.pop();
+
+            let ignored = commands
+                .iter()
+                .filter_map(|x| {
+                    let mut cursor = tree.walk();
+                    x.children(&mut cursor)
+                        .find(|x| x.kind() == "command_name")
+                        .map(|x| x.as_str().to_string())
+                })
+                .filter(|x| !INFALLIBLE.contains(&x.as_str()))
+                .join(", ");
+
+            if !ignored.is_empty() {
+                PipelineUnchecked
+                    .version(pkg)
+                    .message(format!("ignored failures: {ignored}"))
+                    .location(Location::span(&node))
+                    .report(run);
+            }
+        }
+    }
+
+    /// Flag global command substitutions running commands outside the package manager.
+    fn substitutions(&self, pkg: &EbuildPkg, tree: &Tree, run: &ScannerRun) {
+        let eapi = pkg.eapi();
+        let defined: HashSet<_> = tree
+            .iter_func()
+            .filter_map(|x| x.name().map(|s| s.to_string()))
+            .collect();
+        let eclasses: Vec<_> = pkg.inherited().iter().map(|x| self.functions(x)).collect();
+        let known = |name: &str| {
+            BUILTINS.contains(&name)
+                || eapi.commands().contains(name)
+                || defined.contains(name)
+                || eclasses.iter().any(|x| x.contains(name))
+        };
+
+        for node in tree
+            .iter_global()
+            .filter(|x| x.kind() == "command_substitution")
+        {
+            if node
+                .into_iter()
+                .any(|x| x.kind() == "command_name" && !known(x.as_str()))
+            {
+                CommandSubstitutionGlobal
+                    .version(pkg)
+                    .message(node.as_str())
+                    .location(Location::span(&node))
+                    .report(run);
+            }
+        }
+    }
+}
+
+impl EbuildPkgCheck for Check {
+    fn run(&self, pkg: &EbuildPkg, run: &ScannerRun) {
+        let tree = pkg.tree();
+        self.paths(pkg, tree, run);
+        self.cd(pkg, tree, run);
+        self.pipelines(pkg, tree, run);
+        self.substitutions(pkg, tree, run);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        temp.create_eclass("funcs", "funcs_version() { echo 1; }\n")
+            .unwrap();
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit funcs
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+            MY_PV=$(ver_cut 1-2)
+            MY_FUNC=$(funcs_version)
+            MY_ARCH=$(uname -m)
+
+            src_prepare() {
+            	default
+            	cd ${S}/src
+            	cd "${WORKDIR}" || die
+            	if cd "${T}"; then
+            		cp ${FILESDIR}/foo "${T}" || die
+            	fi
+            	cat foo | sort || die
+            	echo foo | sort || die
+            	[[ -d ${D} ]] && einfo "${ED}"
+            	local dir=${S}
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+
+        let expected = [
+            r#"{"kind":"CdUnchecked","scope":{"Version":["cat/pkg-1",{"line":13,"column":2,"end":12}]},"message":"cd ${S}/src"}"#,
+            r#"{"kind":"CommandSubstitutionGlobal","scope":{"Version":["cat/pkg-1",{"line":9,"column":9,"end":19}]},"message":"$(uname -m)"}"#,
+            r#"{"kind":"PathUnquoted","scope":{"Version":["cat/pkg-1",{"line":13,"column":5,"end":8}]},"message":"${S}"}"#,
+            r#"{"kind":"PathUnquoted","scope":{"Version":["cat/pkg-1",{"line":16,"column":6,"end":16}]},"message":"${FILESDIR}"}"#,
+            r#"{"kind":"PipelineUnchecked","scope":{"Version":["cat/pkg-1",{"line":18,"column":2,"end":15}]},"message":"ignored failures: cat"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit funcs
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+            MY_PV=$(ver_cut 1-2)
+            MY_FUNC=$(funcs_version)
+
+            src_prepare() {
+            	default
+            	cd "${S}"/src || die
+            	cd "${WORKDIR}" || die
+            	if cd "${T}"; then
+            		cp "${FILESDIR}"/foo "${T}" || die
+            	fi
+            	sort foo || die
+            	echo foo | sort || die
+            	[[ -d ${D} ]] && einfo "${ED}"
+            	local dir=${S}
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
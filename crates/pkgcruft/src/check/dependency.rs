@@ -14,7 +14,8 @@ use crate::scan::ScannerRun;
 use super::EbuildPkgCheck;
 
 pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgCheck + 'static {
-    let unused = if run.enabled(PackageDeprecatedUnused) {
+    // track deprecated package usage while scanning if the revdeps index is unavailable
+    let unused = if run.enabled(PackageDeprecatedUnused) && run.revdeps().is_none() {
         run.repo
             .metadata()
             .pkg_deprecated()
@@ -44,7 +45,9 @@ impl EbuildPkgCheck for Check {
                     .use_deps()
                     .into_iter()
                     .flatten()
-                    .filter(|x| matches!(x.kind(), UseDepKind::Conditional))
+                    .filter(|x| {
+                        matches!(x.kind(), UseDepKind::Conditional | UseDepKind::Equal)
+                    })
                     .map(|x| x.flag())
                     .filter(|flag| !pkg.iuse_effective().contains(*flag))
                 {
@@ -123,16 +126,32 @@ impl EbuildPkgCheck for Check {
     }
 
     fn finish_check(&self, run: &ScannerRun) {
-        if run.enabled(PackageDeprecatedUnused) && !self.unused.is_empty() {
-            let unused = self
-                .unused
+        if !run.enabled(PackageDeprecatedUnused) {
+            return;
+        }
+
+        let unused: Vec<_> = if let Some(revdeps) = run.revdeps() {
+            run.repo
+                .metadata()
+                .pkg_deprecated()
                 .iter()
+                .filter(|entry| {
+                    !revdeps
+                        .get(entry.cpn())
+                        .into_iter()
+                        .flat_map(|x| x.keys())
+                        .any(|revdep| run.repo.deprecated(revdep.dep()) == Some(*entry))
+                })
                 .map(|x| x.to_string())
-                .sorted()
-                .join(", ");
+                .collect()
+        } else {
+            self.unused.iter().map(|x| x.to_string()).collect()
+        };
+
+        if !unused.is_empty() {
             PackageDeprecatedUnused
                 .repo(&run.repo)
-                .message(unused)
+                .message(unused.iter().sorted().join(", "))
                 .report(run);
         }
     }
@@ -140,7 +159,9 @@ impl EbuildPkgCheck for Check {
 
 #[cfg(test)]
 mod tests {
+    use camino::Utf8Path;
     use pkgcraft::test::{test_data, test_data_patched};
+    use tempfile::tempdir;
 
     use crate::scan::Scanner;
     use crate::test::{assert_unordered_reports, glob_reports};
@@ -157,6 +178,16 @@ mod tests {
         let dir = repo.path().join(CHECK);
         let expected = glob_reports!("{dir}/**/reports.json");
         let reports = scanner.run(repo, repo).unwrap();
+        assert_unordered_reports!(reports, expected.clone());
+
+        // primary unfixed using the revdeps index
+        let cache = tempdir().unwrap();
+        let cache_dir = Utf8Path::from_path(cache.path()).unwrap();
+        let reports = scanner
+            .clone()
+            .cache_dir(cache_dir)
+            .run(repo, repo)
+            .unwrap();
         assert_unordered_reports!(reports, expected);
 
         // primary fixed
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use itertools::Itertools;
+use pkgcraft::dep::{Cpv, Dep};
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::repo::PkgRepository;
+
+use crate::report::ReportKind::DependencyUseMissing;
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgCheck;
+
+pub(super) fn create() -> impl EbuildPkgCheck {
+    Check { targets: Default::default() }
+}
+
+/// Matching packages for a dependency with their IUSE flags.
+type Targets = Arc<Vec<(Cpv, HashSet<String>)>>;
+
+struct Check {
+    targets: DashMap<Dep, Targets>,
+}
+
+super::register!(Check, super::Check::DependencyUse);
+
+impl Check {
+    /// Get all package versions matching a dependency from the repo and its masters.
+    fn targets(&self, dep: &Dep, run: &ScannerRun) -> Targets {
+        let dep = dep.no_use_deps().into_owned();
+        if let Some(targets) = self.targets.get(&dep) {
+            return targets.clone();
+        }
+
+        let targets: Targets = Arc::new(
+            run.repo
+                .trees()
+                .flat_map(|repo| repo.iter_restrict(&dep))
+                .filter_map(Result::ok)
+                .map(|pkg| (pkg.cpv().clone(), pkg.iuse_effective().iter().cloned().collect()))
+                .collect(),
+        );
+        self.targets.insert(dep, targets.clone());
+        targets
+    }
+}
+
+impl EbuildPkgCheck for Check {
+    fn run(&self, pkg: &EbuildPkg, run: &ScannerRun) {
+        for key in pkg.eapi().dep_keys().iter().copied() {
+            for dep in pkg
+                .dependencies([key])
+                .iter_flatten()
+                .filter(|x| x.blocker().is_none() && x.use_deps().is_some())
+                .unique()
+            {
+                let targets = self.targets(dep, run);
+                for use_dep in dep
+                    .use_deps()
+                    .into_iter()
+                    .flatten()
+                    .filter(|x| x.default().is_none())
+                {
+                    let flag = use_dep.flag();
+                    let missing = targets
+                        .iter()
+                        .filter(|(_, iuse)| !iuse.contains(flag))
+                        .map(|(cpv, _)| cpv)
+                        .join(", ");
+                    if !missing.is_empty() {
+                        DependencyUseMissing
+                            .version(pkg)
+                            .message(format!("{key}: {dep}: missing {flag}: {missing}"))
+                            .report(run);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        temp.create_ebuild("dev/target-1", &["IUSE=a b"]).unwrap();
+        temp.create_ebuild("dev/target-2", &["IUSE=a"]).unwrap();
+        let data = [
+            "IUSE=c",
+            "DEPEND=dev/target[a,b] dev/target[b(-)] =dev/target-1[b] dev/target[c?]",
+            "RDEPEND=dev/target[missing] !dev/target[missing] dev/nonexistent[a]",
+        ];
+        temp.create_ebuild("cat/pkg-1", &data).unwrap();
+
+        let expected = [
+            r#"{"kind":"DependencyUseMissing","scope":{"Version":["cat/pkg-1",null]},"message":"DEPEND: dev/target[a,b]: missing b: dev/target-2"}"#,
+            r#"{"kind":"DependencyUseMissing","scope":{"Version":["cat/pkg-1",null]},"message":"DEPEND: dev/target[c?]: missing c: dev/target-1, dev/target-2"}"#,
+            r#"{"kind":"DependencyUseMissing","scope":{"Version":["cat/pkg-1",null]},"message":"RDEPEND: dev/target[missing]: missing missing: dev/target-1, dev/target-2"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let data = [
+            "IUSE=c",
+            "DEPEND=dev/target[a] dev/target[b(-)] =dev/target-1[b] dev/target[c(-)?]",
+            "RDEPEND=dev/target !dev/target[missing] dev/nonexistent[a]",
+        ];
+        temp.create_ebuild("cat/pkg-1", &data).unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
@@ -0,0 +1,362 @@
+use std::fs;
+
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use pkgcraft::bash::{Node, Tree};
+use pkgcraft::eapi::EAPIS;
+use pkgcraft::pkg::ebuild::metadata::Key;
+use pkgcraft::repo::ebuild::Eclass;
+use pkgcraft::repo::ebuild::eclass::doc::EclassDoc;
+use regex::Regex;
+use tracing::warn;
+
+use crate::report::ReportKind::{
+    EclassDocInvalid, EclassDocMissing, EclassEapisMismatch, EclassFunctionUndocumented,
+    EclassNameMismatch, EclassSyntaxError, EclassVariableUndocumented,
+};
+use crate::scan::ScannerRun;
+
+use super::EclassCheck;
+
+pub(super) fn create() -> impl EclassCheck {
+    Check {
+        eapi_re: Regex::new(r#"^"?\$\{?EAPI(:-0)?\}?"?$"#).unwrap(),
+        variable_re: Regex::new(r"^[A-Z][A-Z0-9_]*$").unwrap(),
+    }
+}
+
+struct Check {
+    eapi_re: Regex,
+    variable_re: Regex,
+}
+
+super::register!(Check, super::Check::Eclasses);
+
+/// Split a documentation parsing error into its line number and message.
+fn doc_error(err: &pkgcraft::Error) -> (Option<usize>, String) {
+    let err = err.to_string();
+    err.strip_prefix("line ")
+        .and_then(|s| s.split_once(": "))
+        .and_then(|(line, msg)| Some((Some(line.parse().ok()?), msg.to_string())))
+        .unwrap_or((None, err))
+}
+
+impl Check {
+    /// Flag bash syntax errors, returning true if none exist.
+    fn syntax(&self, eclass: &Eclass, tree: &Tree, run: &ScannerRun) -> bool {
+        let mut valid = true;
+        for node in tree
+            .into_iter()
+            .filter(|x| x.is_error() || x.is_missing())
+            // skip errors nested inside other errors
+            .filter(|x| !x.parent().is_some_and(|p| p.is_error()))
+        {
+            let message = if node.is_missing() {
+                format!("missing {}", node.kind())
+            } else {
+                "invalid syntax".to_string()
+            };
+
+            EclassSyntaxError
+                .eclass(eclass)
+                .message(message)
+                .location(&node)
+                .report(run);
+            valid = false;
+        }
+        valid
+    }
+
+    /// Return the supported EAPIs from a global EAPI guard if one exists.
+    fn eapi_guard<'a>(
+        &self,
+        data: &str,
+        tree: &'a Tree,
+    ) -> Option<(Node<'a>, IndexSet<String>)> {
+        let data = data.as_bytes();
+        let node = tree.iter_global().find(|x| {
+            x.kind() == "case_statement"
+                && x.child_by_field_name("value")
+                    .and_then(|v| v.utf8_text(data).ok())
+                    .is_some_and(|v| self.eapi_re.is_match(v))
+        })?;
+
+        let mut eapis = IndexSet::new();
+        let mut cursor = tree.walk();
+        for item in node
+            .children(&mut cursor)
+            .filter(|x| x.kind() == "case_item")
+        {
+            // items that die are unsupported
+            if item
+                .into_iter()
+                .any(|x| x.kind() == "command_name" && x.as_str() == "die")
+            {
+                continue;
+            }
+
+            let mut cursor = item.walk();
+            for value in item.children_by_field_name("value", &mut cursor) {
+                let value = value
+                    .utf8_text(data)
+                    .unwrap_or_default()
+                    .trim_matches(['"', '\'']);
+                // patterns can't be reliably compared
+                if value.contains(['*', '?', '[']) {
+                    return None;
+                }
+                eapis.insert(value.to_string());
+            }
+        }
+
+        Some((node, eapis))
+    }
+
+    /// Parse the eclass documentation, flagging missing or invalid documentation.
+    fn doc(&self, eclass: &Eclass, data: &str, run: &ScannerRun) -> Option<EclassDoc> {
+        let Some(lineno) = data
+            .lines()
+            .position(|x| x.starts_with("# @ECLASS:"))
+            .map(|i| i + 1)
+        else {
+            EclassDocMissing.eclass(eclass).report(run);
+            return None;
+        };
+
+        let doc: EclassDoc = match data.parse() {
+            Ok(doc) => doc,
+            Err(e) => {
+                let (line, msg) = doc_error(&e);
+                let mut report = EclassDocInvalid.eclass(eclass).message(msg);
+                if let Some(line) = line {
+                    report = report.location(line);
+                }
+                report.report(run);
+                return None;
+            }
+        };
+
+        if doc.name() != eclass.name() {
+            EclassNameMismatch
+                .eclass(eclass)
+                .message(format!("@ECLASS: {}.eclass", doc.name()))
+                .location(lineno)
+                .report(run);
+        }
+
+        Some(doc)
+    }
+
+    /// Flag EAPI guards that don't match the documented supported EAPIs.
+    fn eapis(
+        &self,
+        eclass: &Eclass,
+        doc: &EclassDoc,
+        data: &str,
+        tree: &Tree,
+        run: &ScannerRun,
+    ) {
+        let Some((node, guard)) = self.eapi_guard(data, tree) else {
+            return;
+        };
+
+        let supported: IndexSet<_> = doc.supported_eapis().iter().cloned().collect();
+        if supported != guard {
+            let supported = if supported.is_empty() {
+                "none".to_string()
+            } else {
+                supported.iter().join(" ")
+            };
+            EclassEapisMismatch
+                .eclass(eclass)
+                .message(format!(
+                    "@SUPPORTED_EAPIS: {supported}, EAPI guard: {}",
+                    guard.iter().join(" ")
+                ))
+                .location(&node)
+                .report(run);
+        }
+    }
+
+    /// Flag exported functions and variables lacking documentation.
+    fn undocumented(&self, eclass: &Eclass, doc: &EclassDoc, tree: &Tree, run: &ScannerRun) {
+        // global functions not prefixed with an underscore are exported
+        for node in tree
+            .iter_global()
+            .filter(|x| x.kind() == "function_definition")
+        {
+            if let Some(name) = node.name()
+                && !name.starts_with('_')
+                && !doc.functions().contains_key(name)
+            {
+                EclassFunctionUndocumented
+                    .eclass(eclass)
+                    .message(name)
+                    .location(&node)
+                    .report(run);
+            }
+        }
+
+        // global, uppercase variables not prefixed with an underscore are exported
+        let mut variables = IndexMap::new();
+        for node in tree.iter_global().filter(|x| {
+            x.kind() == "variable_assignment"
+                && x.parent().is_none_or(|p| p.kind() != "command")
+        }) {
+            if let Some(name) = node.name()
+                && self.variable_re.is_match(name)
+                && name.parse::<Key>().is_err()
+                && !EAPIS.iter().any(|e| e.env().contains(name))
+                && !doc.variables().contains_key(name)
+            {
+                variables.entry(name.to_string()).or_insert(node);
+            }
+        }
+
+        for (name, node) in variables {
+            EclassVariableUndocumented
+                .eclass(eclass)
+                .message(name)
+                .location(&node)
+                .report(run);
+        }
+    }
+}
+
+impl EclassCheck for Check {
+    fn run(&self, eclass: &Eclass, run: &ScannerRun) {
+        let data = match fs::read_to_string(eclass.path()) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("{CHECK}: {eclass}: failed reading: {e}");
+                return;
+            }
+        };
+
+        let tree = Tree::new(data.as_str().into());
+        let valid = self.syntax(eclass, &tree, run);
+
+        // skip parse tree related checks for eclasses with invalid syntax
+        if let Some(doc) = self.doc(eclass, &data, run)
+            && valid
+        {
+            self.eapis(eclass, &doc, &data, &tree, run);
+            self.undocumented(eclass, &doc, &tree, run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+
+        // valid
+        let data = indoc::indoc! {r#"
+            # @ECLASS: good.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @SUPPORTED_EAPIS: 8
+            # @BLURB: valid eclass
+
+            case ${EAPI} in
+                8) ;;
+                *) die "${ECLASS}: EAPI ${EAPI:-0} not supported" ;;
+            esac
+
+            # @ECLASS_VARIABLE: GOOD_VAR
+            # @DESCRIPTION:
+            # Documented variable.
+            GOOD_VAR=1
+            _GOOD_ECLASS=1
+
+            # @FUNCTION: good_func
+            # @DESCRIPTION:
+            # Documented function.
+            good_func() {
+                LOCAL_VAR=1
+                _good_helper
+            }
+
+            _good_helper() { :; }
+        "#};
+        temp.create_eclass("good", data).unwrap();
+
+        // missing documentation
+        temp.create_eclass("missing", "MISSING_VAR=1\n").unwrap();
+
+        // invalid documentation
+        let data = indoc::indoc! {"
+            # @ECLASS: invalid.eclass
+            # @BLURB: invalid eclass
+        "};
+        temp.create_eclass("invalid", data).unwrap();
+
+        // mismatched documentation
+        let data = indoc::indoc! {r#"
+            # @ECLASS: other.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @SUPPORTED_EAPIS: 7 8
+            # @BLURB: mismatched eclass
+
+            case ${EAPI} in
+                8) ;;
+                *) die "unsupported EAPI" ;;
+            esac
+
+            MISMATCH_VAR=1
+
+            mismatch_func() { :; }
+        "#};
+        temp.create_eclass("mismatch", data).unwrap();
+
+        // invalid syntax
+        let data = indoc::indoc! {"
+            # @ECLASS: syntax.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @BLURB: eclass with invalid syntax
+
+            )
+        "};
+        temp.create_eclass("syntax", data).unwrap();
+
+        let expected = [
+            r#"{"kind":"EclassDocInvalid","scope":{"Eclass":["invalid",{"line":1,"column":0}]},"message":"missing @MAINTAINER"}"#,
+            r#"{"kind":"EclassDocMissing","scope":{"Eclass":["missing",null]}}"#,
+            r#"{"kind":"EclassEapisMismatch","scope":{"Eclass":["mismatch",{"line":7,"column":1}]},"message":"@SUPPORTED_EAPIS: 7 8, EAPI guard: 8"}"#,
+            r#"{"kind":"EclassFunctionUndocumented","scope":{"Eclass":["mismatch",{"line":14,"column":1}]},"message":"mismatch_func"}"#,
+            r#"{"kind":"EclassNameMismatch","scope":{"Eclass":["mismatch",{"line":1,"column":0}]},"message":"@ECLASS: other.eclass"}"#,
+            r#"{"kind":"EclassSyntaxError","scope":{"Eclass":["syntax",{"line":6,"column":1}]},"message":"invalid syntax"}"#,
+            r#"{"kind":"EclassVariableUndocumented","scope":{"Eclass":["mismatch",{"line":12,"column":1}]},"message":"MISMATCH_VAR"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        for name in ["missing", "invalid", "mismatch", "syntax"] {
+            let data = indoc::formatdoc! {"
+                # @ECLASS: {name}.eclass
+                # @MAINTAINER:
+                # Maintainer <maintainer@example.com>
+                # @BLURB: valid eclass
+
+                # @FUNCTION: {name}_func
+                # @DESCRIPTION:
+                # Documented function.
+                {name}_func() {{ :; }}
+            "};
+            temp.create_eclass(name, &data).unwrap();
+        }
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
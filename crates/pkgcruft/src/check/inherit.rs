@@ -0,0 +1,399 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use indexmap::{IndexMap, IndexSet};
+use pkgcraft::bash::{Node, Tree};
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::repo::ebuild::Eclass;
+use pkgcraft::repo::ebuild::eclass::doc::EclassDoc;
+use pkgcraft::shell::phase::PhaseKind;
+
+use crate::report::ReportKind::{
+    FunctionDeprecated, FunctionInternal, InheritIndirect, InheritUnused,
+};
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgCheck;
+
+pub(super) fn create() -> impl EbuildPkgCheck {
+    Check { eclasses: Default::default() }
+}
+
+/// Eclass functionality available to inheriting ebuilds.
+#[derive(Debug, Default)]
+struct EclassInfo {
+    functions: HashSet<String>,
+    variables: HashSet<String>,
+    internal: HashSet<String>,
+    deprecated: HashMap<String, String>,
+    provides: Vec<String>,
+    phases: bool,
+}
+
+impl EclassInfo {
+    /// Determine if a function or variable is provided by the eclass.
+    fn provides(&self, name: &str, function: bool) -> bool {
+        if function {
+            self.functions.contains(name)
+        } else {
+            self.variables.contains(name)
+        }
+    }
+}
+
+struct Check {
+    eclasses: DashMap<Eclass, Arc<EclassInfo>>,
+}
+
+super::register!(Check, super::Check::Inherit);
+
+impl Check {
+    /// Get the functionality provided by an eclass.
+    fn info(&self, eclass: &Eclass) -> Arc<EclassInfo> {
+        if let Some(info) = self.eclasses.get(eclass) {
+            return info.clone();
+        }
+
+        let mut info = EclassInfo::default();
+        if let Ok(data) = fs::read_to_string(eclass.path()) {
+            let tree = Tree::new(data.as_str().into());
+            for node in tree.iter_global() {
+                match node.kind() {
+                    "function_definition" => {
+                        if let Some(name) = node.name() {
+                            if name.parse::<PhaseKind>().is_ok() {
+                                info.phases = true;
+                            }
+                            info.functions.insert(name.to_string());
+                        }
+                    }
+                    "command_name" if node.as_str() == "EXPORT_FUNCTIONS" => {
+                        info.phases = true;
+                    }
+                    _ => (),
+                }
+            }
+
+            // documentation errors are flagged by the Eclasses check
+            if let Ok(doc) = data.parse::<EclassDoc>() {
+                for (name, func) in doc.functions() {
+                    info.functions.insert(name.clone());
+                    if func.internal() {
+                        info.internal.insert(name.clone());
+                    }
+                    if let Some(value) = func.deprecated() {
+                        info.deprecated.insert(name.clone(), value.to_string());
+                    }
+                }
+                info.variables.extend(doc.variables().keys().cloned());
+                info.provides = doc.provides().to_vec();
+            }
+        }
+
+        let info = Arc::new(info);
+        self.eclasses.insert(eclass.clone(), info.clone());
+        info
+    }
+}
+
+impl EbuildPkgCheck for Check {
+    fn run(&self, pkg: &EbuildPkg, run: &ScannerRun) {
+        let tree = pkg.tree();
+        let eclasses: IndexMap<_, _> =
+            pkg.inherited().iter().map(|x| (x, self.info(x))).collect();
+
+        // map eclasses to their direct inherits, including @PROVIDES eclasses
+        let mut direct: IndexMap<&Eclass, &Eclass> =
+            pkg.inherit().iter().map(|x| (x, x)).collect();
+        let mut i = 0;
+        while let Some((eclass, root)) = direct.get_index(i).map(|(k, v)| (*k, *v)) {
+            for name in &eclasses[eclass].provides {
+                if let Some(provided) = pkg.inherited().iter().find(|x| x.name() == name) {
+                    direct.entry(provided).or_insert(root);
+                }
+            }
+            i += 1;
+        }
+
+        // functions defined by the ebuild override eclass functions
+        let defined: HashSet<_> = tree
+            .iter_func()
+            .filter_map(|x| x.name().map(|s| s.to_string()))
+            .collect();
+
+        // track the first usage of each function and variable
+        let mut usage = IndexMap::<(String, bool), Option<Node>>::new();
+        let mut assigned = HashSet::new();
+        for node in tree {
+            let name = node.as_str().to_string();
+            match node.kind() {
+                "command_name" if !defined.contains(&name) => {
+                    usage.entry((name, true)).or_insert(Some(node));
+                }
+                "variable_name" => {
+                    let entry = usage.entry((name.clone(), false)).or_default();
+                    if node
+                        .parent()
+                        .is_some_and(|x| x.kind() == "variable_assignment")
+                    {
+                        assigned.insert(name);
+                    } else if entry.is_none() {
+                        *entry = Some(node);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut used = HashSet::new();
+        for ((name, function), node) in usage {
+            let providers: Vec<_> = eclasses
+                .iter()
+                .filter(|(_, info)| info.provides(&name, function))
+                .collect();
+            let Some((eclass, _)) = providers.first() else {
+                continue;
+            };
+
+            let roots: Vec<_> = providers
+                .iter()
+                .filter_map(|(eclass, _)| direct.get(*eclass).copied())
+                .collect();
+            if !roots.is_empty() {
+                used.extend(roots);
+            } else if let Some(node) = &node
+                && !assigned.contains(&name)
+            {
+                InheritIndirect
+                    .version(pkg)
+                    .message(format!("{eclass}: {name}"))
+                    .location(node)
+                    .report(run);
+            }
+
+            // only function calls are flagged for internal or deprecated usage
+            let Some(node) = node.filter(|_| function) else {
+                continue;
+            };
+
+            if let Some((eclass, _)) =
+                providers.iter().find(|(_, x)| x.internal.contains(&name))
+            {
+                FunctionInternal
+                    .version(pkg)
+                    .message(format!("{eclass}: {name}"))
+                    .location(&node)
+                    .report(run);
+            }
+
+            if let Some((eclass, replacement)) = providers
+                .iter()
+                .find_map(|(e, x)| x.deprecated.get(&name).map(|r| (e, r)))
+            {
+                let message = if replacement == "none" {
+                    format!("{eclass}: {name}")
+                } else {
+                    format!("{eclass}: {name}: migrate to {replacement}")
+                };
+                FunctionDeprecated
+                    .version(pkg)
+                    .message(message)
+                    .location(&node)
+                    .report(run);
+            }
+        }
+
+        // eclasses exporting phase functions are implicitly used
+        let mut unused: IndexSet<_> = pkg
+            .inherit()
+            .iter()
+            .filter(|x| !used.contains(x) && !eclasses[x].phases)
+            .map(|x| x.name())
+            .collect();
+        if unused.is_empty() {
+            return;
+        }
+
+        for node in tree
+            .iter_global()
+            .filter(|x| x.kind() == "command_name" && x.as_str() == "inherit")
+        {
+            let Some(cmd) = node.parent() else {
+                continue;
+            };
+            let mut cursor = tree.walk();
+            for arg in cmd.children(&mut cursor).filter(|x| x.kind() == "word") {
+                if unused.shift_remove(arg.as_str()) {
+                    InheritUnused
+                        .version(pkg)
+                        .message(arg.as_str())
+                        .location(&arg)
+                        .report(run);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+
+        let data = indoc::indoc! {"
+            # @ECLASS: base.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @BLURB: base eclass
+
+            # @ECLASS_VARIABLE: BASE_VAR
+            # @DESCRIPTION:
+            # Documented variable.
+
+            # @FUNCTION: base_func
+            # @DESCRIPTION:
+            # Documented function.
+            base_func() { :; }
+
+            # @FUNCTION: base_internal
+            # @INTERNAL
+            # @DESCRIPTION:
+            # Internal function.
+            base_internal() { :; }
+
+            # @FUNCTION: base_old
+            # @DEPRECATED: base_func
+            # @DESCRIPTION:
+            # Deprecated function.
+            base_old() { :; }
+        "};
+        temp.create_eclass("base", data).unwrap();
+
+        let data = indoc::indoc! {"
+            # @ECLASS: wrapper.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @BLURB: eclass inheriting another eclass
+
+            inherit base
+
+            # @FUNCTION: wrapper_func
+            # @DESCRIPTION:
+            # Documented function.
+            wrapper_func() { base_func; }
+        "};
+        temp.create_eclass("wrapper", data).unwrap();
+
+        let data = indoc::indoc! {"
+            # @ECLASS: phases.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @BLURB: eclass exporting phase functions
+
+            phases_src_compile() { :; }
+
+            EXPORT_FUNCTIONS src_compile
+        "};
+        temp.create_eclass("phases", data).unwrap();
+
+        let data = indoc::indoc! {"
+            # @ECLASS: unused.eclass
+            # @MAINTAINER:
+            # Maintainer <maintainer@example.com>
+            # @BLURB: unused eclass
+
+            unused_func() { :; }
+        "};
+        temp.create_eclass("unused", data).unwrap();
+
+        // indirect usage and unused inherits
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit wrapper phases unused
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+
+            src_prepare() {
+            	default
+            	wrapper_func
+            	base_func
+            	einfo "${BASE_VAR}"
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+
+        // internal and deprecated function usage
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit base
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+            BASE_VAR=1
+
+            src_prepare() {
+            	default
+            	base_internal
+            	base_old
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-2", data).unwrap();
+
+        let expected = [
+            r#"{"kind":"InheritIndirect","scope":{"Version":["cat/pkg-1",{"line":11,"column":2}]},"message":"base: base_func"}"#,
+            r#"{"kind":"InheritIndirect","scope":{"Version":["cat/pkg-1",{"line":12,"column":11}]},"message":"base: BASE_VAR"}"#,
+            r#"{"kind":"InheritUnused","scope":{"Version":["cat/pkg-1",{"line":3,"column":24}]},"message":"unused"}"#,
+            r#"{"kind":"FunctionInternal","scope":{"Version":["cat/pkg-2",{"line":11,"column":2}]},"message":"base: base_internal"}"#,
+            r#"{"kind":"FunctionDeprecated","scope":{"Version":["cat/pkg-2",{"line":12,"column":2}]},"message":"base: base_old: migrate to base_func"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit base wrapper phases
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+
+            src_prepare() {
+            	default
+            	wrapper_func
+            	base_func
+            	einfo "${BASE_VAR}"
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-1", data).unwrap();
+        let data = indoc::indoc! {r#"
+            EAPI=8
+
+            inherit base
+
+            DESCRIPTION="stub package description"
+            SLOT=0
+            BASE_VAR=1
+
+            src_prepare() {
+            	default
+            	base_func
+            }
+        "#};
+        temp.create_ebuild_from_str("cat/pkg-2", data).unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
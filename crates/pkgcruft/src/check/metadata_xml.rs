@@ -0,0 +1,492 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use pkgcraft::dep::Cpn;
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::keyword::KeywordStatus;
+use pkgcraft::repo::Repository;
+use regex::Regex;
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::report::ReportKind::{
+    MaintainerEmailInvalid, MaintainerMissing, MaintainerTypeUnknown, MetadataXmlInvalid,
+    MetadataXmlMissing, MetadataXmlSlotUnknown, PackageOrphaned, RemoteIdInvalid,
+    RemoteIdUnknown, StabilizeAllarchesInvalid,
+};
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgSetCheck;
+
+/// Supported remote-id types mapped to their value patterns.
+static REMOTE_IDS: &[(&str, &str)] = &[
+    ("bitbucket", PAIR),
+    ("codeberg", PAIR),
+    ("cpan", SINGLE),
+    ("cpan-module", r"^[A-Za-z0-9_]+(::[A-Za-z0-9_]+)*$"),
+    ("cpe", r"^cpe:/[aho]:[^:\s]+:[^:\s]+(:[^:\s]*)*$"),
+    ("cran", SINGLE),
+    ("ctan", r"^[^\s]+$"),
+    ("freedesktop-gitlab", PATH),
+    ("gentoo", PATH),
+    ("github", PAIR),
+    ("gitlab", PATH),
+    ("gnome-gitlab", PATH),
+    ("google-code", SINGLE),
+    ("hackage", SINGLE),
+    ("heptapod", PATH),
+    ("kde-invent", PATH),
+    ("launchpad", SINGLE),
+    ("luarocks", r"^[^\s/]+(/[^\s/]+)?$"),
+    ("nimble", SINGLE),
+    ("osdn", SINGLE),
+    ("pear", SINGLE),
+    ("pecl", SINGLE),
+    ("pypi", SINGLE),
+    ("rubygems", SINGLE),
+    ("savannah", SINGLE),
+    ("savannah-nongnu", SINGLE),
+    ("sourceforge", SINGLE),
+    ("sourcehut", r"^~[^\s/]+/[^\s/]+$"),
+    ("vim", SINGLE),
+];
+
+/// Remote-id value pattern for single component names.
+const SINGLE: &str = r"^[^\s/]+$";
+
+/// Remote-id value pattern for owner and project names.
+const PAIR: &str = r"^[^\s/]+/[^\s/]+$";
+
+/// Remote-id value pattern for nested project paths.
+const PATH: &str = r"^[^\s/]+(/[^\s/]+)+$";
+
+/// Schema definition for an element.
+#[derive(Default)]
+struct Schema {
+    attrs: &'static [&'static str],
+    required_attrs: &'static [&'static str],
+    required: &'static [&'static str],
+    unique: &'static [&'static str],
+}
+
+/// Return the schema for an element with a given parent as defined by GLEP 68.
+fn schema(parent: &str, name: &str) -> Option<Schema> {
+    let schema = match (parent, name) {
+        ("", "pkgmetadata") => Schema {
+            unique: &["stabilize-allarches", "upstream"],
+            ..Default::default()
+        },
+        ("pkgmetadata", "maintainer") => Schema {
+            attrs: &["proxied", "restrict", "type"],
+            required_attrs: &["type"],
+            required: &["email"],
+            unique: &["email", "name"],
+        },
+        ("pkgmetadata", "longdescription") => Schema {
+            attrs: &["lang", "restrict"],
+            ..Default::default()
+        },
+        ("pkgmetadata", "slots") => Schema {
+            attrs: &["lang"],
+            unique: &["subslots"],
+            ..Default::default()
+        },
+        ("pkgmetadata", "stabilize-allarches") => Schema {
+            attrs: &["restrict"],
+            ..Default::default()
+        },
+        ("pkgmetadata", "use") => Schema {
+            attrs: &["lang"],
+            ..Default::default()
+        },
+        ("pkgmetadata", "upstream") => Schema {
+            unique: &["bugs-to", "changelog"],
+            ..Default::default()
+        },
+        ("slots", "slot") => Schema {
+            attrs: &["name"],
+            required_attrs: &["name"],
+            ..Default::default()
+        },
+        ("use", "flag") => Schema {
+            attrs: &["name", "restrict"],
+            required_attrs: &["name"],
+            ..Default::default()
+        },
+        ("upstream", "maintainer") => Schema {
+            attrs: &["status"],
+            required: &["name"],
+            unique: &["email", "name"],
+            ..Default::default()
+        },
+        ("upstream", "doc") => Schema {
+            attrs: &["lang"],
+            ..Default::default()
+        },
+        ("upstream", "remote-id") => Schema {
+            attrs: &["type"],
+            required_attrs: &["type"],
+            ..Default::default()
+        },
+        ("maintainer", "description" | "email" | "name")
+        | ("slots", "subslots")
+        | ("upstream", "bugs-to" | "changelog")
+        | ("longdescription" | "flag", "cat" | "pkg") => Default::default(),
+        _ => return None,
+    };
+
+    Some(schema)
+}
+
+/// Validate an element and its descendants, returning all schema violations.
+fn validate(doc: &Document, node: Node, errors: &mut Vec<String>) {
+    let parent = node
+        .parent_element()
+        .map(|x| x.tag_name().name())
+        .unwrap_or_default();
+    let name = node.tag_name().name();
+    let line = doc.text_pos_at(node.range().start).row;
+
+    let Some(schema) = schema(parent, name) else {
+        errors.push(format!("line {line}: invalid element: {name}"));
+        return;
+    };
+
+    for attr in node.attributes() {
+        if !schema.attrs.contains(&attr.name()) {
+            errors.push(format!("line {line}: {name}: invalid attribute: {}", attr.name()));
+        }
+    }
+
+    for attr in schema.required_attrs {
+        if node.attribute(*attr).is_none() {
+            errors.push(format!("line {line}: {name}: missing attribute: {attr}"));
+        }
+    }
+
+    let mut counts = HashMap::<_, usize>::new();
+    for child in node.children().filter(|x| x.is_element()) {
+        *counts.entry(child.tag_name().name()).or_default() += 1;
+        validate(doc, child, errors);
+    }
+
+    for child in schema.required {
+        if !counts.contains_key(child) {
+            errors.push(format!("line {line}: {name}: missing element: {child}"));
+        }
+    }
+
+    for child in schema.unique {
+        if counts.get(child).copied().unwrap_or_default() > 1 {
+            errors.push(format!("line {line}: {name}: duplicate element: {child}"));
+        }
+    }
+}
+
+pub(super) fn create() -> impl EbuildPkgSetCheck {
+    Check {
+        email_re: Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap(),
+        remote_ids: REMOTE_IDS
+            .iter()
+            .map(|(name, re)| (*name, Regex::new(re).unwrap()))
+            .collect(),
+    }
+}
+
+struct Check {
+    email_re: Regex,
+    remote_ids: HashMap<&'static str, Regex>,
+}
+
+super::register!(Check, super::Check::MetadataXml);
+
+impl Check {
+    /// Check for maintainer related issues.
+    fn maintainers(&self, cpn: &Cpn, doc: &Document, run: &ScannerRun) {
+        let root = doc.root_element();
+        let mut maintainers = 0;
+        for node in root.children().filter(|x| x.has_tag_name("maintainer")) {
+            maintainers += 1;
+
+            if let Some(value) = node.attribute("type")
+                && !["person", "project"].contains(&value)
+            {
+                MaintainerTypeUnknown
+                    .package(cpn)
+                    .message(value)
+                    .report(run);
+            }
+
+            for email in node.children().filter(|x| x.has_tag_name("email")) {
+                let value = email.text().unwrap_or_default().trim();
+                if !self.email_re.is_match(value) {
+                    MaintainerEmailInvalid
+                        .package(cpn)
+                        .message(format!("{value:?}"))
+                        .report(run);
+                }
+            }
+        }
+
+        if maintainers == 0 {
+            // orphaned packages are explicitly marked with a comment
+            let orphaned = root
+                .children()
+                .filter(|x| x.is_comment())
+                .any(|x| x.text().unwrap_or_default().trim() == "maintainer-needed");
+            if orphaned {
+                PackageOrphaned.package(cpn).report(run);
+            } else {
+                MaintainerMissing.package(cpn).report(run);
+            }
+        }
+    }
+
+    /// Check for invalid or unknown remote-ids.
+    fn remote_ids(&self, cpn: &Cpn, doc: &Document, run: &ScannerRun) {
+        let nodes = doc
+            .root_element()
+            .children()
+            .filter(|x| x.has_tag_name("upstream"))
+            .flat_map(|x| x.children())
+            .filter(|x| x.has_tag_name("remote-id"));
+
+        for node in nodes {
+            let Some(kind) = node.attribute("type") else {
+                continue;
+            };
+            let value = node.text().unwrap_or_default().trim();
+            if let Some(re) = self.remote_ids.get(kind) {
+                if !re.is_match(value) {
+                    RemoteIdInvalid
+                        .package(cpn)
+                        .message(format!("{kind}: {value:?}"))
+                        .report(run);
+                }
+            } else {
+                RemoteIdUnknown.package(cpn).message(kind).report(run);
+            }
+        }
+    }
+
+    /// Check for slot descriptions that don't match any package versions.
+    fn slots(&self, cpn: &Cpn, pkgs: &[EbuildPkg], doc: &Document, run: &ScannerRun) {
+        let slots: HashSet<_> = pkgs.iter().map(|x| x.slot()).collect();
+        let nodes = doc
+            .root_element()
+            .children()
+            .filter(|x| x.has_tag_name("slots"))
+            .flat_map(|x| x.children());
+
+        for node in nodes {
+            if node.has_tag_name("slot") {
+                if let Some(slot) = node.attribute("name")
+                    && slot != "*"
+                    && !slots.contains(slot)
+                {
+                    MetadataXmlSlotUnknown
+                        .package(cpn)
+                        .message(format!("slot: {slot}"))
+                        .report(run);
+                }
+            } else if node.has_tag_name("subslots")
+                && pkgs.iter().all(|x| x.subslot() == x.slot())
+            {
+                MetadataXmlSlotUnknown
+                    .package(cpn)
+                    .message("subslots: no versions define subslots")
+                    .report(run);
+            }
+        }
+    }
+
+    /// Check for stabilize-allarches usage on packages that can't use it.
+    fn stabilize_allarches(&self, cpn: &Cpn, pkgs: &[EbuildPkg], run: &ScannerRun) {
+        if !run.repo.metadata().pkg_metadata(cpn).stabilize_allarches() {
+            return;
+        }
+
+        let keyworded = pkgs.iter().any(|pkg| {
+            pkg.keywords()
+                .iter()
+                .any(|k| k.status() != KeywordStatus::Disabled)
+        });
+
+        if !keyworded {
+            StabilizeAllarchesInvalid
+                .package(cpn)
+                .message("no keyworded versions")
+                .report(run);
+        }
+
+        for pkg in pkgs {
+            if pkg.keywords().iter().any(|k| k.arch() == "*") {
+                StabilizeAllarchesInvalid
+                    .package(cpn)
+                    .message(format!("arch-specific version: {}", pkg.cpv()))
+                    .report(run);
+            }
+        }
+    }
+}
+
+impl EbuildPkgSetCheck for Check {
+    fn run(&self, cpn: &Cpn, pkgs: &[EbuildPkg], run: &ScannerRun) {
+        let path = run.repo.path().join(cpn.to_string()).join("metadata.xml");
+        let Ok(data) = fs::read_to_string(&path) else {
+            MetadataXmlMissing.package(cpn).report(run);
+            return;
+        };
+
+        let opts = ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        };
+        let doc = match Document::parse_with_options(&data, opts) {
+            Ok(doc) => doc,
+            Err(e) => {
+                MetadataXmlInvalid.package(cpn).message(e).report(run);
+                return;
+            }
+        };
+
+        let mut errors = vec![];
+        validate(&doc, doc.root_element(), &mut errors);
+        for error in errors {
+            MetadataXmlInvalid.package(cpn).message(error).report(run);
+        }
+
+        self.maintainers(cpn, &doc, run);
+        self.remote_ids(cpn, &doc, run);
+        self.slots(cpn, pkgs, &doc, run);
+        self.stabilize_allarches(cpn, pkgs, run);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        fs::write(temp.path().join("profiles/arch.list"), "amd64\n").unwrap();
+
+        // valid
+        temp.create_ebuild("cat/valid-1", &["KEYWORDS=amd64", "SLOT=1/2"])
+            .unwrap();
+        let data = indoc::indoc! {r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <!DOCTYPE pkgmetadata SYSTEM "https://www.gentoo.org/dtd/metadata.dtd">
+            <pkgmetadata>
+                <maintainer type="person">
+                    <email>a.person@email.com</email>
+                </maintainer>
+                <slots>
+                    <slot name="1">slot description</slot>
+                    <subslots>subslot description</subslots>
+                </slots>
+                <stabilize-allarches/>
+                <upstream>
+                    <remote-id type="github">pkgcraft/pkgcraft</remote-id>
+                </upstream>
+            </pkgmetadata>
+        "#};
+        fs::write(temp.path().join("cat/valid/metadata.xml"), data).unwrap();
+
+        // invalid
+        temp.create_ebuild("cat/invalid-1", &["KEYWORDS=-* amd64"])
+            .unwrap();
+        let data = indoc::indoc! {r#"
+            <pkgmetadata>
+                <maintainer type="team" lang="en">
+                    <email>invalid</email>
+                </maintainer>
+                <slots>
+                    <slot name="2">slot description</slot>
+                    <subslots>subslot description</subslots>
+                </slots>
+                <stabilize-allarches/>
+                <upstream>
+                    <remote-id type="github">pkgcraft</remote-id>
+                    <remote-id type="unknown">pkgcraft</remote-id>
+                    <remote-id>pkgcraft</remote-id>
+                </upstream>
+                <unknown/>
+            </pkgmetadata>
+        "#};
+        fs::write(temp.path().join("cat/invalid/metadata.xml"), data).unwrap();
+
+        // orphaned
+        temp.create_ebuild("cat/orphaned-1", &[]).unwrap();
+        let data = indoc::indoc! {r#"
+            <pkgmetadata>
+                <!-- maintainer-needed -->
+            </pkgmetadata>
+        "#};
+        fs::write(temp.path().join("cat/orphaned/metadata.xml"), data).unwrap();
+
+        // unmaintained
+        temp.create_ebuild("cat/unmaintained-1", &[]).unwrap();
+        fs::write(temp.path().join("cat/unmaintained/metadata.xml"), "<pkgmetadata/>")
+            .unwrap();
+
+        // malformed
+        temp.create_ebuild("cat/malformed-1", &[]).unwrap();
+        fs::write(temp.path().join("cat/malformed/metadata.xml"), "<pkgmetadata>").unwrap();
+
+        // missing
+        temp.create_ebuild("cat/missing-1", &[]).unwrap();
+
+        let expected = [
+            r#"{"kind":"MaintainerEmailInvalid","scope":{"Package":"cat/invalid"},"message":"\"invalid\""}"#,
+            r#"{"kind":"MaintainerMissing","scope":{"Package":"cat/unmaintained"}}"#,
+            r#"{"kind":"MaintainerTypeUnknown","scope":{"Package":"cat/invalid"},"message":"team"}"#,
+            r#"{"kind":"MetadataXmlInvalid","scope":{"Package":"cat/invalid"},"message":"line 2: maintainer: invalid attribute: lang"}"#,
+            r#"{"kind":"MetadataXmlInvalid","scope":{"Package":"cat/invalid"},"message":"line 13: remote-id: missing attribute: type"}"#,
+            r#"{"kind":"MetadataXmlInvalid","scope":{"Package":"cat/invalid"},"message":"line 15: invalid element: unknown"}"#,
+            r#"{"kind":"MetadataXmlInvalid","scope":{"Package":"cat/malformed"},"message":"the root node was opened but never closed"}"#,
+            r#"{"kind":"MetadataXmlMissing","scope":{"Package":"cat/missing"}}"#,
+            r#"{"kind":"MetadataXmlSlotUnknown","scope":{"Package":"cat/invalid"},"message":"slot: 2"}"#,
+            r#"{"kind":"MetadataXmlSlotUnknown","scope":{"Package":"cat/invalid"},"message":"subslots: no versions define subslots"}"#,
+            r#"{"kind":"PackageOrphaned","scope":{"Package":"cat/orphaned"}}"#,
+            r#"{"kind":"RemoteIdInvalid","scope":{"Package":"cat/invalid"},"message":"github: \"pkgcraft\""}"#,
+            r#"{"kind":"RemoteIdUnknown","scope":{"Package":"cat/invalid"},"message":"unknown"}"#,
+            r#"{"kind":"StabilizeAllarchesInvalid","scope":{"Package":"cat/invalid"},"message":"arch-specific version: cat/invalid-1"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let data = indoc::indoc! {r#"
+            <pkgmetadata>
+                <maintainer type="person">
+                    <email>a.person@email.com</email>
+                </maintainer>
+                <slots>
+                    <slot name="0">slot description</slot>
+                </slots>
+                <upstream>
+                    <remote-id type="github">pkgcraft/pkgcraft</remote-id>
+                </upstream>
+            </pkgmetadata>
+        "#};
+        fs::write(temp.path().join("cat/invalid/metadata.xml"), data).unwrap();
+        let data = indoc::indoc! {r#"
+            <pkgmetadata>
+                <maintainer type="project">
+                    <email>project@email.com</email>
+                </maintainer>
+            </pkgmetadata>
+        "#};
+        for pkg in ["orphaned", "unmaintained", "malformed", "missing"] {
+            fs::write(temp.path().join(format!("cat/{pkg}/metadata.xml")), data).unwrap();
+        }
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::HashSet;
+
+use camino::Utf8PathBuf;
+use dashmap::{DashMap, DashSet};
+use pkgcraft::dep::Dep;
+use pkgcraft::eapi::Eapi;
+use pkgcraft::pkg::Package;
+use pkgcraft::repo::PkgRepository;
+use pkgcraft::repo::ebuild::profile::Profile;
+use pkgcraft::traits::{Contains, FilterLines};
+
+use crate::report::ReportKind::{
+    ProfileDescMissing, ProfileEntryInvalid, ProfileFlagUnknown, ProfilePackageObsolete,
+    ProfilePackageUnknown, ProfileParentInvalid, ProfileUnused,
+};
+use crate::scan::ScannerRun;
+use crate::source::ProfileNode;
+
+use super::ProfileCheck;
+
+/// Profile files containing package dependencies.
+static PKG_FILES: &[&str] = &["package.mask", "package.unmask"];
+
+/// Profile files containing package dependencies with USE flags.
+static PKG_FLAG_FILES: &[&str] = &[
+    "package.use",
+    "package.use.force",
+    "package.use.mask",
+    "package.use.stable.force",
+    "package.use.stable.mask",
+];
+
+/// Profile files containing USE flags.
+static FLAG_FILES: &[&str] = &["use.force", "use.mask", "use.stable.force", "use.stable.mask"];
+
+pub(super) fn create(run: &ScannerRun) -> impl ProfileCheck + 'static {
+    let mut flags = HashSet::new();
+    for repo in run.repo.trees() {
+        let metadata = repo.metadata();
+        flags.extend(metadata.arches().iter().map(|x| x.to_string()));
+        flags.extend(metadata.use_global().keys().cloned());
+        flags.extend(
+            metadata
+                .use_local()
+                .values()
+                .flat_map(|x| x.keys().cloned()),
+        );
+        for (name, values) in metadata.use_expand() {
+            flags.extend(values.keys().map(|x| format!("{name}_{x}")));
+        }
+    }
+
+    // implicit flags defined by profile settings, e.g. IUSE_IMPLICIT
+    for profile in run
+        .repo
+        .metadata()
+        .profiles_desc()
+        .iter()
+        .filter_map(|x| Profile::load(&run.repo, x.path()).ok())
+    {
+        let var = |name: &str| profile.var(name).unwrap_or_default().split_whitespace();
+        flags.extend(var("IUSE_IMPLICIT").map(Into::into));
+        let unprefixed: HashSet<_> = var("USE_EXPAND_UNPREFIXED").collect();
+        for name in var("USE_EXPAND_IMPLICIT") {
+            let values = var(&format!("USE_EXPAND_VALUES_{name}"));
+            if unprefixed.contains(name) {
+                flags.extend(values.map(Into::into));
+            } else {
+                let prefix = name.to_lowercase();
+                flags.extend(values.map(|x| format!("{prefix}_{x}")));
+            }
+        }
+    }
+
+    let listed = run
+        .repo
+        .metadata()
+        .profiles_desc()
+        .iter()
+        .map(|x| run.repo.path().join("profiles").join(x.path()))
+        .collect();
+
+    Check {
+        flags,
+        listed,
+        nodes: Default::default(),
+        parents: Default::default(),
+    }
+}
+
+struct Check {
+    flags: HashSet<String>,
+    listed: HashSet<Utf8PathBuf>,
+    nodes: DashMap<Utf8PathBuf, (Utf8PathBuf, bool)>,
+    parents: DashSet<Utf8PathBuf>,
+}
+
+super::register!(Check, super::Check::Profiles);
+
+impl Check {
+    /// Verify a package dependency exists, returning true if it has matching versions.
+    fn pkg_exists(
+        &self,
+        node: &ProfileNode,
+        name: &str,
+        i: usize,
+        dep: &Dep,
+        run: &ScannerRun,
+    ) -> bool {
+        let dep = dep.no_use_deps();
+        let relpath = node.relpath(name);
+        if !run.repo.trees().any(|r| r.contains(dep.cpn())) {
+            ProfilePackageUnknown
+                .profile(relpath)
+                .message(dep)
+                .location(i)
+                .report(run);
+            false
+        } else if !run.repo.trees().any(|r| r.contains(&*dep)) {
+            ProfilePackageObsolete
+                .profile(relpath)
+                .message(dep)
+                .location(i)
+                .report(run);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Parse a package dependency, reporting invalid entries.
+    fn parse_dep(
+        &self,
+        node: &ProfileNode,
+        name: &str,
+        i: usize,
+        value: &str,
+        eapi: &'static Eapi,
+        run: &ScannerRun,
+    ) -> Option<Dep> {
+        eapi.dep(value)
+            .map_err(|e| {
+                ProfileEntryInvalid
+                    .profile(node.relpath(name))
+                    .message(e)
+                    .location(i)
+                    .report(run);
+            })
+            .ok()
+    }
+}
+
+impl ProfileCheck for Check {
+    fn run(&self, node: &ProfileNode, run: &ScannerRun) {
+        let eapi = match node.eapi(&run.repo) {
+            Ok(eapi) => eapi,
+            Err(e) => {
+                ProfileEntryInvalid
+                    .profile(node.relpath("eapi"))
+                    .message(e)
+                    .location(1)
+                    .report(run);
+                run.repo.eapi()
+            }
+        };
+
+        for (i, value) in node.read("parent").filter_lines() {
+            match Profile::parent(&run.repo, node.abspath(), value) {
+                Ok(path) => {
+                    self.parents.insert(path);
+                }
+                Err(e) => {
+                    ProfileParentInvalid
+                        .profile(node.relpath("parent"))
+                        .message(e)
+                        .location(i)
+                        .report(run);
+                }
+            }
+        }
+
+        for name in PKG_FILES {
+            for (i, value) in node.read(name).filter_lines() {
+                let value = value.strip_prefix('-').unwrap_or(value);
+                if let Some(dep) = self.parse_dep(node, name, i, value, eapi, run) {
+                    self.pkg_exists(node, name, i, &dep, run);
+                }
+            }
+        }
+
+        for name in PKG_FLAG_FILES {
+            for (i, value) in node.read(name).filter_lines() {
+                let mut values = value.split_whitespace();
+                let value = values.next().unwrap_or_default();
+                let Some(dep) = self.parse_dep(node, name, i, value, eapi, run) else {
+                    continue;
+                };
+                if !self.pkg_exists(node, name, i, &dep, run) {
+                    continue;
+                }
+
+                let pkgs: Vec<_> = run
+                    .repo
+                    .trees()
+                    .flat_map(|r| r.iter_restrict(&*dep.no_use_deps()))
+                    .filter_map(Result::ok)
+                    .collect();
+                for flag in values.map(|s| s.strip_prefix('-').unwrap_or(s)) {
+                    if flag != "*" && !pkgs.iter().any(|p| p.iuse_effective().contains(flag)) {
+                        ProfileFlagUnknown
+                            .profile(node.relpath(name))
+                            .message(format!("{dep}: {flag}"))
+                            .location(i)
+                            .report(run);
+                    }
+                }
+            }
+        }
+
+        for name in FLAG_FILES {
+            for (i, value) in node.read(name).filter_lines() {
+                let flag = value.strip_prefix('-').unwrap_or(value);
+                if flag != "*" && !self.flags.contains(flag) {
+                    ProfileFlagUnknown
+                        .profile(node.relpath(name))
+                        .message(flag)
+                        .location(i)
+                        .report(run);
+                }
+            }
+        }
+
+        // track profile directories for unused and unlisted checks
+        if !node.is_root() && !node.files().is_empty() {
+            let has_parent = node.files().contains("parent");
+            self.nodes
+                .insert(node.abspath().to_path_buf(), (node.path().to_path_buf(), has_parent));
+        }
+    }
+
+    fn finish_check(&self, run: &ScannerRun) {
+        for entry in self.nodes.iter() {
+            let (abspath, (path, has_parent)) = entry.pair();
+            if self.listed.contains(abspath) || self.parents.contains(abspath) {
+                continue;
+            }
+
+            if *has_parent {
+                ProfileDescMissing.profile(path).report(run);
+            } else {
+                ProfileUnused.profile(path).report(run);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("arch.list"), "amd64\n").unwrap();
+        fs::write(path.join("use.desc"), "global - global flag\n").unwrap();
+        fs::write(path.join("profiles.desc"), "amd64 default/amd64 stable\n").unwrap();
+        let data = indoc::indoc! {"
+            cat/pkg
+            -=cat/pkg-1
+            =cat/pkg-2
+            cat/nonexistent
+        "};
+        fs::write(path.join("package.mask"), data).unwrap();
+
+        // base profile
+        fs::create_dir_all(path.join("base")).unwrap();
+        let data = indoc::indoc! {"
+            -*
+            global
+            -amd64
+            unknown
+            prefix
+            elibc_glibc
+            arm64
+        "};
+        fs::write(path.join("base/use.mask"), data).unwrap();
+        let data = indoc::indoc! {r#"
+            IUSE_IMPLICIT="prefix"
+            USE_EXPAND_IMPLICIT="ARCH ELIBC"
+            USE_EXPAND_UNPREFIXED="ARCH"
+            USE_EXPAND_VALUES_ARCH="amd64 arm64"
+            USE_EXPAND_VALUES_ELIBC="glibc"
+        "#};
+        fs::write(path.join("base/make.defaults"), data).unwrap();
+        let data = indoc::indoc! {"
+            cat/pkg local -global
+            cat/nonexistent local
+        "};
+        fs::write(path.join("base/package.use.mask"), data).unwrap();
+
+        // listed profile
+        fs::create_dir_all(path.join("default/amd64")).unwrap();
+        fs::write(path.join("default/amd64/parent"), "../../base\n").unwrap();
+
+        // unlisted profile
+        fs::create_dir_all(path.join("default/unlisted")).unwrap();
+        fs::write(path.join("default/unlisted/parent"), "../../base\n../../nonexistent\n")
+            .unwrap();
+
+        // unused profile
+        fs::create_dir_all(path.join("features/unused")).unwrap();
+        fs::write(path.join("features/unused/use.force"), "global\n").unwrap();
+
+        // ignored data directories
+        fs::create_dir_all(path.join("updates")).unwrap();
+        fs::write(path.join("updates/1Q-2025"), "move cat/a cat/b\n").unwrap();
+
+        temp.create_ebuild("cat/pkg-1", &["IUSE=local"]).unwrap();
+
+        let expected = [
+            r#"{"kind":"ProfileDescMissing","scope":{"Profile":["default/unlisted",null]}}"#,
+            r#"{"kind":"ProfileFlagUnknown","scope":{"Profile":["base/package.use.mask",{"line":1,"column":0}]},"message":"cat/pkg: global"}"#,
+            r#"{"kind":"ProfileFlagUnknown","scope":{"Profile":["base/use.mask",{"line":4,"column":0}]},"message":"unknown"}"#,
+            r#"{"kind":"ProfilePackageObsolete","scope":{"Profile":["package.mask",{"line":3,"column":0}]},"message":"=cat/pkg-2"}"#,
+            r#"{"kind":"ProfilePackageUnknown","scope":{"Profile":["base/package.use.mask",{"line":2,"column":0}]},"message":"cat/nonexistent"}"#,
+            r#"{"kind":"ProfilePackageUnknown","scope":{"Profile":["package.mask",{"line":4,"column":0}]},"message":"cat/nonexistent"}"#,
+            r#"{"kind":"ProfileParentInvalid","scope":{"Profile":["default/unlisted/parent",{"line":2,"column":0}]},"message":"nonexistent parent: ../../nonexistent"}"#,
+            r#"{"kind":"ProfileUnused","scope":{"Profile":["features/unused",null]}}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let data = indoc::indoc! {"
+            amd64 default/amd64 stable
+            amd64 default/unlisted dev
+        "};
+        fs::write(path.join("profiles.desc"), data).unwrap();
+        fs::write(path.join("package.mask"), "cat/pkg\n-=cat/pkg-1\n").unwrap();
+        let data = indoc::indoc! {"
+            -*
+            global
+            -amd64
+            prefix
+            elibc_glibc
+            arm64
+        "};
+        fs::write(path.join("base/use.mask"), data).unwrap();
+        fs::write(path.join("base/package.use.mask"), "cat/pkg local\n").unwrap();
+        fs::write(path.join("default/amd64/parent"), "../../base\n../../features/unused\n")
+            .unwrap();
+        fs::write(path.join("default/unlisted/parent"), "../../base\n").unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
@@ -1,14 +1,85 @@
+use std::cmp::Reverse;
+use std::sync::LazyLock;
+
 use dashmap::DashSet;
+use indexmap::IndexSet;
 use itertools::Itertools;
+use pkgcraft::dep::Uri;
+use pkgcraft::eapi::Feature::SrcUriUnrestrict;
 use pkgcraft::error::Error;
 use pkgcraft::fetch::Fetchable;
+use pkgcraft::pkg::Package;
 use pkgcraft::pkg::ebuild::EbuildPkg;
+use regex::Regex;
+use url::Url;
 
-use crate::report::ReportKind::{MirrorsUnused, UriInvalid};
+use crate::report::ReportKind::{
+    DistfileGeneric, MirrorMissing, MirrorUnknown, MirrorsUnused, RemoteIdMissing, UriInvalid,
+};
 use crate::scan::ScannerRun;
 
 use super::EbuildPkgCheck;
 
+/// Distfile names lacking any package identification, e.g. bare version archives.
+static GENERIC_FILENAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    let version = r"v?[0-9][0-9.]*([_-]?(alpha|beta|pre|rc)[0-9]*)?";
+    let archive = r"\.(tar(\.[a-z0-9]+)?|t[bgx]z2?|zip|7z)";
+    let generic = r"(archive|download|latest|main|master|source)(\.[^/]+)?";
+    Regex::new(&format!("^({version}{archive}|{generic})$")).unwrap()
+});
+
+/// Upstream URL patterns for metadata.xml remote-id types.
+static REMOTE_IDS: LazyLock<Vec<(&str, Regex)>> = LazyLock::new(|| {
+    [
+        ("codeberg", r"^https?://codeberg\.org/(?<name>[^/]+/[^/]+)"),
+        ("github", r"^https?://(www\.)?github\.com/(?<name>[^/]+/[^/]+)"),
+        ("gitlab", r"^https?://gitlab\.com/(?<name>[^/]+/[^/]+)"),
+        ("pypi", r"^https?://files\.pythonhosted\.org/packages/source/./(?<name>[^/]+)"),
+        ("pypi", r"^https?://pypi\.org/project/(?<name>[^/]+)"),
+    ]
+    .into_iter()
+    .map(|(site, re)| (site, Regex::new(re).unwrap()))
+    .collect()
+});
+
+/// Return the remote-id type and name matching an upstream URL if one exists.
+fn remote_id(url: &str) -> Option<(&'static str, String)> {
+    REMOTE_IDS.iter().find_map(|(site, re)| {
+        re.captures(url)
+            .and_then(|caps| caps.name("name"))
+            .map(|m| (*site, m.as_str().trim_end_matches(".git").to_string()))
+    })
+}
+
+/// Return the URL for a URI using a mirror unknown to the repo if one exists.
+fn unknown_mirror(uri: &Uri, pkg: &EbuildPkg, run: &ScannerRun) -> Option<Url> {
+    let mut value = uri.as_str();
+    if pkg.eapi().has(SrcUriUnrestrict) {
+        value = value
+            .strip_prefix("mirror+")
+            .or_else(|| value.strip_prefix("fetch+"))
+            .unwrap_or(value);
+    }
+
+    let url = Url::parse(value).ok()?;
+    let name = url.domain()?;
+    if url.scheme() == "mirror" && !run.repo.mirrors().contains_key(name) {
+        Some(url)
+    } else {
+        None
+    }
+}
+
+/// Normalize a remote-id name for comparison.
+fn normalize(site: &str, name: &str) -> String {
+    let name = name.to_lowercase();
+    if site == "pypi" {
+        name.replace(['_', '.'], "-")
+    } else {
+        name
+    }
+}
+
 pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgCheck + 'static {
     let unused = if run.enabled(MirrorsUnused) {
         run.repo.metadata().mirrors().keys().cloned().collect()
@@ -16,24 +87,80 @@ pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgCheck + 'static {
         Default::default()
     };
 
-    Check { unused }
+    // match the most specific mirror URLs first
+    let mirrors = run
+        .repo
+        .mirrors()
+        .iter()
+        .flat_map(|(name, mirrors)| {
+            mirrors
+                .iter()
+                .map(move |x| (x.url().to_string(), name.clone()))
+        })
+        .sorted_by_key(|(url, _)| Reverse(url.len()))
+        .collect();
+
+    Check { unused, mirrors }
 }
 
 struct Check {
     unused: DashSet<String>,
+    mirrors: Vec<(String, String)>,
 }
 
 super::register!(Check, super::Check::SrcUri);
 
+impl Check {
+    /// Flag upstream URLs lacking related metadata.xml remote-ids.
+    fn remote_ids<'a, I>(&self, pkg: &EbuildPkg, urls: I, run: &ScannerRun)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let existing: Vec<_> = pkg
+            .metadata()
+            .upstream()
+            .into_iter()
+            .flat_map(|x| x.remote_ids())
+            .map(|x| (x.site(), normalize(x.site(), x.name())))
+            .collect();
+
+        let missing: IndexSet<_> = urls
+            .into_iter()
+            .filter_map(remote_id)
+            .filter(|(site, name)| {
+                // remote-ids can reference nested projects, e.g. gitlab subgroups
+                let name = normalize(site, name);
+                !existing.iter().any(|(s, n)| {
+                    s == site && (*n == name || n.starts_with(&format!("{name}/")))
+                })
+            })
+            .collect();
+
+        for (site, name) in missing {
+            RemoteIdMissing
+                .version(pkg)
+                .message(format!("{site}: {name}"))
+                .report(run);
+        }
+    }
+}
+
 impl EbuildPkgCheck for Check {
     fn run(&self, pkg: &EbuildPkg, run: &ScannerRun) {
+        let mut urls = vec![];
         for uri in pkg.src_uri().iter_flatten() {
-            let result = Fetchable::from_uri(uri, pkg, false);
-            let Ok(fetchable) = result else {
-                if let Err(Error::InvalidFetchable(err)) = result {
-                    UriInvalid.version(pkg).message(err).report(run);
+            let fetchable = match Fetchable::from_uri(uri, pkg, false) {
+                Ok(value) => value,
+                Err(Error::InvalidFetchable(err)) => {
+                    // unknown mirrors are flagged separately from other invalid URIs
+                    if let Some(url) = unknown_mirror(uri, pkg, run) {
+                        MirrorUnknown.version(pkg).message(url).report(run);
+                    } else {
+                        UriInvalid.version(pkg).message(err).report(run);
+                    }
+                    continue;
                 }
-                continue;
+                Err(_) => continue,
             };
 
             if let Some(mirror) = fetchable.mirrors().first() {
@@ -41,8 +168,32 @@ impl EbuildPkgCheck for Check {
                 if run.enabled(MirrorsUnused) {
                     self.unused.remove(mirror.name());
                 }
+            } else if let Some((prefix, name)) = self
+                .mirrors
+                .iter()
+                .find(|(prefix, _)| fetchable.as_str().starts_with(prefix.as_str()))
+            {
+                let url = fetchable.as_str();
+                let path = &url[prefix.len()..];
+                MirrorMissing
+                    .version(pkg)
+                    .message(format!("{url}: mirror://{name}/{path}"))
+                    .report(run);
+            }
+
+            let filename = fetchable.filename();
+            if fetchable.rename().is_none()
+                && GENERIC_FILENAME_RE.is_match(filename)
+                && !filename.contains(pkg.package())
+            {
+                DistfileGeneric.version(pkg).message(filename).report(run);
             }
+
+            urls.push(fetchable.url.to_string());
         }
+
+        let urls = urls.iter().chain(pkg.homepage().iter()).map(|x| x.as_str());
+        self.remote_ids(pkg, urls, run);
     }
 
     fn finish_check(&self, run: &ScannerRun) {
@@ -60,10 +211,13 @@ impl EbuildPkgCheck for Check {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
     use pkgcraft::test::{assert_err_re, test_data, test_data_patched};
 
     use crate::scan::Scanner;
-    use crate::test::{assert_unordered_reports, glob_reports};
+    use crate::test::{assert_temp_repo_reports, assert_unordered_reports, glob_reports};
 
     use super::*;
 
@@ -91,4 +245,28 @@ mod tests {
         let reports = scanner.run(repo, repo).unwrap();
         assert_unordered_reports!(reports, []);
     }
+
+    #[test]
+    fn mirror_missing() {
+        // mirror definitions are repo-wide so a separate repo is used
+        let scanner = Scanner::new().reports([MirrorMissing]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        fs::write(
+            temp.path().join("profiles/thirdpartymirrors"),
+            "example https://mirror.example.com/pub\n",
+        )
+        .unwrap();
+
+        let src_uri = "SRC_URI=https://mirror.example.com/pub/pkg/pkg-1.tar.gz";
+        temp.create_ebuild("cat/pkg-1", &[src_uri]).unwrap();
+        let expected = [
+            r#"{"kind":"MirrorMissing","scope":{"Version":["cat/pkg-1",null]},"message":"https://mirror.example.com/pub/pkg/pkg-1.tar.gz: mirror://example/pkg/pkg-1.tar.gz"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        let src_uri = "SRC_URI=mirror://example/pkg/pkg-1.tar.gz";
+        temp.create_ebuild("cat/pkg-1", &[src_uri]).unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
 }
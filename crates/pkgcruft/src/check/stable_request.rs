@@ -0,0 +1,206 @@
+use dashmap::DashMap;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use pkgcraft::dep::{Cpn, Dep, Dependency};
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::keyword::{Arch, KeywordStatus};
+use pkgcraft::repo::PkgRepository;
+
+use crate::report::ReportKind::StableRequest;
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgSetCheck;
+
+pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgSetCheck + 'static {
+    Check {
+        stable: run
+            .repo
+            .metadata()
+            .arches_desc()
+            .get("stable")
+            .cloned()
+            .unwrap_or_default(),
+        cache: Default::default(),
+    }
+}
+
+struct Check {
+    stable: IndexSet<Arch>,
+    cache: DashMap<(Arch, Dep), bool>,
+}
+
+super::register!(Check, super::Check::StableRequest);
+
+/// Return true if a package has a keyword for an arch with a given status.
+fn keyworded(pkg: &EbuildPkg, arch: &Arch, status: KeywordStatus) -> bool {
+    pkg.keywords()
+        .iter()
+        .any(|k| k.arch() == arch && k.status() == status)
+}
+
+impl Check {
+    /// Determine if a dependency has a stable match for an arch.
+    fn stable(&self, dep: &Dep, arch: &Arch, run: &ScannerRun) -> bool {
+        let key = (arch.clone(), dep.no_use_deps().into_owned());
+        if let Some(value) = self.cache.get(&key) {
+            return *value;
+        }
+
+        let (arch, dep) = &key;
+        let stable = run.repo.trees().any(|repo| {
+            repo.iter_restrict(dep)
+                .filter_map(Result::ok)
+                .any(|pkg| keyworded(&pkg, arch, KeywordStatus::Stable))
+        });
+
+        self.cache.insert(key, stable);
+        stable
+    }
+
+    /// Determine if a dependency tree node is satisfiable using stable packages.
+    ///
+    /// Conditional dependencies are always evaluated since any USE configuration
+    /// could be used on stable systems.
+    fn resolved(&self, dep: &Dependency<&Dep>, arch: &Arch, run: &ScannerRun) -> bool {
+        match dep {
+            // blockers don't pull in packages
+            Dependency::Enabled(x) => x.blocker().is_some() || self.stable(x, arch, run),
+            Dependency::AllOf(deps) | Dependency::Conditional(_, deps) => {
+                deps.iter().all(|x| self.resolved(x, arch, run))
+            }
+            Dependency::AnyOf(deps) => deps.iter().any(|x| self.resolved(x, arch, run)),
+            _ => true,
+        }
+    }
+}
+
+impl EbuildPkgSetCheck for Check {
+    fn run(&self, _cpn: &Cpn, pkgs: &[EbuildPkg], run: &ScannerRun) {
+        let Some(history) = run.history() else {
+            return;
+        };
+
+        let mut candidates = IndexMap::<_, Vec<_>>::new();
+        for slot in pkgs.iter().map(|x| x.slot()).unique() {
+            // live ebuilds are never stabilized
+            let pkgs: Vec<_> = pkgs
+                .iter()
+                .filter(|x| x.slot() == slot && !x.live())
+                .collect();
+
+            for arch in &self.stable {
+                // only consider arches with existing stable versions
+                let Some(i) = pkgs
+                    .iter()
+                    .rposition(|x| keyworded(x, arch, KeywordStatus::Stable))
+                else {
+                    continue;
+                };
+
+                // find the newest unstable version that has been in the tree long enough
+                let Some(pkg) = pkgs[i + 1..]
+                    .iter()
+                    .rev()
+                    .filter(|x| keyworded(x, arch, KeywordStatus::Unstable))
+                    .find(|x| history.days(x).is_some_and(|days| days >= run.stable_time))
+                else {
+                    continue;
+                };
+
+                let deps = pkg.dependencies([]);
+                if deps.iter().all(|x| self.resolved(x, arch, run)) {
+                    candidates.entry(pkg.cpv()).or_default().push(arch);
+                }
+            }
+        }
+
+        for (cpv, arches) in candidates {
+            StableRequest
+                .version(cpv)
+                .message(arches.iter().sorted().join(", "))
+                .report(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::{assert_temp_repo_reports, git_commit};
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("arch.list"), "amd64\narm64\nx86\n").unwrap();
+        fs::write(path.join("arches.desc"), "amd64 stable\narm64 stable\nx86 testing\n")
+            .unwrap();
+
+        // dependencies
+        temp.create_ebuild("dev/stable-1", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+        temp.create_ebuild("dev/unstable-1", &["KEYWORDS=amd64 ~arm64"])
+            .unwrap();
+
+        // candidate with stable dependencies on all arches
+        temp.create_ebuild("cat/a-1", &["KEYWORDS=amd64 arm64 x86"])
+            .unwrap();
+        temp.create_ebuild("cat/a-2", &["KEYWORDS=~amd64 ~arm64 ~x86", "RDEPEND=dev/stable"])
+            .unwrap();
+        // candidate with unstable dependencies on arm64
+        let data = [
+            "KEYWORDS=~amd64 ~arm64",
+            "IUSE=test",
+            "RDEPEND=|| ( dev/nonexistent dev/stable ) test? ( dev/unstable ) !dev/unstable",
+        ];
+        temp.create_ebuild("cat/b-1", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+        temp.create_ebuild("cat/b-2", &data).unwrap();
+        // unstable-only packages aren't candidates
+        temp.create_ebuild("cat/c-1", &["KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+        // newest candidate in the slot is selected
+        temp.create_ebuild("cat/d-1", &["KEYWORDS=amd64"]).unwrap();
+        temp.create_ebuild("cat/d-2", &["KEYWORDS=~amd64"]).unwrap();
+        temp.create_ebuild("cat/d-3", &["KEYWORDS=~amd64"]).unwrap();
+        git_commit(temp.path(), 60);
+
+        // recently added versions aren't candidates
+        temp.create_ebuild("cat/d-4", &["KEYWORDS=~amd64"]).unwrap();
+        temp.create_ebuild("cat/e-1", &["KEYWORDS=amd64"]).unwrap();
+        temp.create_ebuild("cat/e-2", &["KEYWORDS=~amd64"]).unwrap();
+        git_commit(temp.path(), 0);
+
+        // default stabilization time
+        let scanner = Scanner::new().reports([CHECK]);
+        let expected = [
+            r#"{"kind":"StableRequest","scope":{"Version":["cat/a-2",null]},"message":"amd64, arm64"}"#,
+            r#"{"kind":"StableRequest","scope":{"Version":["cat/b-2",null]},"message":"amd64"}"#,
+            r#"{"kind":"StableRequest","scope":{"Version":["cat/d-3",null]},"message":"amd64"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // custom stabilization time
+        let custom = Scanner::new().reports([CHECK]).stable_time(90);
+        assert_temp_repo_reports(&custom, &temp, []);
+
+        // fixed
+        temp.create_ebuild("cat/a-2", &["KEYWORDS=amd64 arm64 ~x86", "RDEPEND=dev/stable"])
+            .unwrap();
+        let data = [
+            "KEYWORDS=amd64 ~arm64",
+            "IUSE=test",
+            "RDEPEND=|| ( dev/nonexistent dev/stable ) test? ( dev/unstable ) !dev/unstable",
+        ];
+        temp.create_ebuild("cat/b-2", &data).unwrap();
+        temp.create_ebuild("cat/d-3", &["KEYWORDS=amd64"]).unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
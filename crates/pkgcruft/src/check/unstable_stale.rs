@@ -0,0 +1,111 @@
+use indexmap::IndexSet;
+use itertools::Itertools;
+use pkgcraft::dep::Cpn;
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::keyword::{Arch, KeywordStatus::Unstable};
+
+use crate::report::ReportKind::UnstableStale;
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgSetCheck;
+
+pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgSetCheck + 'static {
+    Check {
+        stable: run
+            .repo
+            .metadata()
+            .arches_desc()
+            .get("stable")
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+struct Check {
+    stable: IndexSet<Arch>,
+}
+
+super::register!(Check, super::Check::UnstableStale);
+
+impl EbuildPkgSetCheck for Check {
+    fn run(&self, cpn: &Cpn, pkgs: &[EbuildPkg], run: &ScannerRun) {
+        let Some(history) = run.history() else {
+            return;
+        };
+
+        // live ebuilds are never stabilized
+        let Some(pkg) = pkgs.iter().rev().find(|x| !x.live()) else {
+            return;
+        };
+
+        // select keywords allowed stable in the repo
+        let keywords: Vec<_> = pkg
+            .keywords()
+            .iter()
+            .filter(|k| self.stable.contains(k.arch()))
+            .collect();
+
+        if keywords.is_empty() || keywords.iter().any(|k| k.status() != Unstable) {
+            return;
+        }
+
+        if let Some(days) = history.days(pkg)
+            && days >= run.stable_time
+        {
+            let arches = keywords.iter().map(|k| k.arch()).sorted().join(", ");
+            UnstableStale
+                .package(cpn)
+                .message(format!("{}: unstable for {days} days: {arches}", pkg.version()))
+                .report(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::{assert_temp_repo_reports, git_commit};
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("arch.list"), "amd64\narm64\n").unwrap();
+        fs::write(path.join("arches.desc"), "amd64 stable\narm64 testing\n").unwrap();
+
+        // stale unstable-only package
+        temp.create_ebuild("cat/a-1", &["KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+        // older version is stale but newest was recently added
+        temp.create_ebuild("cat/b-1", &["KEYWORDS=~amd64"]).unwrap();
+        // stable keywords exist for all stable arches
+        temp.create_ebuild("cat/c-1", &["KEYWORDS=amd64 ~arm64"])
+            .unwrap();
+        git_commit(temp.path(), 60);
+        temp.create_ebuild("cat/b-2", &["KEYWORDS=~amd64"]).unwrap();
+        git_commit(temp.path(), 0);
+
+        // default stabilization time
+        let scanner = Scanner::new().reports([CHECK]);
+        let expected = [
+            r#"{"kind":"UnstableStale","scope":{"Package":"cat/a"},"message":"1: unstable for 60 days: amd64"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // custom stabilization time
+        let custom = Scanner::new().reports([CHECK]).stable_time(90);
+        assert_temp_repo_reports(&custom, &temp, []);
+
+        // fixed
+        temp.create_ebuild("cat/a-1", &["KEYWORDS=amd64 ~arm64"])
+            .unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
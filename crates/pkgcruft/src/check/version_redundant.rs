@@ -0,0 +1,93 @@
+use pkgcraft::dep::Cpn;
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::keyword::KeywordStatus::Disabled;
+
+use crate::report::ReportKind::VersionRedundant;
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgSetCheck;
+
+pub(super) fn create() -> impl EbuildPkgSetCheck {
+    Check
+}
+
+struct Check;
+
+super::register!(Check, super::Check::VersionRedundant);
+
+impl EbuildPkgSetCheck for Check {
+    fn run(&self, _cpn: &Cpn, pkgs: &[EbuildPkg], run: &ScannerRun) {
+        // live ebuilds neither cover nor are covered by other versions
+        let pkgs: Vec<_> = pkgs.iter().filter(|x| !x.live()).collect();
+
+        for (i, pkg) in pkgs.iter().enumerate() {
+            let keywords: Vec<_> = pkg
+                .keywords()
+                .iter()
+                .filter(|k| k.status() != Disabled)
+                .collect();
+
+            // skip packages without enabled keywords
+            if keywords.is_empty() {
+                continue;
+            }
+
+            // stable keywords require stable coverage while unstable ones accept either
+            let newer = pkgs[i + 1..]
+                .iter()
+                .filter(|x| x.slot() == pkg.slot())
+                .find(|x| {
+                    keywords.iter().all(|k| {
+                        x.keywords()
+                            .iter()
+                            .any(|n| n.arch() == k.arch() && n.status() >= k.status())
+                    })
+                });
+
+            if let Some(newer) = newer {
+                VersionRedundant
+                    .version(*pkg)
+                    .message(format!("keywords covered by {}", newer.version()))
+                    .report(run);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        temp.create_ebuild("cat/pkg-0", &["KEYWORDS="]).unwrap();
+        temp.create_ebuild("cat/pkg-1", &["KEYWORDS=amd64 ~arm64 -x86"])
+            .unwrap();
+        temp.create_ebuild("cat/pkg-2", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+        temp.create_ebuild("cat/pkg-3", &["KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+        temp.create_ebuild("cat/pkg-4", &["SLOT=1", "KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+        temp.create_ebuild("cat/pkg-9999", &["PROPERTIES=live", "KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+
+        let expected = [
+            r#"{"kind":"VersionRedundant","scope":{"Version":["cat/pkg-1",null]},"message":"keywords covered by 2"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        temp.create_ebuild("cat/pkg-1", &["KEYWORDS=amd64 ~arm64 x86"])
+            .unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
@@ -0,0 +1,252 @@
+use dashmap::DashMap;
+use indexmap::{IndexMap, IndexSet};
+use itertools::Itertools;
+use pkgcraft::dep::{Dep, Dependency};
+use pkgcraft::pkg::Package;
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::pkg::ebuild::keyword::{Arch, Keyword, KeywordStatus};
+use pkgcraft::repo::PkgRepository;
+use pkgcraft::repo::ebuild::profile::{Profile, ProfileStatus};
+use tracing::warn;
+
+use crate::report::ReportKind::{DependencyUnresolvable, DependencyUnresolvableDev};
+use crate::scan::ScannerRun;
+
+use super::EbuildPkgCheck;
+
+pub(super) fn create(run: &ScannerRun) -> impl EbuildPkgCheck + 'static {
+    let profiles = run
+        .repo
+        .metadata()
+        .profiles_desc()
+        .iter()
+        .filter(|x| x.status() != ProfileStatus::Exp)
+        .filter_map(|x| match Profile::load(&run.repo, x.path()) {
+            Ok(profile) => Some((x.arch().clone(), x.status(), profile)),
+            Err(e) => {
+                warn!("{CHECK}: skipping profile: {}: {e}", x.path());
+                None
+            }
+        })
+        .collect();
+
+    Check {
+        profiles,
+        cache: Default::default(),
+    }
+}
+
+struct Check {
+    profiles: Vec<(Arch, ProfileStatus, Profile)>,
+    cache: DashMap<(usize, Keyword, Dep), bool>,
+}
+
+super::register!(Check, super::Check::Visibility);
+
+/// Profile state used to evaluate the dependencies of a package.
+struct State<'a> {
+    id: usize,
+    profile: &'a Profile,
+    keyword: &'a Keyword,
+    iuse: &'a IndexSet<String>,
+    masked: IndexSet<String>,
+    forced: IndexSet<String>,
+}
+
+impl Check {
+    /// Determine if a dependency is satisfiable for a profile and keyword.
+    ///
+    /// Stable keywords require stable dependencies while unstable keywords accept
+    /// either. USE dependencies are ignored.
+    fn visible(&self, dep: &Dep, state: &State, run: &ScannerRun) -> bool {
+        let dep = dep.no_use_deps().into_owned();
+        let key = (state.id, state.keyword.clone(), dep);
+        if let Some(value) = self.cache.get(&key) {
+            return *value;
+        }
+
+        let (_, keyword, dep) = &key;
+        let visible = run.repo.trees().any(|repo| {
+            repo.iter_restrict(dep)
+                .filter_map(Result::ok)
+                .filter(|pkg| !state.profile.masked(pkg))
+                .any(|pkg| {
+                    pkg.keywords()
+                        .iter()
+                        .any(|k| k.arch() == keyword.arch() && k.status() >= keyword.status())
+                })
+        });
+
+        self.cache.insert(key, visible);
+        visible
+    }
+
+    /// Return the unresolvable dependencies for a dependency tree node.
+    fn unresolved(
+        &self,
+        dep: &Dependency<&Dep>,
+        state: &State,
+        run: &ScannerRun,
+    ) -> Vec<String> {
+        match dep {
+            Dependency::Enabled(x) => {
+                // blockers don't pull in packages
+                if x.blocker().is_none() && !self.visible(x, state, run) {
+                    vec![x.to_string()]
+                } else {
+                    vec![]
+                }
+            }
+            Dependency::AllOf(deps) => deps
+                .iter()
+                .flat_map(|x| self.unresolved(x, state, run))
+                .collect(),
+            Dependency::AnyOf(deps) => {
+                // any-of groups only require a single resolvable alternative
+                if deps
+                    .iter()
+                    .any(|x| self.unresolved(x, state, run).is_empty())
+                {
+                    vec![]
+                } else {
+                    vec![dep.to_string()]
+                }
+            }
+            Dependency::Conditional(use_dep, deps) => {
+                // skip conditionals that can't be satisfied under the profile
+                let flag = use_dep.flag();
+                let disabled = if use_dep.enabled() {
+                    state.masked.contains(flag) || !state.iuse.contains(flag)
+                } else {
+                    state.forced.contains(flag)
+                };
+
+                if disabled {
+                    vec![]
+                } else {
+                    deps.iter()
+                        .flat_map(|x| self.unresolved(x, state, run))
+                        .map(|x| format!("{use_dep} ( {x} )"))
+                        .collect()
+                }
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl EbuildPkgCheck for Check {
+    fn run(&self, pkg: &EbuildPkg, run: &ScannerRun) {
+        let mut failures = IndexMap::<_, Vec<_>>::new();
+
+        for (id, (arch, status, profile)) in self.profiles.iter().enumerate() {
+            // only check profiles for arches the package is keyworded for
+            let Some(keyword) = pkg
+                .keywords()
+                .iter()
+                .find(|k| k.arch() == arch && k.status() != KeywordStatus::Disabled)
+            else {
+                continue;
+            };
+
+            if profile.masked(pkg) {
+                continue;
+            }
+
+            let state = State {
+                id,
+                profile,
+                keyword,
+                iuse: pkg.iuse_effective(),
+                masked: profile.use_masked(pkg),
+                forced: profile.use_forced(pkg),
+            };
+
+            let kind = match status {
+                ProfileStatus::Stable => DependencyUnresolvable,
+                _ => DependencyUnresolvableDev,
+            };
+
+            for key in pkg.eapi().dep_keys().iter().copied() {
+                for dep in &pkg.dependencies([key]) {
+                    for x in self.unresolved(dep, &state, run) {
+                        failures
+                            .entry((kind, key, keyword, x))
+                            .or_default()
+                            .push(profile.path());
+                    }
+                }
+            }
+        }
+
+        for ((kind, key, keyword, dep), profiles) in failures {
+            let profiles = profiles.iter().unique().join(", ");
+            kind.version(pkg)
+                .message(format!("{key}: {dep} ({keyword}): {profiles}"))
+                .report(run);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pkgcraft::repo::ebuild::EbuildRepoBuilder;
+
+    use crate::scan::Scanner;
+    use crate::test::assert_temp_repo_reports;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let scanner = Scanner::new().reports([CHECK]);
+        let mut temp = EbuildRepoBuilder::new().build().unwrap();
+        let path = temp.path().join("profiles");
+        fs::write(path.join("arch.list"), "amd64\narm64\n").unwrap();
+        let data = indoc::indoc! {"
+            amd64 default/amd64 stable
+            arm64 default/arm64 dev
+            arm64 default/arm64/exp exp
+        "};
+        fs::write(path.join("profiles.desc"), data).unwrap();
+        fs::write(path.join("package.mask"), "dev/masked\n").unwrap();
+        for (arch, dir) in [("amd64", "default/amd64"), ("arm64", "default/arm64")] {
+            fs::create_dir_all(path.join(dir)).unwrap();
+            fs::write(path.join(dir).join("make.defaults"), format!("ARCH={arch}\n")).unwrap();
+        }
+        fs::write(path.join("default/amd64/use.mask"), "masked\n").unwrap();
+
+        // dependencies
+        temp.create_ebuild("dev/stable-1", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+        temp.create_ebuild("dev/unstable-1", &["KEYWORDS=~amd64 ~arm64"])
+            .unwrap();
+        temp.create_ebuild("dev/masked-1", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+
+        // package stable on amd64 and unstable on arm64
+        let data = [
+            "KEYWORDS=amd64 ~arm64",
+            "IUSE=masked test",
+            "RDEPEND=dev/stable dev/unstable masked? ( dev/masked )",
+            "DEPEND=test? ( dev/masked ) || ( dev/masked dev/stable ) !dev/masked",
+        ];
+        temp.create_ebuild("cat/pkg-1", &data).unwrap();
+
+        let expected = [
+            r#"{"kind":"DependencyUnresolvable","scope":{"Version":["cat/pkg-1",null]},"message":"DEPEND: test? ( dev/masked ) (amd64): default/amd64"}"#,
+            r#"{"kind":"DependencyUnresolvable","scope":{"Version":["cat/pkg-1",null]},"message":"RDEPEND: dev/unstable (amd64): default/amd64"}"#,
+            r#"{"kind":"DependencyUnresolvableDev","scope":{"Version":["cat/pkg-1",null]},"message":"DEPEND: test? ( dev/masked ) (~arm64): default/arm64"}"#,
+            r#"{"kind":"DependencyUnresolvableDev","scope":{"Version":["cat/pkg-1",null]},"message":"RDEPEND: masked? ( dev/masked ) (~arm64): default/arm64"}"#,
+        ];
+        assert_temp_repo_reports(&scanner, &temp, expected);
+
+        // fixed
+        fs::write(path.join("package.mask"), "").unwrap();
+        temp.create_ebuild("dev/unstable-1", &["KEYWORDS=amd64 arm64"])
+            .unwrap();
+        assert_temp_repo_reports(&scanner, &temp, []);
+    }
+}
@@ -13,6 +13,8 @@ pub enum Error {
     CheckInit(Check, String),
     #[error("{0}")]
     IO(String),
+    #[error("git error: {0}")]
+    Git(String),
     #[error("{0}")]
     Pkgcraft(#[from] pkgcraft::Error),
 }
@@ -22,3 +24,9 @@ impl From<io::Error> for Error {
         Error::IO(format!("{e}: {}", e.kind()))
     }
 }
+
+impl From<git2::Error> for Error {
+    fn from(e: git2::Error) -> Self {
+        Error::Git(e.message().to_string())
+    }
+}
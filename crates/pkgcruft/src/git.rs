@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use git2::{Delta, DiffOptions, Repository, Sort};
+use pkgcraft::pkg::ebuild::EbuildPkg;
+use pkgcraft::repo::{EbuildRepo, PkgRepository};
+
+use crate::Error;
+
+/// Number of seconds in a day.
+const DAY: i64 = 86400;
+
+/// Ebuild addition history for a repo pulled from its git log.
+#[derive(Debug)]
+pub(crate) struct History {
+    added: HashMap<Utf8PathBuf, i64>,
+    now: i64,
+}
+
+impl History {
+    /// Load the ebuild addition history for a repo.
+    ///
+    /// Commits are walked from HEAD towards the root, only tracking the most recent
+    /// addition of each existing ebuild file so re-added ebuilds use their latest time.
+    /// The walk stops once all existing ebuilds have been found.
+    pub(crate) fn load(repo: &EbuildRepo) -> crate::Result<Self> {
+        let git = Repository::discover(repo.path())?;
+        let workdir = git
+            .workdir()
+            .and_then(Utf8Path::from_path)
+            .ok_or_else(|| Error::Git(format!("invalid git workdir: {repo}")))?;
+        let prefix = repo
+            .path()
+            .canonicalize_utf8()?
+            .strip_prefix(workdir.canonicalize_utf8()?)
+            .map(|x| x.to_path_buf())
+            .unwrap_or_default();
+
+        let mut opts = DiffOptions::new();
+        opts.pathspec(prefix.join("*.ebuild").as_str());
+
+        let mut walk = git.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+
+        let mut pending: HashSet<_> = repo.iter_cpv().map(|x| x.relpath()).collect();
+        let mut added = HashMap::new();
+        for oid in walk {
+            if pending.is_empty() {
+                break;
+            }
+
+            let commit = git.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let parent = commit.parents().next().map(|x| x.tree()).transpose()?;
+            let diff = git.diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(&mut opts))?;
+            for delta in diff.deltas().filter(|x| x.status() == Delta::Added) {
+                if let Some(path) = delta.new_file().path().and_then(Utf8Path::from_path)
+                    && let Ok(relpath) = path.strip_prefix(&prefix)
+                    && let Some(relpath) = pending.take(relpath)
+                {
+                    added.insert(relpath, commit.time().seconds());
+                }
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs() as i64)
+            .unwrap_or_default();
+
+        Ok(Self { added, now })
+    }
+
+    /// Return the number of days since a package was added, if it exists in the history.
+    pub(crate) fn days(&self, pkg: &EbuildPkg) -> Option<u64> {
+        self.added
+            .get(&pkg.relpath())
+            .map(|time| ((self.now - time) / DAY).max(0) as u64)
+    }
+}
@@ -50,12 +50,12 @@ fn pkg_producer(
     finish_tx: Sender<(Check, Option<Target>)>,
 ) -> thread::JoinHandle<()> {
     thread::spawn(move || {
-        // run repo checks in parallel
-        for check in run
-            .checks
-            .iter()
-            .filter(|c| c.sources().contains(&SourceKind::Repo))
-        {
+        // run repo, eclass, and profile checks in parallel
+        for check in run.checks.iter().filter(|c| {
+            c.sources().contains(&SourceKind::Repo)
+                || c.sources().contains(&SourceKind::Eclass)
+                || c.sources().contains(&SourceKind::Profile)
+        }) {
             tx.send((Some(*check), Target::Repo, 0)).ok();
         }
 
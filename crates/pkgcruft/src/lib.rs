@@ -1,5 +1,6 @@
 pub mod check;
 pub mod error;
+mod git;
 pub mod ignore;
 pub mod iter;
 pub mod report;
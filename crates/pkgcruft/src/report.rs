@@ -397,12 +397,21 @@ pub enum ReportKind {
     /// Ebuild uses a bash builtin as an external command.
     Builtin,
 
+    /// Ebuild uses `cd` without checking for failure via `|| die`.
+    CdUnchecked,
+
     /// An EAPI command uses `|| die` which is unneeded.
     CommandDieUnneeded,
 
     /// An EAPI command is used in an invalid scope.
     CommandScopeInvalid,
 
+    /// Ebuild runs external commands via global scope command substitution.
+    ///
+    /// Global scope is executed during metadata generation so the results depend on
+    /// the system running the generation, breaking metadata caching.
+    CommandSubstitutionGlobal,
+
     /// Package dependency flagged as deprecated by the repo.
     DependencyDeprecated,
 
@@ -415,6 +424,26 @@ pub enum ReportKind {
     /// Dependency is missing a slot.
     DependencySlotMissing,
 
+    /// Dependency can't be resolved for a stable profile.
+    ///
+    /// Dependencies are evaluated under each profile's USE, mask, and keyword
+    /// settings where stable keywords require stable dependencies.
+    DependencyUnresolvable,
+
+    /// Dependency can't be resolved for a development profile.
+    DependencyUnresolvableDev,
+
+    /// Package dependency with a USE dependency missing from matching package IUSE.
+    ///
+    /// USE dependencies with (+) or (-) defaults are ignored.
+    DependencyUseMissing,
+
+    /// Ebuild distfile name lacking package identification.
+    ///
+    /// Generic names such as version-only archives from upstream tags should be
+    /// renamed via `->` in SRC_URI to avoid collisions in distfile mirrors.
+    DistfileGeneric,
+
     /// Package has a banned EAPI.
     EapiBanned,
 
@@ -442,15 +471,45 @@ pub enum ReportKind {
     /// Multiple ebuild versions for a package are equivalent, e.g. 0 and 0-r0.
     EbuildVersionsEqual,
 
+    /// Eclass documentation that is invalid.
+    ///
+    /// This includes unknown, duplicate, or misplaced tags in addition to missing
+    /// required tags such as @MAINTAINER and @BLURB.
+    EclassDocInvalid,
+
+    /// Eclass lacking documentation.
+    EclassDocMissing,
+
+    /// Eclass @SUPPORTED_EAPIS documentation not matching its EAPI guard.
+    EclassEapisMismatch,
+
+    /// Eclass exported function lacking documentation.
+    EclassFunctionUndocumented,
+
+    /// Eclass @ECLASS documentation name not matching its file name.
+    EclassNameMismatch,
+
+    /// Eclass with invalid bash syntax.
+    EclassSyntaxError,
+
     /// Eclass that is unused in the parent repository.
     EclassUnused,
 
+    /// Eclass exported variable lacking documentation.
+    EclassVariableUndocumented,
+
     /// Usage of a nonexistent file in $FILESDIR.
     FileUnknown,
 
     /// Package has unused files in $FILESDIR.
     FilesUnused,
 
+    /// Ebuild calls a deprecated eclass function.
+    FunctionDeprecated,
+
+    /// Ebuild calls an internal eclass function.
+    FunctionInternal,
+
     /// File has an invalid copyright and/or license header.
     HeaderInvalid,
 
@@ -463,6 +522,12 @@ pub enum ReportKind {
     /// Repo has an unused ignore directive.
     IgnoreUnused,
 
+    /// Ebuild uses functionality from an indirectly inherited eclass.
+    InheritIndirect,
+
+    /// Ebuild directly inherits an unused eclass.
+    InheritUnused,
+
     /// Ebuild has an invalid USE flag.
     IuseInvalid,
 
@@ -490,6 +555,15 @@ pub enum ReportKind {
     /// Package only has live ebuilds.
     LiveOnly,
 
+    /// Package maintainer with an invalid email address.
+    MaintainerEmailInvalid,
+
+    /// Package without any maintainers.
+    MaintainerMissing,
+
+    /// Package maintainer with an unknown type.
+    MaintainerTypeUnknown,
+
     /// Package manifest has a matching hash with a different file name.
     ManifestCollide,
 
@@ -502,6 +576,21 @@ pub enum ReportKind {
     /// Ebuild fails during metadata generation.
     MetadataError,
 
+    /// Package metadata.xml file that is malformed or doesn't match the schema.
+    MetadataXmlInvalid,
+
+    /// Package missing a metadata.xml file.
+    MetadataXmlMissing,
+
+    /// Package metadata.xml slot descriptions for nonexistent slots.
+    MetadataXmlSlotUnknown,
+
+    /// Ebuild SRC_URI matches a third party mirror without using mirror://.
+    MirrorMissing,
+
+    /// Ebuild SRC_URI uses a mirror missing from profiles/thirdpartymirrors.
+    MirrorUnknown,
+
     /// Repo has unused mirrors.
     MirrorsUnused,
 
@@ -513,18 +602,66 @@ pub enum ReportKind {
     /// Repo has unused profiles/package.deprecated entry.
     PackageDeprecatedUnused,
 
+    /// Package explicitly marked as needing a maintainer.
+    PackageOrphaned,
+
     /// Overlay package matches the name of a package from a parent repo.
     PackageOverride,
 
+    /// Ebuild path variable is subject to word splitting.
+    ///
+    /// This includes unquoted usage of ${D}, ${ED}, ${S}, ${WORKDIR}, ${T}, and
+    /// ${FILESDIR} in command arguments, arrays, and for loops.
+    PathUnquoted,
+
     /// An ebuild phase is directly called.
     PhaseCall,
 
+    /// Ebuild pipeline using `|| die` ignores failures from all but the final command.
+    PipelineUnchecked,
+
+    /// Profile directory not listed in profiles.desc.
+    ///
+    /// This is reported for profile directories with a parent file that aren't
+    /// used as parents by any other profile.
+    ProfileDescMissing,
+
+    /// Profile file entry that is invalid.
+    ProfileEntryInvalid,
+
+    /// Profile file references a USE flag that doesn't exist.
+    ProfileFlagUnknown,
+
+    /// Profile file entry matching no versions of an existing package.
+    ProfilePackageObsolete,
+
+    /// Profile file references a package that doesn't exist.
+    ProfilePackageUnknown,
+
+    /// Profile parent file entry that is invalid.
+    ProfileParentInvalid,
+
+    /// Profile directory that isn't used.
+    ///
+    /// This is reported for profile directories lacking a parent file that
+    /// aren't listed in profiles.desc or used as parents by any other profile.
+    ProfileUnused,
+
     /// Ebuild has invalid PROPERTIES.
     PropertiesInvalid,
 
     /// Ebuild can support newer python version(s).
     PythonUpdate,
 
+    /// Package metadata.xml remote-id with a malformed value.
+    RemoteIdInvalid,
+
+    /// Ebuild SRC_URI or HOMEPAGE references an upstream lacking a metadata.xml remote-id.
+    RemoteIdMissing,
+
+    /// Package metadata.xml remote-id with an unknown type.
+    RemoteIdUnknown,
+
     /// Repo has unused profiles/categories entry.
     RepoCategoriesUnused,
 
@@ -543,9 +680,28 @@ pub enum ReportKind {
     /// Ebuild can support newer ruby version(s).
     RubyUpdate,
 
+    /// Package metadata.xml stabilize-allarches usage that is invalid.
+    ///
+    /// This is reported for packages lacking keywords or versions restricted to
+    /// specific arches via `-*` keywords.
+    StabilizeAllarchesInvalid,
+
+    /// Ebuild that is a candidate for stabilization.
+    ///
+    /// This uses git history to find unstable versions newer than the current stable
+    /// version that have exceeded the configured stabilization time with all
+    /// dependencies stable on the listed arches.
+    StableRequest,
+
     /// Package only has unstable keywords.
     UnstableOnly,
 
+    /// Package with a newest version that has been unstable-only for too long.
+    ///
+    /// This uses git history to determine when the version was added and is
+    /// reported when it exceeds the configured stabilization time.
+    UnstableStale,
+
     /// Ebuild has an unsupported or invalid URI.
     UriInvalid,
 
@@ -573,6 +729,9 @@ pub enum ReportKind {
     /// An EAPI variable is used in an invalid scope.
     VariableScopeInvalid,
 
+    /// Ebuild with keywords fully covered by a newer version in the same slot.
+    VersionRedundant,
+
     /// Whitespace usage that is invalid.
     WhitespaceInvalid,
 
@@ -625,6 +784,24 @@ impl ReportKind {
         })
     }
 
+    /// Create an eclass scope report.
+    pub(crate) fn eclass<S: fmt::Display>(self, name: S) -> ReportBuilder {
+        ReportBuilder(Report {
+            kind: self,
+            scope: ReportScope::Eclass(name.to_string(), None),
+            message: Default::default(),
+        })
+    }
+
+    /// Create a profile scope report for a path relative to the profiles directory.
+    pub(crate) fn profile<P: AsRef<Utf8Path>>(self, path: P) -> ReportBuilder {
+        ReportBuilder(Report {
+            kind: self,
+            scope: ReportScope::Profile(path.as_ref().to_string(), None),
+            message: Default::default(),
+        })
+    }
+
     /// Create a report using a scope.
     pub(crate) fn in_scope(self, scope: ReportScope) -> ReportBuilder {
         ReportBuilder(Report {
@@ -640,12 +817,18 @@ impl ReportKind {
         match self {
             Self::ArchesUnused => Warning,
             Self::Builtin => Error,
+            Self::CdUnchecked => Warning,
             Self::CommandDieUnneeded => Warning,
             Self::CommandScopeInvalid => Error,
+            Self::CommandSubstitutionGlobal => Warning,
             Self::DependencyDeprecated => Warning,
             Self::DependencyInvalid => Error,
             Self::DependencyRevisionMissing => Warning,
             Self::DependencySlotMissing => Warning,
+            Self::DependencyUnresolvable => Error,
+            Self::DependencyUnresolvableDev => Warning,
+            Self::DependencyUseMissing => Error,
+            Self::DistfileGeneric => Warning,
             Self::EapiBanned => Error,
             Self::EapiDeprecated => Warning,
             Self::EapiFormat => Style,
@@ -654,13 +837,24 @@ impl ReportKind {
             Self::EapiUnused => Warning,
             Self::EbuildNameInvalid => Error,
             Self::EbuildVersionsEqual => Error,
+            Self::EclassDocInvalid => Warning,
+            Self::EclassDocMissing => Warning,
+            Self::EclassEapisMismatch => Warning,
+            Self::EclassFunctionUndocumented => Warning,
+            Self::EclassNameMismatch => Error,
+            Self::EclassSyntaxError => Critical,
             Self::EclassUnused => Warning,
+            Self::EclassVariableUndocumented => Warning,
             Self::FileUnknown => Error,
             Self::FilesUnused => Warning,
+            Self::FunctionDeprecated => Warning,
+            Self::FunctionInternal => Warning,
             Self::HeaderInvalid => Error,
             Self::HomepageInvalid => Error,
             Self::IgnoreInvalid => Warning,
             Self::IgnoreUnused => Warning,
+            Self::InheritIndirect => Warning,
+            Self::InheritUnused => Warning,
             Self::IuseInvalid => Error,
             Self::KeywordsDropped => Warning,
             Self::KeywordsLive => Warning,
@@ -671,23 +865,47 @@ impl ReportKind {
             Self::LicensesUnused => Warning,
             Self::LiveOnly => Warning,
             Self::ManifestInvalid => Error,
+            Self::MaintainerEmailInvalid => Error,
+            Self::MaintainerMissing => Warning,
+            Self::MaintainerTypeUnknown => Error,
             Self::ManifestCollide => Warning,
             Self::ManifestConflict => Error,
             Self::MetadataError => Critical,
+            Self::MetadataXmlInvalid => Error,
+            Self::MetadataXmlMissing => Error,
+            Self::MetadataXmlSlotUnknown => Warning,
+            Self::MirrorMissing => Warning,
+            Self::MirrorUnknown => Error,
             Self::MirrorsUnused => Warning,
             Self::Optfeature => Warning,
             Self::PackageDeprecatedUnused => Warning,
+            Self::PackageOrphaned => Info,
             Self::PackageOverride => Warning,
+            Self::PathUnquoted => Warning,
             Self::PhaseCall => Error,
+            Self::PipelineUnchecked => Warning,
+            Self::ProfileDescMissing => Warning,
+            Self::ProfileEntryInvalid => Error,
+            Self::ProfileFlagUnknown => Warning,
+            Self::ProfilePackageObsolete => Warning,
+            Self::ProfilePackageUnknown => Warning,
+            Self::ProfileParentInvalid => Error,
+            Self::ProfileUnused => Warning,
             Self::PropertiesInvalid => Error,
             Self::PythonUpdate => Info,
+            Self::RemoteIdInvalid => Error,
+            Self::RemoteIdMissing => Info,
+            Self::RemoteIdUnknown => Error,
             Self::RepoCategoriesUnused => Warning,
             Self::RepoCategoryEmpty => Warning,
             Self::RepoPackageEmpty => Warning,
             Self::RestrictInvalid => Error,
             Self::RestrictMissing => Warning,
             Self::RubyUpdate => Info,
+            Self::StabilizeAllarchesInvalid => Warning,
+            Self::StableRequest => Info,
             Self::UnstableOnly => Info,
+            Self::UnstableStale => Info,
             Self::UriInvalid => Error,
             Self::UseGlobalUnused => Warning,
             Self::UseLocalDescMissing => Error,
@@ -696,6 +914,7 @@ impl ReportKind {
             Self::UseLocalUnused => Warning,
             Self::VariableOrder => Style,
             Self::VariableScopeInvalid => Error,
+            Self::VersionRedundant => Info,
             Self::WhitespaceInvalid => Warning,
             Self::WhitespaceUnneeded => Style,
         }
@@ -709,12 +928,18 @@ impl ReportKind {
         match self {
             Self::ArchesUnused => Scope::Repo,
             Self::Builtin => Scope::Version,
+            Self::CdUnchecked => Scope::Version,
             Self::CommandDieUnneeded => Scope::Version,
             Self::CommandScopeInvalid => Scope::Version,
+            Self::CommandSubstitutionGlobal => Scope::Version,
             Self::DependencyDeprecated => Scope::Version,
             Self::DependencyInvalid => Scope::Version,
             Self::DependencyRevisionMissing => Scope::Version,
             Self::DependencySlotMissing => Scope::Version,
+            Self::DependencyUnresolvable => Scope::Version,
+            Self::DependencyUnresolvableDev => Scope::Version,
+            Self::DependencyUseMissing => Scope::Version,
+            Self::DistfileGeneric => Scope::Version,
             Self::EapiBanned => Scope::Version,
             Self::EapiDeprecated => Scope::Version,
             Self::EapiFormat => Scope::Version,
@@ -723,13 +948,24 @@ impl ReportKind {
             Self::EapiUnused => Scope::Repo,
             Self::EbuildNameInvalid => Scope::Package,
             Self::EbuildVersionsEqual => Scope::Package,
+            Self::EclassDocInvalid => Scope::Repo,
+            Self::EclassDocMissing => Scope::Repo,
+            Self::EclassEapisMismatch => Scope::Repo,
+            Self::EclassFunctionUndocumented => Scope::Repo,
+            Self::EclassNameMismatch => Scope::Repo,
+            Self::EclassSyntaxError => Scope::Repo,
             Self::EclassUnused => Scope::Repo,
+            Self::EclassVariableUndocumented => Scope::Repo,
             Self::FileUnknown => Scope::Version,
             Self::FilesUnused => Scope::Package,
+            Self::FunctionDeprecated => Scope::Version,
+            Self::FunctionInternal => Scope::Version,
             Self::HeaderInvalid => Scope::Version,
             Self::HomepageInvalid => Scope::Version,
             Self::IgnoreInvalid => Scope::Version,
             Self::IgnoreUnused => Scope::Version,
+            Self::InheritIndirect => Scope::Version,
+            Self::InheritUnused => Scope::Version,
             Self::IuseInvalid => Scope::Version,
             Self::KeywordsDropped => Scope::Version,
             Self::KeywordsLive => Scope::Version,
@@ -740,23 +976,47 @@ impl ReportKind {
             Self::LicensesUnused => Scope::Repo,
             Self::LiveOnly => Scope::Package,
             Self::ManifestInvalid => Scope::Package,
+            Self::MaintainerEmailInvalid => Scope::Package,
+            Self::MaintainerMissing => Scope::Package,
+            Self::MaintainerTypeUnknown => Scope::Package,
             Self::ManifestCollide => Scope::Package,
             Self::ManifestConflict => Scope::Category,
             Self::MetadataError => Scope::Version,
+            Self::MetadataXmlInvalid => Scope::Package,
+            Self::MetadataXmlMissing => Scope::Package,
+            Self::MetadataXmlSlotUnknown => Scope::Package,
+            Self::MirrorMissing => Scope::Version,
+            Self::MirrorUnknown => Scope::Version,
             Self::MirrorsUnused => Scope::Repo,
             Self::Optfeature => Scope::Version,
             Self::PackageDeprecatedUnused => Scope::Repo,
+            Self::PackageOrphaned => Scope::Package,
             Self::PackageOverride => Scope::Package,
+            Self::PathUnquoted => Scope::Version,
             Self::PhaseCall => Scope::Version,
+            Self::PipelineUnchecked => Scope::Version,
+            Self::ProfileDescMissing => Scope::Repo,
+            Self::ProfileEntryInvalid => Scope::Repo,
+            Self::ProfileFlagUnknown => Scope::Repo,
+            Self::ProfilePackageObsolete => Scope::Repo,
+            Self::ProfilePackageUnknown => Scope::Repo,
+            Self::ProfileParentInvalid => Scope::Repo,
+            Self::ProfileUnused => Scope::Repo,
             Self::PropertiesInvalid => Scope::Version,
             Self::PythonUpdate => Scope::Version,
+            Self::RemoteIdInvalid => Scope::Package,
+            Self::RemoteIdMissing => Scope::Version,
+            Self::RemoteIdUnknown => Scope::Package,
             Self::RepoCategoriesUnused => Scope::Repo,
             Self::RepoCategoryEmpty => Scope::Repo,
             Self::RepoPackageEmpty => Scope::Package,
             Self::RestrictInvalid => Scope::Version,
             Self::RestrictMissing => Scope::Version,
             Self::RubyUpdate => Scope::Version,
+            Self::StabilizeAllarchesInvalid => Scope::Package,
+            Self::StableRequest => Scope::Version,
             Self::UnstableOnly => Scope::Package,
+            Self::UnstableStale => Scope::Package,
             Self::UriInvalid => Scope::Version,
             Self::UseGlobalUnused => Scope::Repo,
             Self::UseLocalDescMissing => Scope::Package,
@@ -765,6 +1025,7 @@ impl ReportKind {
             Self::UseLocalUnused => Scope::Package,
             Self::VariableOrder => Scope::Version,
             Self::VariableScopeInvalid => Scope::Version,
+            Self::VersionRedundant => Scope::Version,
             Self::WhitespaceInvalid => Scope::Version,
             Self::WhitespaceUnneeded => Scope::Version,
         }
@@ -791,6 +1052,8 @@ impl ReportKind {
             Self::ManifestConflict => scope == Scope::Repo,
             Self::MirrorsUnused => scope == Scope::Repo,
             Self::PackageDeprecatedUnused => scope == Scope::Repo,
+            Self::ProfileDescMissing => scope == Scope::Repo,
+            Self::ProfileUnused => scope == Scope::Repo,
             Self::RepoCategoryEmpty => scope == Scope::Repo,
             Self::UseGlobalUnused => scope == Scope::Repo,
             _ => false,
@@ -843,7 +1106,10 @@ impl ReportBuilder {
     where
         L: Into<Location>,
     {
-        if let ReportScope::Version(_, location @ None) = &mut self.0.scope {
+        if let ReportScope::Version(_, location @ None)
+        | ReportScope::Eclass(_, location @ None)
+        | ReportScope::Profile(_, location @ None) = &mut self.0.scope
+        {
             *location = Some(value.into());
         } else {
             panic!("invalid report scope: {:?}", self.0.scope);
@@ -865,6 +1131,27 @@ impl ReportBuilder {
 pub struct Location {
     pub line: usize,
     pub column: usize,
+    #[serde(default, skip_serializing_if = "is_unset")]
+    pub end: usize,
+}
+
+/// Return true if a location field is unset.
+fn is_unset(value: &usize) -> bool {
+    *value == 0
+}
+
+impl Location {
+    /// Create a location spanning the columns of a single-line node.
+    ///
+    /// The end column is left unset for nodes spanning multiple lines.
+    pub(crate) fn span(node: &Node<'_>) -> Self {
+        let (start, end) = (node.start_position(), node.end_position());
+        Self {
+            line: start.row + 1,
+            column: start.column + 1,
+            end: if start.row == end.row { end.column } else { 0 },
+        }
+    }
 }
 
 impl fmt::Debug for Location {
@@ -877,7 +1164,11 @@ impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "line {}", self.line)?;
         if self.column > 0 {
-            write!(f, ", column {}", self.column)?;
+            if self.end > self.column {
+                write!(f, ", columns {}-{}", self.column, self.end)?;
+            } else {
+                write!(f, ", column {}", self.column)?;
+            }
         }
         Ok(())
     }
@@ -885,13 +1176,17 @@ impl fmt::Display for Location {
 
 impl From<usize> for Location {
     fn from(value: usize) -> Self {
-        Self { line: value, column: 0 }
+        Self { line: value, column: 0, end: 0 }
     }
 }
 
 impl From<(usize, usize)> for Location {
     fn from(value: (usize, usize)) -> Self {
-        Self { line: value.0, column: value.1 }
+        Self {
+            line: value.0,
+            column: value.1,
+            end: 0,
+        }
     }
 }
 
@@ -900,6 +1195,7 @@ impl From<&Node<'_>> for Location {
         Self {
             line: value.start_position().row + 1,
             column: value.start_position().column + 1,
+            end: 0,
         }
     }
 }
@@ -909,6 +1205,8 @@ pub enum ReportScope {
     Version(Cpv, Option<Location>),
     Package(Cpn),
     Category(String),
+    Eclass(String, Option<Location>),
+    Profile(String, Option<Location>),
     Repo(String),
 }
 
@@ -918,6 +1216,8 @@ impl ReportScope {
             Self::Version(_, _) => Scope::Version,
             Self::Package(_) => Scope::Package,
             Self::Category(_) => Scope::Category,
+            Self::Eclass(_, _) => Scope::Repo,
+            Self::Profile(_, _) => Scope::Repo,
             Self::Repo(_) => Scope::Repo,
         }
     }
@@ -933,6 +1233,8 @@ impl ReportScope {
             Self::Version(cpv, _) => cpv.relpath(),
             Self::Package(cpn) => cpn.to_string().into(),
             Self::Category(category) => category.into(),
+            Self::Eclass(name, _) => format!("eclass/{name}.eclass").into(),
+            Self::Profile(path, _) => format!("profiles/{path}").into(),
             Self::Repo(_) => Default::default(),
         }
     }
@@ -943,6 +1245,16 @@ impl Ord for ReportScope {
         match (self, other) {
             (Self::Repo(v1), Self::Repo(v2)) => v1.cmp(v2),
             (Self::Category(v1), Self::Category(v2)) => v1.cmp(v2),
+            (Self::Eclass(v1, l1), Self::Eclass(v2, l2)) => {
+                v1.cmp(v2).then_with(|| l1.cmp(l2))
+            }
+            (Self::Eclass(..), Self::Profile(..) | Self::Repo(_)) => Ordering::Less,
+            (Self::Profile(..) | Self::Repo(_), Self::Eclass(..)) => Ordering::Greater,
+            (Self::Profile(v1, l1), Self::Profile(v2, l2)) => {
+                v1.cmp(v2).then_with(|| l1.cmp(l2))
+            }
+            (Self::Profile(..), Self::Repo(_)) => Ordering::Less,
+            (Self::Repo(_), Self::Profile(..)) => Ordering::Greater,
             (Self::Package(v1), Self::Package(v2)) => v1.cmp(v2),
             (Self::Version(v1, l1), Self::Version(v2, l2)) => {
                 v1.cmp(v2).then_with(|| l1.cmp(l2))
@@ -974,6 +1286,14 @@ impl fmt::Debug for ReportScope {
             Self::Version(cpv, None) => write!(f, "Version( {cpv} )"),
             Self::Package(cpn) => write!(f, "Package( {cpn} )"),
             Self::Category(cat) => write!(f, "Category( {cat} )"),
+            Self::Eclass(name, Some(location)) => {
+                write!(f, "Eclass( {name}, {location:?} )")
+            }
+            Self::Eclass(name, None) => write!(f, "Eclass( {name} )"),
+            Self::Profile(path, Some(location)) => {
+                write!(f, "Profile( {path}, {location:?} )")
+            }
+            Self::Profile(path, None) => write!(f, "Profile( {path} )"),
             Self::Repo(repo) => write!(f, "Repo( {repo} )"),
         }
     }
@@ -986,6 +1306,12 @@ impl fmt::Display for ReportScope {
             Self::Version(cpv, None) => write!(f, "{cpv}"),
             Self::Package(cpn) => write!(f, "{cpn}"),
             Self::Category(cat) => write!(f, "{cat}/*"),
+            Self::Eclass(name, Some(location)) => {
+                write!(f, "eclass/{name}.eclass, {location}")
+            }
+            Self::Eclass(name, None) => write!(f, "eclass/{name}.eclass"),
+            Self::Profile(path, Some(location)) => write!(f, "profiles/{path}, {location}"),
+            Self::Profile(path, None) => write!(f, "profiles/{path}"),
             Self::Repo(repo) => write!(f, "{repo}"),
         }
     }
@@ -1239,6 +1565,22 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn location() {
+        let location = Location::from(3);
+        assert_eq!(location.to_string(), "line 3");
+        let location = Location::from((3, 5));
+        assert_eq!(location.to_string(), "line 3, column 5");
+        assert_eq!(serde_json::to_string(&location).unwrap(), r#"{"line":3,"column":5}"#);
+
+        // column ranges
+        let location = Location { line: 3, column: 5, end: 10 };
+        assert_eq!(location.to_string(), "line 3, columns 5-10");
+        let s = serde_json::to_string(&location).unwrap();
+        assert_eq!(s, r#"{"line":3,"column":5,"end":10}"#);
+        assert_eq!(serde_json::from_str::<Location>(&s).unwrap(), location);
+    }
+
     #[test]
     fn report_target() {
         let data = test_data();
@@ -167,10 +167,12 @@ impl From<FancyReporter> for Reporter {
 impl FancyReporter {
     fn report<W: Write>(&mut self, report: &Report, output: &mut W) -> crate::Result<()> {
         let scope = report.scope();
-        let key = if let ReportScope::Version(cpv, _) = scope {
-            cpv.cpn().to_string()
-        } else {
-            scope.to_string()
+        let key = match scope {
+            ReportScope::Version(cpv, _) => cpv.cpn().to_string(),
+            ReportScope::Eclass(..) | ReportScope::Profile(..) => {
+                scope.to_relpath().to_string()
+            }
+            _ => scope.to_string(),
         };
 
         if !self
@@ -193,6 +195,10 @@ impl FancyReporter {
             if let Some(value) = location {
                 write!(output, ", {value}")?;
             }
+        } else if let ReportScope::Eclass(_, Some(location))
+        | ReportScope::Profile(_, Some(location)) = scope
+        {
+            write!(output, ": {location}")?;
         }
 
         if let Some(value) = report.message() {
@@ -263,6 +269,18 @@ impl FormatReporter {
             ReportScope::Category(cat) => {
                 attrs.extend([("category".to_string(), cat.to_string())]);
             }
+            ReportScope::Eclass(name, _) => {
+                attrs.extend([
+                    ("path".to_string(), report.scope().to_relpath().to_string()),
+                    ("eclass".to_string(), name.to_string()),
+                ]);
+            }
+            ReportScope::Profile(path, _) => {
+                attrs.extend([
+                    ("path".to_string(), report.scope().to_relpath().to_string()),
+                    ("profile".to_string(), path.to_string()),
+                ]);
+            }
             ReportScope::Repo(repo) => {
                 attrs.extend([("repo".to_string(), repo.to_string())]);
             }
@@ -39,7 +39,9 @@ impl From<&ReportScope> for Target {
             ReportScope::Version(cpv, _) => cpv.cpn().clone().into(),
             ReportScope::Package(cpn) => cpn.clone().into(),
             ReportScope::Category(s) => Target::Category(s.to_string()),
-            ReportScope::Repo(_) => Target::Repo,
+            ReportScope::Eclass(..) | ReportScope::Profile(..) | ReportScope::Repo(_) => {
+                Target::Repo
+            }
         }
     }
 }
@@ -112,6 +114,8 @@ enum CheckRunner {
     Cpn(CpnCheckRunner),
     Cpv(CpvCheckRunner),
     Category(CategoryCheckRunner),
+    Eclass(EclassCheckRunner),
+    Profile(ProfileCheckRunner),
     Repo(RepoCheckRunner),
 }
 
@@ -123,6 +127,8 @@ impl CheckRunner {
             SourceKind::Cpn => Self::Cpn(Default::default()),
             SourceKind::Cpv => Self::Cpv(Default::default()),
             SourceKind::Category => Self::Category(Default::default()),
+            SourceKind::Eclass => Self::Eclass(Default::default()),
+            SourceKind::Profile => Self::Profile(Default::default()),
             SourceKind::Repo => Self::Repo(Default::default()),
         }
     }
@@ -134,6 +140,8 @@ impl CheckRunner {
             Self::Cpn(r) => r.add_check(check, run),
             Self::Cpv(r) => r.add_check(check, run),
             Self::Category(r) => r.add_check(check, run),
+            Self::Eclass(r) => r.add_check(check, run),
+            Self::Profile(r) => r.add_check(check, run),
             Self::Repo(r) => r.add_check(check, run),
         }
     }
@@ -157,6 +165,8 @@ impl CheckRunner {
             (Self::Cpn(r), Target::Cpn(cpn)) => r.run_check(check, cpn, run),
             (Self::Cpv(r), Target::Cpv(cpv)) => r.run_check(check, cpv, run),
             (Self::Category(r), Target::Category(cat)) => r.run_check(check, cat, run),
+            (Self::Eclass(r), Target::Repo) => r.run_check(check, run),
+            (Self::Profile(r), Target::Repo) => r.run_check(check, run),
             (Self::Repo(r), Target::Repo) => r.run_check(check, run),
             _ => (),
         }
@@ -178,6 +188,8 @@ impl CheckRunner {
             Self::Cpn(r) => r.finish_check(check, run),
             Self::Cpv(r) => r.finish_check(check, run),
             Self::Category(r) => r.finish_check(check, run),
+            Self::Eclass(r) => r.finish_check(check, run),
+            Self::Profile(r) => r.finish_check(check, run),
             _ => (),
         }
     }
@@ -479,6 +491,52 @@ impl CategoryCheckRunner {
     }
 }
 
+/// Create check runners for sources iterating over items across an entire repo.
+macro_rules! make_repo_source_check_runner {
+    ($check_runner:ident, $runner:ty, $source:ty) => {
+        #[derive(Default)]
+        struct $check_runner {
+            checks: IndexMap<Check, $runner>,
+            source: std::sync::OnceLock<$source>,
+        }
+
+        impl $check_runner {
+            fn add_check(&mut self, check: Check, run: &ScannerRun) {
+                self.checks.insert(check, check.to_runner(run));
+            }
+
+            fn run_check(&self, check: &Check, run: &ScannerRun) {
+                let runner = self
+                    .checks
+                    .get(check)
+                    .unwrap_or_else(|| unreachable!("unknown check: {check}"));
+                let source = self.source.get_or_init(|| <$source>::new(run));
+                let now = Instant::now();
+                for item in source.iter_restrict(&run.restrict) {
+                    runner.run(&item, run);
+                }
+                *run.stats.entry(*check).or_default() += now.elapsed();
+            }
+
+            fn finish_check(&self, check: &Check, run: &ScannerRun) {
+                let runner = self
+                    .checks
+                    .get(check)
+                    .unwrap_or_else(|| unreachable!("unknown check: {check}"));
+                let now = Instant::now();
+                runner.finish_check(run);
+                *run.stats.entry(*check).or_default() += now.elapsed();
+            }
+        }
+    };
+}
+
+// Check runner for eclass files.
+make_repo_source_check_runner!(EclassCheckRunner, EclassRunner, EclassSource);
+
+// Check runner for profile directories.
+make_repo_source_check_runner!(ProfileCheckRunner, ProfileRunner, ProfileSource);
+
 /// Check runner for repo targets.
 #[derive(Default)]
 struct RepoCheckRunner {
@@ -2,21 +2,27 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use camino::{Utf8Path, Utf8PathBuf};
 use dashmap::DashMap;
 use indexmap::IndexSet;
 use itertools::Itertools;
 use pkgcraft::repo::EbuildRepo;
+use pkgcraft::repo::ebuild::{RevDepCache, RevDepIndex};
 use pkgcraft::restrict::{Restrict, Scope, TryIntoRestrict};
 use pkgcraft::utils::bounded_jobs;
 use tracing::{info, warn};
 
 use crate::check::Check;
 use crate::error::Error;
+use crate::git::History;
 use crate::ignore::Ignore;
 use crate::iter::{ReportIter, ReportSender};
 use crate::report::{Report, ReportKind, ReportSet, ReportTarget};
 use crate::source::PkgFilter;
 
+/// Default number of days before unstable versions are considered for stabilization.
+pub const STABLE_TIME: u64 = 30;
+
 /// Scanner builder.
 #[derive(Debug, Default, Clone)]
 pub struct Scanner {
@@ -26,6 +32,8 @@ pub struct Scanner {
     reports: IndexSet<ReportTarget>,
     exit: IndexSet<ReportSet>,
     filters: IndexSet<PkgFilter>,
+    stable_time: Option<u64>,
+    cache_dir: Option<Utf8PathBuf>,
     failed: Arc<AtomicBool>,
     stats: Arc<DashMap<Check, Duration>>,
 }
@@ -86,6 +94,18 @@ impl Scanner {
         self
     }
 
+    /// Set the number of days before unstable versions are considered for stabilization.
+    pub fn stable_time(mut self, days: u64) -> Self {
+        self.stable_time = Some(days);
+        self
+    }
+
+    /// Set the cache directory used to load persistent repo indexes.
+    pub fn cache_dir<P: AsRef<Utf8Path>>(mut self, path: P) -> Self {
+        self.cache_dir = Some(path.as_ref().to_path_buf());
+        self
+    }
+
     /// Return true if the scanning process failed, false otherwise.
     pub fn failed(&self) -> bool {
         self.failed.load(Ordering::Relaxed)
@@ -185,6 +205,10 @@ pub(crate) struct ScannerRun {
     force: bool,
     pub(crate) jobs: usize,
     pub(crate) filters: IndexSet<PkgFilter>,
+    pub(crate) stable_time: u64,
+    history: OnceLock<Option<History>>,
+    cache_dir: Option<Utf8PathBuf>,
+    revdeps: OnceLock<Option<RevDepIndex>>,
     pub(crate) checks: IndexSet<Check>,
     pub(crate) ignore: Ignore,
     enabled: IndexSet<ReportKind>,
@@ -215,6 +239,10 @@ impl ScannerRun {
             force: scanner.force,
             jobs: bounded_jobs(scanner.jobs),
             filters: scanner.filters.clone(),
+            stable_time: scanner.stable_time.unwrap_or(STABLE_TIME),
+            history: Default::default(),
+            cache_dir: scanner.cache_dir.clone(),
+            revdeps: Default::default(),
             checks: Default::default(),
             ignore: Ignore::new(repo),
             enabled: Default::default(),
@@ -251,6 +279,37 @@ impl ScannerRun {
         }
     }
 
+    /// Return the git history for the target repo, if available.
+    pub(crate) fn history(&self) -> Option<&History> {
+        self.history
+            .get_or_init(|| {
+                History::load(&self.repo)
+                    .map_err(|e| warn!("{}: failed loading git history: {e}", self.repo))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Return the reverse dependencies for the target repo, if a cache directory is set.
+    ///
+    /// Outdated revdeps indexes are only updated in memory.
+    pub(crate) fn revdeps(&self) -> Option<&RevDepCache> {
+        self.revdeps
+            .get_or_init(|| {
+                let path = RevDepIndex::default_path(self.cache_dir.as_ref()?, &self.repo);
+                let mut index = RevDepIndex::load(path)
+                    .map_err(|e| warn!("{}: failed loading revdeps index: {e}", self.repo))
+                    .ok()?;
+                if index.is_stale(&self.repo) {
+                    // invalid packages are flagged by the metadata check
+                    index.update(&self.repo, true).ok()?;
+                }
+                Some(index)
+            })
+            .as_ref()
+            .map(|index| index.cache())
+    }
+
     /// Return true if the run has a report variant enabled.
     pub(crate) fn enabled(&self, kind: ReportKind) -> bool {
         self.enabled.contains(&kind)
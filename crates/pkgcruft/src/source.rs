@@ -1,17 +1,21 @@
-use std::fmt;
 use std::str::FromStr;
+use std::{fmt, fs};
 
+use camino::{Utf8Path, Utf8PathBuf};
 use colored::{Color, Colorize};
 use indexmap::{IndexMap, IndexSet};
 use itertools::{Either, Itertools};
 use pkgcraft::dep::Cpv;
+use pkgcraft::eapi::Eapi;
 use pkgcraft::error::Error::InvalidPkg;
 use pkgcraft::pkg::Package;
 use pkgcraft::pkg::ebuild::{EbuildPkg, EbuildRawPkg, keyword::KeywordStatus};
+use pkgcraft::repo::ebuild::Eclass;
 use pkgcraft::repo::{EbuildRepo, PkgRepository};
 use pkgcraft::restrict::{self, Restrict, Restriction, Scope};
 use pkgcraft::types::OrderedMap;
 use strum::{AsRefStr, Display, EnumIter, IntoEnumIterator};
+use walkdir::WalkDir;
 
 use crate::error::Error;
 use crate::scan::ScannerRun;
@@ -25,6 +29,8 @@ pub enum SourceKind {
     EbuildRawPkg,
     Cpn,
     Category,
+    Eclass,
+    Profile,
     Repo,
 }
 
@@ -37,6 +43,8 @@ impl SourceKind {
             Self::EbuildRawPkg => Scope::Version,
             Self::Cpn => Scope::Package,
             Self::Category => Scope::Category,
+            Self::Eclass => Scope::Repo,
+            Self::Profile => Scope::Repo,
             Self::Repo => Scope::Repo,
         }
     }
@@ -338,6 +346,171 @@ impl Source for EbuildRawPkgSource {
     }
 }
 
+/// Source of eclasses provided by a repo, ignoring those inherited from masters.
+pub(crate) struct EclassSource {
+    repo: EbuildRepo,
+}
+
+impl EclassSource {
+    pub(crate) fn new(run: &ScannerRun) -> Self {
+        Self { repo: run.repo.clone() }
+    }
+}
+
+impl fmt::Display for EclassSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind().fmt(f)
+    }
+}
+
+impl Source for EclassSource {
+    type Item = Eclass;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Eclass
+    }
+
+    fn is_filtered(&self) -> bool {
+        false
+    }
+
+    fn iter_restrict<R: Into<Restrict>>(
+        &self,
+        _val: R,
+    ) -> impl Iterator<Item = Self::Item> + '_ {
+        self.repo.metadata().eclasses().iter().cloned()
+    }
+
+    fn iter_restrict_ordered<R: Into<Restrict>>(
+        &self,
+        val: R,
+    ) -> impl Iterator<Item = Self::Item> + '_ {
+        self.iter_restrict(val)
+    }
+}
+
+/// Profile directory from a repo's profiles directory.
+pub(crate) struct ProfileNode {
+    path: Utf8PathBuf,
+    abspath: Utf8PathBuf,
+    files: IndexSet<String>,
+}
+
+impl ProfileNode {
+    /// Return the node's path relative to the profiles directory.
+    pub(crate) fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Return the node's absolute path.
+    pub(crate) fn abspath(&self) -> &Utf8Path {
+        &self.abspath
+    }
+
+    /// Return true if the node is the root profiles directory, otherwise false.
+    pub(crate) fn is_root(&self) -> bool {
+        self.path.as_str().is_empty()
+    }
+
+    /// Return the names of all files in the node.
+    pub(crate) fn files(&self) -> &IndexSet<String> {
+        &self.files
+    }
+
+    /// Return the path for a file relative to the profiles directory.
+    pub(crate) fn relpath(&self, name: &str) -> Utf8PathBuf {
+        self.path.join(name)
+    }
+
+    /// Read a file from the node, returning an empty string if it doesn't exist.
+    pub(crate) fn read(&self, name: &str) -> String {
+        if self.files.contains(name) {
+            fs::read_to_string(self.abspath.join(name)).unwrap_or_default()
+        } else {
+            Default::default()
+        }
+    }
+
+    /// Return the EAPI used by the node, falling back to the repo EAPI.
+    pub(crate) fn eapi(&self, repo: &EbuildRepo) -> pkgcraft::Result<&'static Eapi> {
+        if self.files.contains("eapi") {
+            self.abspath.join("eapi").as_path().try_into()
+        } else {
+            Ok(repo.eapi())
+        }
+    }
+}
+
+/// Source of profile directories, ignoring non-profile data directories.
+pub(crate) struct ProfileSource {
+    repo: EbuildRepo,
+}
+
+impl ProfileSource {
+    pub(crate) fn new(run: &ScannerRun) -> Self {
+        Self { repo: run.repo.clone() }
+    }
+
+    /// Return the ordered iterator of all profile directories.
+    fn iter(&self) -> impl Iterator<Item = ProfileNode> + '_ {
+        let root = self.repo.path().join("profiles");
+        WalkDir::new(&root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                let name = e.file_name().to_str().unwrap_or_default();
+                !name.starts_with('.')
+                    && (e.depth() != 1 || !["desc", "templates", "updates"].contains(&name))
+            })
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_dir())
+            .filter_map(move |e| {
+                let abspath = Utf8Path::from_path(e.path())?.to_path_buf();
+                let path = abspath.strip_prefix(&root).ok()?.to_path_buf();
+                let files = fs::read_dir(&abspath)
+                    .ok()?
+                    .filter_map(Result::ok)
+                    .filter(|e| e.file_type().map(|x| x.is_file()).unwrap_or_default())
+                    .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                    .sorted()
+                    .collect();
+                Some(ProfileNode { path, abspath, files })
+            })
+    }
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind().fmt(f)
+    }
+}
+
+impl Source for ProfileSource {
+    type Item = ProfileNode;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Profile
+    }
+
+    fn is_filtered(&self) -> bool {
+        false
+    }
+
+    fn iter_restrict<R: Into<Restrict>>(
+        &self,
+        _val: R,
+    ) -> impl Iterator<Item = Self::Item> + '_ {
+        self.iter()
+    }
+
+    fn iter_restrict_ordered<R: Into<Restrict>>(
+        &self,
+        _val: R,
+    ) -> impl Iterator<Item = Self::Item> + '_ {
+        self.iter()
+    }
+}
+
 /// Cache used to avoid recreating package objects for package and version scope scans.
 #[derive(Debug)]
 pub(crate) struct PkgCache<T> {
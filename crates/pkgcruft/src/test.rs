@@ -1,7 +1,14 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use assert_cmd::Command;
 use glob::glob;
+use pkgcraft::cli::Targets;
+use pkgcraft::config::Config;
+use pkgcraft::repo::ebuild::EbuildTempRepo;
 
 use crate::report::{Iter, Report};
+use crate::scan::Scanner;
 
 pub trait ToReports {
     fn to_reports(&mut self) -> Result<Vec<Report>, String>;
@@ -59,6 +66,30 @@ macro_rules! glob_reports {
 }
 pub use glob_reports;
 
+/// Commit all files in a git repo, initializing the repo if it doesn't exist.
+///
+/// The commit time is backdated by the given number of days.
+pub fn git_commit<P: AsRef<Path>>(path: P, days: u64) {
+    let path = path.as_ref();
+    let repo = git2::Repository::open(path)
+        .or_else(|_| git2::Repository::init(path))
+        .unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let time = git2::Time::new(now.as_secs() as i64 - days as i64 * 86400, 0);
+    let sig = git2::Signature::new("pkgcruft", "pkgcruft@pkgcraft.test", &time).unwrap();
+    let parent = repo.head().ok().and_then(|x| x.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "commit", &tree, &parents)
+        .unwrap();
+}
+
 /// Verify two, ordered report iterables are equal.
 #[macro_export]
 macro_rules! assert_ordered_reports {
@@ -97,6 +128,24 @@ macro_rules! assert_unordered_reports {
 }
 pub use assert_unordered_reports;
 
+/// Scan a temporary ebuild repo, verifying the reports match the serialized reports.
+///
+/// The repo is reloaded from disk for each call so modifications are always scanned.
+pub fn assert_temp_repo_reports<'a, I>(scanner: &Scanner, temp: &EbuildTempRepo, expected: I)
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut config = Config::new("pkgcraft", "");
+    let repo = Targets::new(&mut config)
+        .repo_targets([temp.path()])
+        .unwrap()
+        .ebuild_repo()
+        .unwrap();
+    let reports = scanner.run(&repo, &repo).unwrap();
+    let expected = expected.into_iter().map(|s| Report::from_json(s).unwrap());
+    assert_unordered_reports!(reports, expected);
+}
+
 #[cfg(test)]
 mod tests {
     use pkgcraft::test::*;